@@ -0,0 +1,44 @@
+use hyper::{Body, Request, Response};
+use routerify::{Router, RouterService};
+use std::convert::Infallible;
+
+// A handler for "/" page.
+async fn home_handler(_: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(Response::new(Body::from("Home page")))
+}
+
+fn router() -> Router<Body, Infallible> {
+    Router::builder().get("/", home_handler).build().unwrap()
+}
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() {
+    use hyper::server::{accept, Server};
+    use tokio::net::UnixListener;
+
+    let path = std::env::temp_dir().join("routerify.sock");
+    let _ = std::fs::remove_file(&path);
+
+    // Accept the connections of the Unix domain socket, their requests have the `0.0.0.0:0` remote address.
+    let listener = UnixListener::bind(&path).unwrap();
+    let incoming = accept::poll_fn(move |cx| listener.poll_accept(cx).map(|res| Some(res.map(|(stream, _)| stream))));
+
+    // Create a Service from the router above to handle incoming requests.
+    let service = RouterService::new(router()).unwrap();
+
+    // Create a server by passing the created service to `.serve` method.
+    let server = Server::builder(incoming).serve(service);
+
+    println!("App is running on: {}", path.display());
+    println!("Try it with: curl --unix-socket {} http://localhost/", path.display());
+    if let Err(err) = server.await {
+        eprintln!("Server error: {}", err);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    drop(RouterService::new(router()).unwrap());
+    eprintln!("The Unix domain sockets are not supported on this platform");
+}
@@ -0,0 +1,56 @@
+use crate::HasStatusCode;
+use hyper::StatusCode;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+
+/// The error returned by the [`RequestBodyExt::body_form`](../ext/trait.RequestBodyExt.html#tymethod.body_form) method when
+/// the request body couldn't be read or deserialized.
+///
+/// It implements the [`HasStatusCode`](../trait.HasStatusCode.html) trait, so it can be registered by the
+/// [`RouterBuilder::error_status`](../struct.RouterBuilder.html#method.error_status) method to respond with a client error.
+#[derive(Debug)]
+pub enum BodyError {
+    /// The `Content-Type` header of the request is missing or it's not the expected one.
+    UnsupportedContentType {
+        /// The received content type, it's `None` if the header is missing.
+        content_type: Option<String>,
+    },
+    /// The request body couldn't be read, e.g. the connection was closed or the body exceeded the `max_size` of the route.
+    Read(hyper::Error),
+    /// The request body couldn't be deserialized into the requested type.
+    Invalid(Box<dyn StdError + Send + Sync + 'static>),
+}
+
+impl Display for BodyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::UnsupportedContentType {
+                content_type: Some(content_type),
+            } => {
+                write!(f, "Unsupported content type: {}", content_type)
+            }
+            BodyError::UnsupportedContentType { content_type: None } => write!(f, "Missing content type"),
+            BodyError::Read(err) => write!(f, "Couldn't read the request body: {}", err),
+            BodyError::Invalid(err) => write!(f, "Invalid request body: {}", err),
+        }
+    }
+}
+
+impl StdError for BodyError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            BodyError::UnsupportedContentType { .. } => None,
+            BodyError::Read(err) => Some(err),
+            BodyError::Invalid(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl HasStatusCode for BodyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BodyError::UnsupportedContentType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            BodyError::Read(_) | BodyError::Invalid(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::Error;
+use futures_core::Stream;
+use hyper::body::{Bytes, HttpBody};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+// A body wrapper which fails once more than the allowed number of bytes are read. It counts the decoded bytes, so it works
+// for the chunked bodies without the `Content-Length` header too. The rest of an oversized body is never polled.
+pub(crate) struct LimitedBody {
+    body: hyper::Body,
+    remaining: u64,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl LimitedBody {
+    // The `exceeded` flag is set once the limit is exceeded, so the caller can tell the reason of a failed read.
+    pub(crate) fn new(body: hyper::Body, max_size: u64, exceeded: Arc<AtomicBool>) -> LimitedBody {
+        LimitedBody {
+            body,
+            remaining: max_size,
+            exceeded,
+        }
+    }
+
+    pub(crate) fn into_body(self) -> hyper::Body {
+        hyper::Body::wrap_stream(self)
+    }
+}
+
+impl Stream for LimitedBody {
+    type Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.exceeded.load(Ordering::SeqCst) {
+            return Poll::Ready(None);
+        }
+
+        match Pin::new(&mut self.body).poll_data(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                let len = chunk.len() as u64;
+                if len > self.remaining {
+                    self.exceeded.store(true, Ordering::SeqCst);
+                    return Poll::Ready(Some(Err(Error::new("The request body is too large").into())));
+                }
+
+                self.remaining -= len;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
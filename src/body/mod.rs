@@ -0,0 +1,23 @@
+//! Helpers to work with the request and the response bodies.
+
+#[cfg(feature = "json")]
+pub use self::error::BodyError;
+pub(crate) use self::limit::LimitedBody;
+pub(crate) use self::prepend::PrependedBody;
+pub use self::reader::BodyReader;
+pub use self::stream::stream;
+#[cfg(feature = "multipart")]
+pub use multer::{Field, Multipart};
+
+/// The error type of the [`Multipart`](./struct.Multipart.html) parser.
+#[cfg(feature = "multipart")]
+pub type MultipartError = multer::Error;
+pub(crate) use self::timeout::TimeoutBody;
+
+#[cfg(feature = "json")]
+mod error;
+mod limit;
+mod prepend;
+mod reader;
+mod stream;
+mod timeout;
@@ -0,0 +1,35 @@
+use futures_core::Stream;
+use hyper::body::{Bytes, HttpBody};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// A body which yields an already polled chunk before the rest of the body, it's used to peek into a response body.
+pub(crate) struct PrependedBody {
+    first: Option<Result<Bytes, hyper::Error>>,
+    body: hyper::Body,
+}
+
+impl PrependedBody {
+    pub(crate) fn new(first: Result<Bytes, hyper::Error>, body: hyper::Body) -> PrependedBody {
+        PrependedBody {
+            first: Some(first),
+            body,
+        }
+    }
+
+    pub(crate) fn into_body(self) -> hyper::Body {
+        hyper::Body::wrap_stream(self)
+    }
+}
+
+impl Stream for PrependedBody {
+    type Item = Result<Bytes, hyper::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(first) = self.first.take() {
+            return Poll::Ready(Some(first));
+        }
+
+        Pin::new(&mut self.body).poll_data(cx)
+    }
+}
@@ -0,0 +1,59 @@
+use hyper::body::{Bytes, HttpBody};
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// An [`AsyncRead`](https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html) adapter over the request body.
+///
+/// It reads the body chunk by chunk as they arrive, so a large upload can be processed without buffering it in memory, e.g.
+/// it can be piped into a file via [`tokio::io::copy`](https://docs.rs/tokio/1/tokio/io/fn.copy.html).
+///
+/// It can be created by the [`RequestBodyExt::body_reader`](../ext/trait.RequestBodyExt.html#tymethod.body_reader) method.
+pub struct BodyReader {
+    body: hyper::Body,
+    chunk: Bytes,
+}
+
+impl BodyReader {
+    /// Creates a new reader over the specified body.
+    pub fn new(body: hyper::Body) -> BodyReader {
+        BodyReader {
+            body,
+            chunk: Bytes::new(),
+        }
+    }
+
+    /// Returns the inner body. Any data which is already polled but not read yet will be lost.
+    pub fn into_inner(self) -> hyper::Body {
+        self.body
+    }
+}
+
+impl AsyncRead for BodyReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            if !self.chunk.is_empty() {
+                let n = self.chunk.len().min(buf.remaining());
+                buf.put_slice(&self.chunk.split_to(n));
+                return Poll::Ready(Ok(()));
+            }
+
+            match Pin::new(&mut self.body).poll_data(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.chunk = chunk;
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(io::Error::other(err))),
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Debug for BodyReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ body: {:?}, buffered: {:?} }}", self.body, self.chunk.len())
+    }
+}
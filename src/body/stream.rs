@@ -0,0 +1,33 @@
+use futures_core::Stream;
+use hyper::body::Bytes;
+
+/// Creates a response body from a stream of chunks, so a route handler can respond with an arbitrary stream without
+/// setting up a channel. Every chunk is sent as soon as the stream yields it and an error aborts the response.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::body;
+/// use hyper::{body::Bytes, Response};
+/// use std::convert::Infallible;
+///
+/// # fn run() -> Router<hyper::Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/numbers", |_| async move {
+///         let chunks = (1..=3).map(|n| Ok::<_, Infallible>(Bytes::from(format!("{}\n", n))));
+///         Ok(Response::new(body::stream(futures::stream::iter(chunks))))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn stream<S, E>(s: S) -> hyper::Body
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    hyper::Body::wrap_stream(s)
+}
@@ -0,0 +1,63 @@
+use crate::Error;
+use futures_core::Stream;
+use hyper::body::{Bytes, HttpBody};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Instant, Sleep};
+
+// A body wrapper which fails if no data arrives within the timeout after the first read or the last chunk. The timer
+// starts on the first read, so a handler can do some work before reading the body.
+pub(crate) struct TimeoutBody {
+    body: hyper::Body,
+    timeout: Duration,
+    sleep: Option<Pin<Box<Sleep>>>,
+    timed_out: bool,
+}
+
+impl TimeoutBody {
+    pub(crate) fn new(body: hyper::Body, timeout: Duration) -> TimeoutBody {
+        TimeoutBody {
+            body,
+            timeout,
+            sleep: None,
+            timed_out: false,
+        }
+    }
+
+    pub(crate) fn into_body(self) -> hyper::Body {
+        hyper::Body::wrap_stream(self)
+    }
+}
+
+impl Stream for TimeoutBody {
+    type Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.timed_out {
+            return Poll::Ready(None);
+        }
+
+        let timeout = this.timeout;
+        let timer = this.sleep.get_or_insert_with(|| Box::pin(sleep(timeout)));
+
+        match Pin::new(&mut this.body).poll_data(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                timer.as_mut().reset(Instant::now() + timeout);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => match timer.as_mut().poll(cx) {
+                Poll::Ready(()) => {
+                    this.timed_out = true;
+                    let err = Error::new(format!("Timed out while reading the request body after {:?}", timeout));
+                    Poll::Ready(Some(Err(err.into())))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
@@ -1,14 +1,20 @@
 use http::Extensions;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub(crate) struct DataMap {
     inner: Extensions,
+    // The data shared by a name, so multiple values of the same type can be shared. It's keyed by the type first, so a
+    // lookup can borrow the name.
+    named: HashMap<TypeId, HashMap<String, Box<dyn Any + Send + Sync>>>,
 }
 
 impl DataMap {
     pub fn new() -> DataMap {
         DataMap {
             inner: Extensions::new(),
+            named: HashMap::new(),
         }
     }
 
@@ -19,4 +25,26 @@ impl DataMap {
     pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
         self.inner.get::<T>()
     }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.inner.remove::<T>()
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn insert_named<T: Send + Sync + 'static>(&mut self, name: String, val: T) {
+        self.named
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .insert(name, Box::new(val));
+    }
+
+    pub fn get_named<T: Send + Sync + 'static>(&self, name: &str) -> Option<&T> {
+        self.named
+            .get(&TypeId::of::<T>())
+            .and_then(|named| named.get(name))
+            .and_then(|val| val.downcast_ref::<T>())
+    }
 }
@@ -14,12 +14,16 @@ pub(crate) struct ScopedDataMap {
 }
 
 impl ScopedDataMap {
-    pub fn new<P: Into<String>>(path: P, data_map: Arc<DataMap>) -> crate::Result<ScopedDataMap> {
+    pub fn new<P: Into<String>>(
+        path: P,
+        data_map: Arc<DataMap>,
+        regex_size_limit: Option<usize>,
+    ) -> crate::Result<ScopedDataMap> {
         let path = path.into();
-        let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
+        let (re, _) = generate_exact_match_regex(path.as_str(), regex_size_limit).map_err(|e| {
             Error::new(format!(
-                "Could not create an exact match regex for the scoped data map path: {}",
-                e
+                "Could not create an exact match regex for the scoped data map path `{}`: {}",
+                path, e
             ))
         })?;
 
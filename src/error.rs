@@ -1,9 +1,91 @@
+use hyper::StatusCode;
 use std::error::Error as StdError;
 use std::fmt::{self, Debug, Display, Formatter};
 
 /// The error type used by the error handlers.
 pub type RouteError = Box<dyn StdError + Send + Sync + 'static>;
 
+/// Suggests the response status of an error, e.g. `400 Bad Request` for a validation error.
+///
+/// Register the error type by the [`RouterBuilder::error_status`](./struct.RouterBuilder.html#method.error_status) method,
+/// so the default error handler responds with this status instead of `500 Internal Server Error`. A custom error handler can
+/// read it by the [`RouteErrorExt::status_hint`](./trait.RouteErrorExt.html#tymethod.status_hint) method.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::HasStatusCode;
+/// use hyper::StatusCode;
+///
+/// #[derive(Debug)]
+/// struct ValidationError(String);
+///
+/// impl std::fmt::Display for ValidationError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "Invalid input: {}", self.0)
+///     }
+/// }
+///
+/// impl std::error::Error for ValidationError {}
+///
+/// impl HasStatusCode for ValidationError {
+///     fn status_code(&self) -> StatusCode {
+///         StatusCode::BAD_REQUEST
+///     }
+/// }
+/// ```
+pub trait HasStatusCode {
+    /// Returns the suggested response status for this error.
+    fn status_code(&self) -> StatusCode;
+}
+
+/// A extension trait which extends the [`RouteError`](./type.RouteError.html) type with some helpful methods.
+pub trait RouteErrorExt {
+    /// Returns the suggested status of the error if it's of the specified type or it's caused by an error of the specified
+    /// type. It returns `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{HasStatusCode, RouteError, RouteErrorExt};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # #[derive(Debug)]
+    /// # struct ValidationError;
+    /// # impl std::fmt::Display for ValidationError {
+    /// #     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "Invalid input") }
+    /// # }
+    /// # impl std::error::Error for ValidationError {}
+    /// # impl HasStatusCode for ValidationError {
+    /// #     fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
+    /// # }
+    /// async fn error_handler(err: RouteError) -> Response<Body> {
+    ///     let status = err.status_hint::<ValidationError>().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    ///
+    ///     let mut resp = Response::new(Body::from(err.to_string()));
+    ///     *resp.status_mut() = status;
+    ///     resp
+    /// }
+    /// ```
+    fn status_hint<T: HasStatusCode + StdError + 'static>(&self) -> Option<StatusCode>;
+}
+
+impl RouteErrorExt for RouteError {
+    fn status_hint<T: HasStatusCode + StdError + 'static>(&self) -> Option<StatusCode> {
+        let mut err: Option<&(dyn StdError + 'static)> = Some(self.as_ref());
+
+        while let Some(cur) = err {
+            if let Some(cur) = cur.downcast_ref::<T>() {
+                return Some(cur.status_code());
+            }
+            err = cur.source();
+        }
+
+        None
+    }
+}
+
 /// Simple string error for compatibility with Routerify v1.
 /// Can be used in return types of handlers and middleware.
 pub struct Error {
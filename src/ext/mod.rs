@@ -1,3 +1,5 @@
 pub use request::RequestExt;
+pub use request_body::RequestBodyExt;
 
 mod request;
+mod request_body;
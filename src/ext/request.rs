@@ -1,7 +1,11 @@
 use crate::data_map::SharedDataMap;
-use crate::types::{RequestContext, RequestMeta, RouteParams};
-use hyper::Request;
+use crate::helpers;
+use crate::types::{DisconnectToken, RequestContext, RequestMeta, ResponseHints, RouteParams};
+use hyper::header::{AsHeaderName, HeaderValue};
+use hyper::{Request, Uri};
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.14.4/hyper/struct.Request.html) and [`http::Parts`](https://docs.rs/http/0.2.4/http/request/struct.Parts.html) types with some helpful methods.
 pub trait RequestExt {
@@ -32,7 +36,8 @@ pub trait RequestExt {
     /// ```
     fn params(&self) -> &RouteParams;
 
-    /// It returns the route parameter value by the name of the parameter specified in the path.
+    /// It returns the route parameter value by the name of the parameter specified in the path. The value is percent
+    /// decoded, use the [`param_raw`](#tymethod.param_raw) method for the value as it appears in the request path.
     ///
     /// # Examples
     ///
@@ -58,6 +63,99 @@ pub trait RequestExt {
     /// ```
     fn param<P: Into<String>>(&self, param_name: P) -> Option<&String>;
 
+    /// It returns the route parameter value by the name of the parameter specified in the path as it appears in the
+    /// request path, i.e. without percent decoding. Refer to the
+    /// [`RouteParams::get_raw`](../struct.RouteParams.html#method.get_raw) method for more info.
+    fn param_raw<P: Into<String>>(&self, param_name: P) -> Option<&String>;
+
+    /// It returns all the capture groups of the matched route's regex in the order they appear in the path, e.g. the values
+    /// of the `:param` and `*` segments. It returns `None` if no route has been matched yet, e.g. inside a pre middleware.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userName/files/*", |req| async move {
+    ///         let captures = req.captures().unwrap();
+    ///         let user_name = &captures[0];
+    ///         let file_path = &captures[1];
+    ///
+    ///         Ok(Response::new(Body::from(format!("Username: {}, File: {}", user_name, file_path))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn captures(&self) -> Option<&[String]>;
+
+    /// It returns the part of the path matched by the last `*` segment of the matched route, without the trailing slash.
+    /// It returns `None` if the matched route has no `*` segment or no route has been matched yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/proxy/*", |req| async move {
+    ///         // For "/proxy/a/b/c", this is "a/b/c".
+    ///         let remainder = req.glob_remainder().unwrap_or_default().to_owned();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Forwarding to: {}", remainder))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn glob_remainder(&self) -> Option<&str>;
+
+    /// It returns the path prefix under which the matched route is mounted by the
+    /// [`RouterBuilder::scope`](../struct.RouterBuilder.html#method.scope) method, e.g. `/hello/:name`. The
+    /// [base path](../struct.RouterBuilder.html#method.base_path) of the top level router is included, like the prefix of
+    /// a scope. It returns `None` for the routes of the top level router without a base path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let greeting: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |req| async move {
+    ///         let prefix = req.scope_prefix().unwrap_or_default().to_owned();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Mounted at: {}", prefix))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .scope("/hello", greeting)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn scope_prefix(&self) -> Option<&str>;
+
     /// It returns the remote address of the incoming request.
     ///
     /// # Examples
@@ -83,12 +181,83 @@ pub trait RequestExt {
     /// ```
     fn remote_addr(&self) -> SocketAddr;
 
+    /// Returns the uri of the request as it was received, before the router normalized the path for matching and before
+    /// any pre middleware rewrote the uri, e.g. to verify a webhook signature over the raw request target.
+    ///
+    /// It returns the current uri if the request wasn't dispatched by the router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Request, Uri, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .middleware(Middleware::pre(|mut req: Request<Body>| async move {
+    ///         *req.uri_mut() = Uri::from_static("/v2/hooks");
+    ///         Ok(req)
+    ///     }))
+    ///     .post("/v2/hooks", |req| async move {
+    ///         let signed_target = req.original_uri().to_string();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Verifying the signature of {}", signed_target))))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn original_uri(&self) -> &Uri;
+
     /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
     /// [`data`](../struct.RouterBuilder.html#method.data).
     ///
     /// Please refer to the [Data and State Sharing](../index.html#data-and-state-sharing) for more info.
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T>;
 
+    /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
+    /// [`data_shared`](../struct.RouterBuilder.html#method.data_shared). The returned `Arc` can be moved into a spawned
+    /// task which outlives the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// struct Mailer;
+    ///
+    /// impl Mailer {
+    ///     async fn send_welcome_mail(&self) {}
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .data_shared(Mailer)
+    ///     .post("/signup", |req| async move {
+    ///         let mailer = req.data_arc::<Mailer>().unwrap();
+    ///         tokio::spawn(async move { mailer.send_welcome_mail().await });
+    ///
+    ///         Ok(Response::new(Body::from("Welcome")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn data_arc<T: Send + Sync + 'static>(&self) -> Option<Arc<T>>;
+
+    /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
+    /// [`named_data`](../struct.RouterBuilder.html#method.named_data) with the specified name.
+    fn named_data<T: Send + Sync + 'static>(&self, name: &str) -> Option<&T>;
+
     /// Access data in the request context.
     fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T>;
 
@@ -121,6 +290,323 @@ pub trait RequestExt {
     /// # run();
     /// ```
     fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T);
+
+    /// Removes the data of the specified type from the request context and returns it. It can be used to pass a one-shot
+    /// value, e.g. from a pre middleware to the route handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Request, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .middleware(Middleware::pre(|req: Request<Body>| async move {
+    ///         req.set_context("example".to_string());
+    ///
+    ///         Ok(req)
+    ///     }))
+    ///     .get("/hello", |req| async move {
+    ///         let text = req.take_context::<String>().unwrap();
+    ///         assert!(!req.has_context::<String>());
+    ///
+    ///         Ok(Response::new(Body::from(text)))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn take_context<T: Send + Sync + Clone + 'static>(&self) -> Option<T>;
+
+    /// Checks if the request context has data of the specified type.
+    fn has_context<T: Send + Sync + Clone + 'static>(&self) -> bool;
+
+    /// Hints the post middlewares that the response can be cached for the specified duration, e.g. the
+    /// [`cache_control`](../utility/middlewares/fn.cache_control.html) middleware sets the `Cache-Control` header from it.
+    ///
+    /// The hint is kept in the [`ResponseHints`](../struct.ResponseHints.html) of the request context, so it doesn't clash
+    /// with the other context data and it's read by the
+    /// [`RequestInfo::response_hints`](../struct.RequestInfo.html#method.response_hints) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use routerify::utility::middlewares::cache_control;
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .middleware(cache_control())
+    ///     .get("/catalog", |req| async move {
+    ///         req.hint_cache(Duration::from_secs(60));
+    ///         Ok(Response::new(Body::from("Catalog")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn hint_cache(&self, duration: Duration);
+
+    /// Returns a [`DisconnectToken`](../struct.DisconnectToken.html) which tells whether the client closed the
+    /// connection before the response was sent.
+    ///
+    /// The handler itself is dropped when the connection is closed, so the token is meant to be moved into the work
+    /// which outlives it, e.g. a spawned task. It returns a token which never disconnects if the request wasn't
+    /// dispatched by the router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/report", |req| async move {
+    ///         let token = req.disconnect_token();
+    ///
+    ///         tokio::spawn(async move {
+    ///             for _part in 0..10 {
+    ///                 if token.is_disconnected() {
+    ///                     return;
+    ///                 }
+    ///                 tokio::time::sleep(Duration::from_millis(100)).await;
+    ///             }
+    ///         });
+    ///
+    ///         Ok(Response::new(Body::from("Report is being built")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn disconnect_token(&self) -> DisconnectToken;
+
+    /// Returns the tags attached to the matched route by the
+    /// [`RouterBuilder::get_tagged`](../struct.RouterBuilder.html#method.get_tagged) method, e.g. to enforce the
+    /// permissions in an authorization middleware. It's empty if the route has no tags or no route is matched.
+    ///
+    /// The pre middlewares see the tags of the route which matches the path and the method of the request before they run.
+    fn route_tags(&self) -> &[String];
+
+    /// It returns the scheme of the original request, e.g. `http` or `https`.
+    ///
+    /// If the router is configured to [trust the proxy headers](../struct.RouterBuilder.html#method.trust_proxy_headers),
+    /// the `X-Forwarded-Proto` header is respected, otherwise the scheme is taken from the request uri which is usually
+    /// absent for the requests received by a server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .trust_proxy_headers(true)
+    ///     .get("/hello", |req| async move {
+    ///         let scheme = req.scheme().unwrap_or("http");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Scheme: {}", scheme))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn scheme(&self) -> Option<&str>;
+
+    /// Checks if the original request was made over a secure connection, i.e. the [scheme](#tymethod.scheme) is `https`.
+    fn is_secure(&self) -> bool;
+
+    /// Returns the value of the specified request header. If there are multiple values, the first one is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{header, Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/hello", |req| async move {
+    ///         let has_etag = req.header(header::IF_NONE_MATCH).is_some();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Has ETag: {}", has_etag))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&HeaderValue>;
+
+    /// Returns the value of the specified request header as a string. It returns `None` if the header is missing or its
+    /// value contains non-visible ASCII characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/hello", |req| async move {
+    ///         let user_agent = req.header_str("user-agent").unwrap_or("unknown");
+    ///
+    ///         Ok(Response::new(Body::from(format!("User agent: {}", user_agent))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn header_str<K: AsHeaderName>(&self, name: K) -> Option<&str>;
+
+    /// Returns the token of the `Authorization: Bearer <token>` header. It returns `None` if the header is missing, it has
+    /// another scheme or the token is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/account", |req| async move {
+    ///         match req.bearer_token() {
+    ///             Some(token) => Ok(Response::new(Body::from(format!("Token: {}", token)))),
+    ///             None => Ok(Response::builder().status(StatusCode::UNAUTHORIZED).body(Body::empty()).unwrap()),
+    ///         }
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn bearer_token(&self) -> Option<&str>;
+
+    /// Returns the user id and the password of the `Authorization: Basic <credentials>` header. It returns `None` if the
+    /// header is missing, it has another scheme or the credentials aren't a base64 encoded `user-id:password` pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/admin", |req| async move {
+    ///         match req.basic_auth() {
+    ///             Some((user, password)) if user == "admin" && password == "secret" => {
+    ///                 Ok(Response::new(Body::from("Admin page")))
+    ///             }
+    ///             _ => Ok(Response::builder()
+    ///                 .status(StatusCode::UNAUTHORIZED)
+    ///                 .header("www-authenticate", "Basic realm=\"admin\"")
+    ///                 .body(Body::empty())
+    ///                 .unwrap()),
+    ///         }
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn basic_auth(&self) -> Option<(String, String)>;
+
+    /// It parses and returns the `Content-Type` header value of the request. It returns `None` if the header is missing or
+    /// it's not a valid mime type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .post("/users", |req| async move {
+    ///         let is_json = req
+    ///             .content_type()
+    ///             .map(|mime| mime.essence_str() == "application/json")
+    ///             .unwrap_or(false);
+    ///
+    ///         Ok(Response::new(Body::from(format!("JSON body: {}", is_json))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "mime")]
+    fn content_type(&self) -> Option<mime::Mime>;
+
+    /// Checks if the specified mime type is acceptable to the client as per the `Accept` header of the request. The wildcards
+    /// e.g. `*/*` and `text/*` are respected. A missing `Accept` header means the client accepts any type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users", |req| async move {
+    ///         if req.accepts(&mime::APPLICATION_JSON) {
+    ///             Ok(Response::new(Body::from("[]")))
+    ///         } else {
+    ///             Ok(Response::new(Body::from("No users")))
+    ///         }
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "mime")]
+    fn accepts(&self, mime: &mime::Mime) -> bool;
 }
 
 fn params(ext: &http::Extensions) -> &RouteParams {
@@ -130,7 +616,27 @@ fn params(ext: &http::Extensions) -> &RouteParams {
 }
 
 fn param<P: Into<String>>(ext: &http::Extensions, param_name: P) -> Option<&String> {
-    params(ext).get(&param_name.into())
+    params(ext).get(param_name.into())
+}
+
+fn param_raw<P: Into<String>>(ext: &http::Extensions, param_name: P) -> Option<&String> {
+    params(ext).get_raw(param_name.into())
+}
+
+fn captures(ext: &http::Extensions) -> Option<&[String]> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.captures())
+}
+
+fn glob_remainder(ext: &http::Extensions) -> Option<&str> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.glob_remainder())
+}
+
+fn scope_prefix(ext: &http::Extensions) -> Option<&str> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.scope_prefix())
+}
+
+fn original_uri(ext: &http::Extensions) -> Option<&Uri> {
+    ext.get::<RequestMeta>().and_then(|meta| meta.original_uri())
 }
 
 fn remote_addr(ext: &http::Extensions) -> SocketAddr {
@@ -154,6 +660,24 @@ fn data<T: Send + Sync + 'static>(ext: &http::Extensions) -> Option<&T> {
     None
 }
 
+fn named_data<'a, T: Send + Sync + 'static>(ext: &'a http::Extensions, name: &str) -> Option<&'a T> {
+    let shared_data_maps = ext.get::<Vec<SharedDataMap>>();
+
+    if let Some(shared_data_maps) = shared_data_maps {
+        for shared_data_map in shared_data_maps.iter() {
+            if let Some(data) = shared_data_map.inner.get_named::<T>(name) {
+                return Some(data);
+            }
+        }
+    }
+
+    None
+}
+
+fn data_arc<T: Send + Sync + 'static>(ext: &http::Extensions) -> Option<Arc<T>> {
+    data::<Arc<T>>(ext).cloned()
+}
+
 fn context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions) -> Option<T> {
     let ctx = ext.get::<RequestContext>().expect("Context must be present");
     ctx.get::<T>()
@@ -164,6 +688,122 @@ fn set_context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions, val: T)
     ctx.set(val)
 }
 
+fn take_context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions) -> Option<T> {
+    let ctx = ext.get::<RequestContext>().expect("Context must be present");
+    ctx.remove::<T>()
+}
+
+fn has_context<T: Send + Sync + Clone + 'static>(ext: &http::Extensions) -> bool {
+    let ctx = ext.get::<RequestContext>().expect("Context must be present");
+    ctx.contains::<T>()
+}
+
+fn hint_cache(ext: &http::Extensions, duration: Duration) {
+    let ctx = ext.get::<RequestContext>().expect("Context must be present");
+    let mut hints = ctx.get::<ResponseHints>().unwrap_or_default();
+    hints.set_cache_for(duration);
+    ctx.set(hints)
+}
+
+fn route_tags(ext: &http::Extensions) -> &[String] {
+    ext.get::<RequestMeta>()
+        .map(|meta| meta.route_tags())
+        .unwrap_or_default()
+}
+
+fn disconnect_token(ext: &http::Extensions) -> DisconnectToken {
+    ext.get::<DisconnectToken>().cloned().unwrap_or_default()
+}
+
+fn scheme<'a>(ext: &http::Extensions, headers: &'a hyper::HeaderMap, uri: &'a hyper::Uri) -> Option<&'a str> {
+    let trust_proxy_headers = ext
+        .get::<RequestMeta>()
+        .map(|meta| meta.trust_proxy_headers())
+        .unwrap_or(false);
+
+    if trust_proxy_headers {
+        let forwarded_proto = headers
+            .get("x-forwarded-proto")
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.split(',').next())
+            .map(|val| val.trim())
+            .filter(|val| !val.is_empty());
+
+        if forwarded_proto.is_some() {
+            return forwarded_proto;
+        }
+    }
+
+    uri.scheme_str()
+}
+
+fn is_secure(scheme: Option<&str>) -> bool {
+    scheme
+        .map(|scheme| scheme.eq_ignore_ascii_case("https"))
+        .unwrap_or(false)
+}
+
+fn header_str<K: AsHeaderName>(headers: &hyper::HeaderMap, name: K) -> Option<&str> {
+    headers.get(name).and_then(|val| val.to_str().ok())
+}
+
+// Returns the credentials of the `Authorization` header if it has the specified scheme, the scheme is case insensitive.
+fn auth_credentials<'a>(headers: &'a hyper::HeaderMap, scheme: &str) -> Option<&'a str> {
+    let (auth_scheme, credentials) = header_str(headers, hyper::header::AUTHORIZATION)?
+        .trim()
+        .split_once(' ')?;
+    let credentials = credentials.trim_start();
+
+    if auth_scheme.eq_ignore_ascii_case(scheme) && !credentials.is_empty() {
+        Some(credentials)
+    } else {
+        None
+    }
+}
+
+fn bearer_token(headers: &hyper::HeaderMap) -> Option<&str> {
+    auth_credentials(headers, "Bearer").filter(|token| !token.contains(char::is_whitespace))
+}
+
+fn basic_auth(headers: &hyper::HeaderMap) -> Option<(String, String)> {
+    let decoded = helpers::decode_base64(auth_credentials(headers, "Basic")?)?;
+    let credentials = String::from_utf8(decoded).ok()?;
+    let (user_id, password) = credentials.split_once(':')?;
+    Some((user_id.to_owned(), password.to_owned()))
+}
+
+#[cfg(feature = "mime")]
+fn content_type(headers: &hyper::HeaderMap) -> Option<mime::Mime> {
+    headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<mime::Mime>().ok())
+}
+
+#[cfg(feature = "mime")]
+fn accepts(headers: &hyper::HeaderMap, mime: &mime::Mime) -> bool {
+    let mut accept_headers = headers.get_all(hyper::header::ACCEPT).iter().peekable();
+
+    if accept_headers.peek().is_none() {
+        return true;
+    }
+
+    accept_headers
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .filter_map(|val| val.trim().parse::<mime::Mime>().ok())
+        .filter(|accepted| {
+            accepted
+                .get_param("q")
+                .map(|q| q.as_str().parse::<f32>().map(|q| q > 0.0).unwrap_or(true))
+                .unwrap_or(true)
+        })
+        .any(|accepted| {
+            (accepted.type_() == mime::STAR || accepted.type_() == mime.type_())
+                && (accepted.subtype() == mime::STAR || accepted.subtype() == mime.subtype())
+        })
+}
+
 impl RequestExt for Request<hyper::Body> {
     fn params(&self) -> &RouteParams {
         params(self.extensions())
@@ -173,14 +813,42 @@ impl RequestExt for Request<hyper::Body> {
         param(self.extensions(), param_name)
     }
 
+    fn param_raw<P: Into<String>>(&self, param_name: P) -> Option<&String> {
+        param_raw(self.extensions(), param_name)
+    }
+
+    fn captures(&self) -> Option<&[String]> {
+        captures(self.extensions())
+    }
+
+    fn glob_remainder(&self) -> Option<&str> {
+        glob_remainder(self.extensions())
+    }
+
+    fn scope_prefix(&self) -> Option<&str> {
+        scope_prefix(self.extensions())
+    }
+
     fn remote_addr(&self) -> SocketAddr {
         remote_addr(self.extensions())
     }
 
+    fn original_uri(&self) -> &Uri {
+        original_uri(self.extensions()).unwrap_or_else(|| self.uri())
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(self.extensions())
     }
 
+    fn data_arc<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        data_arc(self.extensions())
+    }
+
+    fn named_data<T: Send + Sync + 'static>(&self, name: &str) -> Option<&T> {
+        named_data(self.extensions(), name)
+    }
+
     fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         context(self.extensions())
     }
@@ -188,6 +856,60 @@ impl RequestExt for Request<hyper::Body> {
     fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T) {
         set_context(self.extensions(), val)
     }
+
+    fn take_context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
+        take_context(self.extensions())
+    }
+
+    fn has_context<T: Send + Sync + Clone + 'static>(&self) -> bool {
+        has_context::<T>(self.extensions())
+    }
+
+    fn hint_cache(&self, duration: Duration) {
+        hint_cache(self.extensions(), duration)
+    }
+
+    fn disconnect_token(&self) -> DisconnectToken {
+        disconnect_token(self.extensions())
+    }
+
+    fn route_tags(&self) -> &[String] {
+        route_tags(self.extensions())
+    }
+
+    fn scheme(&self) -> Option<&str> {
+        scheme(self.extensions(), self.headers(), self.uri())
+    }
+
+    fn is_secure(&self) -> bool {
+        is_secure(self.scheme())
+    }
+
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&HeaderValue> {
+        self.headers().get(name)
+    }
+
+    fn header_str<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        header_str(self.headers(), name)
+    }
+
+    fn bearer_token(&self) -> Option<&str> {
+        bearer_token(self.headers())
+    }
+
+    fn basic_auth(&self) -> Option<(String, String)> {
+        basic_auth(self.headers())
+    }
+
+    #[cfg(feature = "mime")]
+    fn content_type(&self) -> Option<mime::Mime> {
+        content_type(self.headers())
+    }
+
+    #[cfg(feature = "mime")]
+    fn accepts(&self, mime: &mime::Mime) -> bool {
+        accepts(self.headers(), mime)
+    }
 }
 
 impl RequestExt for http::request::Parts {
@@ -199,14 +921,42 @@ impl RequestExt for http::request::Parts {
         param(&self.extensions, param_name)
     }
 
+    fn param_raw<P: Into<String>>(&self, param_name: P) -> Option<&String> {
+        param_raw(&self.extensions, param_name)
+    }
+
+    fn captures(&self) -> Option<&[String]> {
+        captures(&self.extensions)
+    }
+
+    fn glob_remainder(&self) -> Option<&str> {
+        glob_remainder(&self.extensions)
+    }
+
+    fn scope_prefix(&self) -> Option<&str> {
+        scope_prefix(&self.extensions)
+    }
+
     fn remote_addr(&self) -> SocketAddr {
         remote_addr(&self.extensions)
     }
 
+    fn original_uri(&self) -> &Uri {
+        original_uri(&self.extensions).unwrap_or(&self.uri)
+    }
+
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
         data(&self.extensions)
     }
 
+    fn data_arc<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        data_arc(&self.extensions)
+    }
+
+    fn named_data<T: Send + Sync + 'static>(&self, name: &str) -> Option<&T> {
+        named_data(&self.extensions, name)
+    }
+
     fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         context(&self.extensions)
     }
@@ -214,4 +964,114 @@ impl RequestExt for http::request::Parts {
     fn set_context<T: Send + Sync + Clone + 'static>(&self, val: T) {
         set_context(&self.extensions, val)
     }
+
+    fn take_context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
+        take_context(&self.extensions)
+    }
+
+    fn has_context<T: Send + Sync + Clone + 'static>(&self) -> bool {
+        has_context::<T>(&self.extensions)
+    }
+
+    fn hint_cache(&self, duration: Duration) {
+        hint_cache(&self.extensions, duration)
+    }
+
+    fn disconnect_token(&self) -> DisconnectToken {
+        disconnect_token(&self.extensions)
+    }
+
+    fn route_tags(&self) -> &[String] {
+        route_tags(&self.extensions)
+    }
+
+    fn scheme(&self) -> Option<&str> {
+        scheme(&self.extensions, &self.headers, &self.uri)
+    }
+
+    fn is_secure(&self) -> bool {
+        is_secure(self.scheme())
+    }
+
+    fn header<K: AsHeaderName>(&self, name: K) -> Option<&HeaderValue> {
+        self.headers.get(name)
+    }
+
+    fn header_str<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        header_str(&self.headers, name)
+    }
+
+    fn bearer_token(&self) -> Option<&str> {
+        bearer_token(&self.headers)
+    }
+
+    fn basic_auth(&self) -> Option<(String, String)> {
+        basic_auth(&self.headers)
+    }
+
+    #[cfg(feature = "mime")]
+    fn content_type(&self) -> Option<mime::Mime> {
+        content_type(&self.headers)
+    }
+
+    #[cfg(feature = "mime")]
+    fn accepts(&self, mime: &mime::Mime) -> bool {
+        accepts(&self.headers, mime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+
+    #[test]
+    fn test_header() {
+        let req = Request::builder()
+            .header("x-token", "abc")
+            .header("x-binary", HeaderValue::from_bytes(b"caf\xe9").unwrap())
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(req.header("x-token").unwrap(), "abc");
+        assert_eq!(req.header_str("x-token"), Some("abc"));
+
+        assert!(req.header("x-missing").is_none());
+        assert!(req.header_str("x-missing").is_none());
+
+        assert_eq!(req.header("x-binary").unwrap().as_bytes(), b"caf\xe9");
+        assert!(req.header_str("x-binary").is_none());
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn test_content_type() {
+        let req = Request::builder()
+            .header("content-type", "application/json; charset=utf-8")
+            .body(Body::empty())
+            .unwrap();
+        let content_type = req.content_type().unwrap();
+        assert_eq!(content_type.essence_str(), "application/json");
+        assert_eq!(content_type.get_param(mime::CHARSET).unwrap(), mime::UTF_8);
+
+        let req = Request::builder().body(Body::empty()).unwrap();
+        assert!(req.content_type().is_none());
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn test_accepts() {
+        let req = Request::builder().header("accept", "*/*").body(Body::empty()).unwrap();
+        assert!(req.accepts(&mime::APPLICATION_JSON));
+        assert!(req.accepts(&mime::TEXT_HTML));
+
+        let req = Request::builder()
+            .header("accept", "text/*, application/json;q=0")
+            .body(Body::empty())
+            .unwrap();
+        assert!(req.accepts(&mime::TEXT_HTML));
+        assert!(!req.accepts(&mime::APPLICATION_JSON));
+
+        let req = Request::builder().body(Body::empty()).unwrap();
+        assert!(req.accepts(&mime::APPLICATION_JSON));
+    }
 }
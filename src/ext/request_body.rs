@@ -0,0 +1,173 @@
+#[cfg(feature = "json")]
+use crate::body::BodyError;
+use crate::body::BodyReader;
+#[cfg(feature = "multipart")]
+use crate::body::{Multipart, MultipartError};
+use hyper::Request;
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "json")]
+use std::future::Future;
+#[cfg(feature = "json")]
+use std::pin::Pin;
+
+/// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.14.4/hyper/struct.Request.html) type with
+/// some helpful methods to consume the request body.
+pub trait RequestBodyExt {
+    /// It converts the request body into a [`BodyReader`](../body/struct.BodyReader.html) which implements the
+    /// [`AsyncRead`](https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html) trait, so that a large upload can be processed
+    /// without buffering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestBodyExt;
+    /// use hyper::{Response, Body};
+    /// use tokio::io::AsyncReadExt;
+    ///
+    /// # fn run() -> Router<Body, std::io::Error> {
+    /// let router = Router::builder()
+    ///     .post("/upload", |req| async move {
+    ///         let mut reader = req.body_reader();
+    ///         let mut buf = [0; 1024];
+    ///         let mut total = 0;
+    ///
+    ///         loop {
+    ///             let n = reader.read(&mut buf).await?;
+    ///             if n == 0 {
+    ///                 break;
+    ///             }
+    ///             total += n;
+    ///         }
+    ///
+    ///         Ok(Response::new(Body::from(format!("Received {} bytes", total))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn body_reader(self) -> BodyReader;
+
+    /// It parses the boundary from the `Content-Type` header and converts the request body into a
+    /// [`Multipart`](../body/struct.Multipart.html) stream which yields the fields and the files of a
+    /// `multipart/form-data` body without buffering. It returns an error if the request is not a multipart request.
+    ///
+    /// The body is still subject to the `max_size` limit of the route, a larger body fails the stream and the request is
+    /// rejected with `413 Payload Too Large`. It's available only if the `multipart` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestBodyExt;
+    /// use hyper::{Response, Body};
+    ///
+    /// # fn run() -> Router<Body, routerify::body::MultipartError> {
+    /// let router = Router::builder()
+    ///     .post("/upload", |req| async move {
+    ///         let mut multipart = req.multipart()?;
+    ///         let mut files = Vec::new();
+    ///
+    ///         while let Some(field) = multipart.next_field().await? {
+    ///             if let Some(file_name) = field.file_name() {
+    ///                 files.push(file_name.to_owned());
+    ///             }
+    ///         }
+    ///
+    ///         Ok(Response::new(Body::from(format!("Uploaded: {}", files.join(", ")))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "multipart")]
+    fn multipart(self) -> Result<Multipart<'static>, MultipartError>;
+
+    /// It reads the whole `application/x-www-form-urlencoded` request body and deserializes it into the specified type. It
+    /// returns an error if the request has a different content type.
+    ///
+    /// The body is still subject to the `max_size` limit of the route, a larger body fails the reading and the request is
+    /// rejected with `413 Payload Too Large`. It's available only if the `json` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::body::BodyError;
+    /// use routerify::ext::RequestBodyExt;
+    /// use hyper::{Response, Body};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Login {
+    ///     username: String,
+    /// }
+    ///
+    /// # fn run() -> Router<Body, BodyError> {
+    /// let router = Router::builder()
+    ///     .error_status::<BodyError>()
+    ///     .post("/login", |req| async move {
+    ///         let login: Login = req.body_form().await?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("Welcome {}", login.username))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "json")]
+    #[allow(clippy::type_complexity)]
+    fn body_form<T: DeserializeOwned + Send + 'static>(
+        self,
+    ) -> Pin<Box<dyn Future<Output = Result<T, BodyError>> + Send + 'static>>;
+}
+
+impl RequestBodyExt for Request<hyper::Body> {
+    fn body_reader(self) -> BodyReader {
+        BodyReader::new(self.into_body())
+    }
+
+    #[cfg(feature = "multipart")]
+    fn multipart(self) -> Result<Multipart<'static>, MultipartError> {
+        let boundary = self
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+            .ok_or(MultipartError::NoMultipart)
+            .and_then(multer::parse_boundary)?;
+
+        Ok(Multipart::new(self.into_body(), boundary))
+    }
+
+    #[cfg(feature = "json")]
+    fn body_form<T: DeserializeOwned + Send + 'static>(
+        self,
+    ) -> Pin<Box<dyn Future<Output = Result<T, BodyError>> + Send + 'static>> {
+        let content_type = self
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .map(|val| String::from_utf8_lossy(val.as_bytes()).into_owned());
+        let body = self.into_body();
+
+        Box::pin(async move {
+            let is_form = content_type
+                .as_deref()
+                .and_then(|content_type| content_type.split(';').next())
+                .map(|essence| essence.trim().eq_ignore_ascii_case("application/x-www-form-urlencoded"))
+                .unwrap_or(false);
+            if !is_form {
+                return Err(BodyError::UnsupportedContentType { content_type });
+            }
+
+            let bytes = hyper::body::to_bytes(body).await.map_err(BodyError::Read)?;
+            serde_urlencoded::from_bytes::<T>(&bytes).map_err(|e| BodyError::Invalid(e.into()))
+        })
+    }
+}
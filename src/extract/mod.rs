@@ -0,0 +1,247 @@
+//! Minimal extractors which let the route handlers take typed arguments instead of the raw request.
+//!
+//! A handler which takes the extractors as arguments can be registered by the
+//! [`RouterBuilder::get_ext`](../struct.RouterBuilder.html#method.get_ext) and the
+//! [`RouterBuilder::add_ext`](../struct.RouterBuilder.html#method.add_ext) methods. If an argument can't be extracted,
+//! the handler is not called and the request is rejected with the status of the [`ExtractError`](./struct.ExtractError.html),
+//! e.g. `400 Bad Request` for an invalid route parameter.
+//!
+//! The [`Query`](./struct.Query.html) and the [`Json`](./struct.Json.html) extractors are available only if the `json`
+//! feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::Router;
+//! use routerify::extract::Path;
+//! use hyper::{Response, Body};
+//! use std::convert::Infallible;
+//!
+//! async fn book_handler(Path((user_id, book_id)): Path<(u32, u32)>) -> Result<Response<Body>, Infallible> {
+//!     Ok(Response::new(Body::from(format!("User: {}, Book: {}", user_id, book_id))))
+//! }
+//!
+//! # fn run() -> Router<Body, Infallible> {
+//! let router = Router::builder()
+//!     .get_ext("/users/:userId/books/:bookId", book_handler)
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::ext::RequestExt;
+use crate::utility::response::reject_with;
+use crate::{HasStatusCode, State};
+use hyper::{Body, Request, Response, StatusCode};
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+use std::any::type_name;
+use std::fmt::{self, Display, Formatter};
+use std::future::{ready, Future};
+use std::pin::Pin;
+use std::str::FromStr;
+
+/// The boxed future returned by the [`FromRequest::from_request`](./trait.FromRequest.html#tymethod.from_request) method.
+pub type ExtractFuture<T> = Pin<Box<dyn Future<Output = Result<T, ExtractError>> + Send + 'static>>;
+
+/// A type which can be extracted from the request as an argument of a handler.
+pub trait FromRequest: Sized + Send + 'static {
+    /// Extracts the value from the request. An extractor which needs the request body takes it out of the request, so
+    /// only one argument of a handler can consume the body.
+    fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self>;
+}
+
+/// A handler which takes the extractors as arguments, it's implemented for the functions which take up to four
+/// [`FromRequest`](./trait.FromRequest.html) arguments.
+pub trait ExtractHandler<Args, E>: Send + Sync + 'static {
+    /// The future returned by the handler.
+    type Future: Future<Output = Result<Response<Body>, E>> + Send + 'static;
+
+    /// Calls the handler with the extracted arguments.
+    fn call(&self, args: Args) -> Self::Future;
+}
+
+/// The error which rejects a request when an argument of the handler can't be extracted.
+#[derive(Debug, Clone)]
+pub struct ExtractError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ExtractError {
+    /// Creates an error with the status of the rejection response and a message which is used as its body.
+    pub fn new<M: Into<String>>(status: StatusCode, message: M) -> ExtractError {
+        ExtractError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the status of the rejection response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the error message.
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    /// Converts the error into a plain text rejection response.
+    pub fn into_response(self) -> Response<Body> {
+        reject_with(self.status, self.message)
+    }
+}
+
+impl Display for ExtractError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl HasStatusCode for ExtractError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+}
+
+/// Extracts the route parameters in the order they appear in the route path, e.g. the `Path<(u32, String)>` argument
+/// takes the first two parameters. Each of them is parsed by its `FromStr` implementation.
+#[derive(Debug, Clone)]
+pub struct Path<T>(pub T);
+
+/// The tuples which can be parsed from the route parameters by the [`Path`](./struct.Path.html) extractor.
+pub trait FromCaptures: Sized {
+    /// Parses the value from the route parameters in the order they appear in the route path.
+    fn from_captures(captures: &[String]) -> Result<Self, ExtractError>;
+}
+
+macro_rules! impl_from_captures {
+    ($len:expr, $($ty:ident => $idx:tt),+) => {
+        impl<$($ty),+> FromCaptures for ($($ty,)+)
+        where
+            $($ty: FromStr, $ty::Err: Display),+
+        {
+            fn from_captures(captures: &[String]) -> Result<Self, ExtractError> {
+                if captures.len() < $len {
+                    return Err(ExtractError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Expected {} route parameters, but the route has {}", $len, captures.len()),
+                    ));
+                }
+
+                Ok(($(
+                    captures[$idx].parse::<$ty>().map_err(|e| {
+                        ExtractError::new(
+                            StatusCode::BAD_REQUEST,
+                            format!("Invalid route parameter {:?}: {}", captures[$idx], e),
+                        )
+                    })?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_captures!(1, T1 => 0);
+impl_from_captures!(2, T1 => 0, T2 => 1);
+impl_from_captures!(3, T1 => 0, T2 => 1, T3 => 2);
+impl_from_captures!(4, T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+
+impl<T: FromCaptures + Send + 'static> FromRequest for Path<T> {
+    fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self> {
+        let path = T::from_captures(req.captures().unwrap_or_default()).map(Path);
+        Box::pin(ready(path))
+    }
+}
+
+/// Extracts the query string which is deserialized into the specified type.
+///
+/// It's available only if the `json` feature is enabled.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub struct Query<T>(pub T);
+
+#[cfg(feature = "json")]
+impl<T: DeserializeOwned + Send + 'static> FromRequest for Query<T> {
+    fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self> {
+        let query = serde_urlencoded::from_str::<T>(req.uri().query().unwrap_or_default())
+            .map(Query)
+            .map_err(|e| ExtractError::new(StatusCode::BAD_REQUEST, format!("Invalid query string: {}", e)));
+        Box::pin(ready(query))
+    }
+}
+
+/// Extracts the JSON request body which is deserialized into the specified type. It consumes the request body.
+///
+/// It's available only if the `json` feature is enabled.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub struct Json<T>(pub T);
+
+#[cfg(feature = "json")]
+impl<T: DeserializeOwned + Send + 'static> FromRequest for Json<T> {
+    fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self> {
+        let body = std::mem::take(req.body_mut());
+
+        Box::pin(async move {
+            let bytes = hyper::body::to_bytes(body).await.map_err(|e| {
+                ExtractError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Couldn't read the request body: {}", e),
+                )
+            })?;
+
+            serde_json::from_slice::<T>(&bytes)
+                .map(Json)
+                .map_err(|e| ExtractError::new(StatusCode::BAD_REQUEST, format!("Invalid JSON body: {}", e)))
+        })
+    }
+}
+
+impl<S: Send + Sync + 'static> FromRequest for State<S> {
+    fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self> {
+        let state = req.data::<State<S>>().cloned().ok_or_else(|| {
+            ExtractError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("The state of type `{}` is not set", type_name::<S>()),
+            )
+        });
+        Box::pin(ready(state))
+    }
+}
+
+macro_rules! impl_extract_handler {
+    ($($ty:ident => $arg:ident),+) => {
+        impl<$($ty),+> FromRequest for ($($ty,)+)
+        where
+            $($ty: FromRequest),+
+        {
+            fn from_request(req: &mut Request<Body>) -> ExtractFuture<Self> {
+                $(let $arg = $ty::from_request(req);)+
+                Box::pin(async move { Ok(($($arg.await?,)+)) })
+            }
+        }
+
+        impl<F, R, E, $($ty),+> ExtractHandler<($($ty,)+), E> for F
+        where
+            F: Fn($($ty),+) -> R + Send + Sync + 'static,
+            R: Future<Output = Result<Response<Body>, E>> + Send + 'static,
+        {
+            type Future = R;
+
+            fn call(&self, ($($arg,)+): ($($ty,)+)) -> R {
+                self($($arg),+)
+            }
+        }
+    };
+}
+
+impl_extract_handler!(T1 => t1);
+impl_extract_handler!(T1 => t1, T2 => t2);
+impl_extract_handler!(T1 => t1, T2 => t2, T3 => t3);
+impl_extract_handler!(T1 => t1, T2 => t2, T3 => t3, T4 => t4);
@@ -1,7 +1,12 @@
 use crate::types::RequestMeta;
+use crate::types::RouteParams;
 use crate::Error;
 use http::Extensions;
-use percent_encoding::percent_decode_str;
+use hyper::{header, Response, StatusCode, Uri};
+use lazy_static::lazy_static;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use regex::{Captures, Regex};
+use std::any::Any;
 
 pub(crate) fn update_req_meta_in_extensions(ext: &mut Extensions, new_req_meta: RequestMeta) {
     if let Some(existing_req_meta) = ext.get_mut::<RequestMeta>() {
@@ -18,10 +23,226 @@ pub(crate) fn percent_decode_request_path(val: &str) -> crate::Result<String> {
         .map(|val| val.to_string())
 }
 
+// Maps every byte of the percent decoded path to the offset in the raw path it's decoded from, with the length of the raw
+// path as the last item, so the spans matched on the decoded path can be read from the raw path.
+pub(crate) fn percent_decoded_offsets(raw: &str) -> Vec<usize> {
+    let bytes = raw.as_bytes();
+    let mut offsets = Vec::with_capacity(bytes.len() + 1);
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        offsets.push(idx);
+        let is_encoded = bytes[idx] == b'%'
+            && bytes.get(idx + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(idx + 2).is_some_and(u8::is_ascii_hexdigit);
+        idx += if is_encoded { 3 } else { 1 };
+    }
+    offsets.push(bytes.len());
+
+    offsets
+}
+
+// Generates a plain text response for the requests which are rejected by the router itself, e.g. for a malformed request
+// path. It's only possible if the response body type is `hyper::Body`.
+pub(crate) fn gen_client_error_response<B: 'static>(status: StatusCode) -> Option<Response<B>> {
+    let res: Box<dyn Any> = Box::new(
+        Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(hyper::Body::from(status.canonical_reason().unwrap_or_default()))
+            .expect("Couldn't create the client error response"),
+    );
+
+    res.downcast::<Response<B>>().ok().map(|res| *res)
+}
+
+// Generates the body and the content type of the responses of the default 404 route and the default error handler. The
+// JSON body has the `status` and the `error` fields, and the `message` field if there's a message.
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+pub(crate) fn gen_default_error_body(
+    status: StatusCode,
+    message: Option<String>,
+    json: bool,
+) -> (String, &'static str) {
+    let reason = status.canonical_reason().unwrap_or_default();
+
+    #[cfg(feature = "json")]
+    {
+        if json {
+            let body = match message {
+                Some(message) => serde_json::json!({ "status": status.as_u16(), "error": reason, "message": message }),
+                None => serde_json::json!({ "status": status.as_u16(), "error": reason }),
+            };
+            return (body.to_string(), "application/json");
+        }
+    }
+
+    match message {
+        Some(message) => (format!("{}: {}", reason, message), "text/plain"),
+        None => (reason.to_owned(), "text/plain"),
+    }
+}
+
+// Replaces the path of the uri and keeps its scheme, authority and query, it's used to apply the path rewrites.
+pub(crate) fn replace_uri_path(uri: &Uri, path: &str) -> crate::Result<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_owned(),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .map_err(|e| Error::new(format!("Invalid rewritten request path: {}: {}", path, e)))?,
+    );
+
+    Uri::from_parts(parts).map_err(|e| Error::new(format!("Invalid rewritten request uri: {}: {}", path, e)).into())
+}
+
+// Guesses the content type of a response body by its first chunk. It's conservative, so it only recognizes the UTF-8 text
+// and the HTML which starts with a tag, and it returns `None` for the binary data and the XML.
+pub(crate) fn sniff_content_type(chunk: &[u8]) -> Option<&'static str> {
+    // A multi-byte character might be split at the end of the chunk, which is still a valid start of a text.
+    let text = match std::str::from_utf8(chunk) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&chunk[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+
+    let text = text.trim_start();
+    if text.is_empty() || text.contains('\0') {
+        return None;
+    }
+
+    if text.starts_with('<') {
+        if text.starts_with("<?xml") {
+            return None;
+        }
+        return Some("text/html; charset=utf-8");
+    }
+
+    Some("text/plain; charset=utf-8")
+}
+
+pub(crate) fn merge_slashes(val: &str) -> String {
+    let mut merged = String::with_capacity(val.len());
+    for ch in val.chars() {
+        if ch == '/' && merged.ends_with('/') {
+            continue;
+        }
+        merged.push(ch);
+    }
+    merged
+}
+
+// Makes sure that the prefix ends with a slash, so that `/api` prefix doesn't match the `/apis/` path.
+pub(crate) fn prefix_path<P: Into<String>>(prefix: P) -> String {
+    let mut prefix = prefix.into();
+    if !prefix.ends_with('/') {
+        prefix.push('/');
+    }
+    prefix
+}
+
+// Appends the charset to a text-like content type which doesn't have one, it returns `None` if the content type is left as is.
+pub(crate) fn add_charset_to_content_type(content_type: &str, charset: &str) -> Option<String> {
+    let mut parts = content_type.split(';');
+    let essence = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+
+    if parts.any(|param| param.trim().to_ascii_lowercase().starts_with("charset=")) {
+        return None;
+    }
+
+    let is_text = essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence.as_str(),
+            "application/json" | "application/javascript" | "application/xml"
+        );
+
+    if !is_text {
+        return None;
+    }
+
+    Some(format!(
+        "{}; charset={}",
+        content_type.trim_end().trim_end_matches(';'),
+        charset
+    ))
+}
+
+lazy_static! {
+    static ref PATH_PARAM_RE: Regex = Regex::new(r":([^/\.]+)").unwrap();
+}
+
+// The characters which are percent encoded in a path segment.
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+// Replaces the `:param` segments of the path with the percent encoded param values, the unknown params are kept as is.
+pub(crate) fn interpolate_path_params(path: &str, params: &RouteParams) -> String {
+    PATH_PARAM_RE
+        .replace_all(path, |caps: &Captures| match params.get(&caps[1]) {
+            Some(val) => utf8_percent_encode(val, PATH_SEGMENT_ENCODE_SET).to_string(),
+            None => caps[0].to_owned(),
+        })
+        .into_owned()
+}
+
+// Decodes a base64 value with the standard alphabet, e.g. the Basic credentials. The padding is optional, but the value
+// must not contain any other character.
+pub(crate) fn decode_base64(val: &str) -> Option<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let unpadded = val.strip_suffix("==").or_else(|| val.strip_suffix('=')).unwrap_or(val);
+    if unpadded.len() % 4 == 1 || (unpadded.len() != val.len() && val.len() % 4 != 0) {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+    for chunk in unpadded.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (idx, c) in chunk.iter().enumerate() {
+            bits |= sextet(*c)? << (18 - 6 * idx);
+        }
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+
+    Some(decoded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_percent_decoded_offsets() {
+        assert_eq!(percent_decoded_offsets("/a%2Fb"), vec![0, 1, 2, 5, 6]);
+        assert_eq!(percent_decoded_offsets("/100%"), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(percent_decoded_offsets(""), vec![0]);
+    }
+
     #[test]
     fn test_percent_decode_request_path() {
         let val = "/Alice%20John/do something";
@@ -39,4 +260,27 @@ mod tests {
         let val = "go%crazy";
         assert_eq!(percent_decode_request_path(val).unwrap(), "go%crazy".to_owned());
     }
+
+    #[test]
+    fn test_merge_slashes() {
+        assert_eq!(merge_slashes("/users//42"), "/users/42".to_owned());
+        assert_eq!(merge_slashes("///users///42//"), "/users/42/".to_owned());
+        assert_eq!(merge_slashes("/users/42"), "/users/42".to_owned());
+        assert_eq!(merge_slashes("/files/a%2F%2Fb"), "/files/a%2F%2Fb".to_owned());
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(
+            decode_base64("YWxhZGRpbjpvcGVuc2VzYW1l").unwrap(),
+            b"aladdin:opensesame"
+        );
+        assert_eq!(decode_base64("YQ==").unwrap(), b"a");
+        assert_eq!(decode_base64("YWI=").unwrap(), b"ab");
+        assert_eq!(decode_base64("YWI").unwrap(), b"ab");
+        assert_eq!(decode_base64("").unwrap(), b"");
+        assert!(decode_base64("YWI*").is_none());
+        assert!(decode_base64("Y").is_none());
+        assert!(decode_base64("YQ=").is_none());
+    }
 }
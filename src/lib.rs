@@ -266,6 +266,11 @@
 //! # run();
 //! ```
 //!
+//! The parameter values are percent decoded everywhere they are exposed, i.e. by `req.param()`, `req.params()` and the
+//! [`RequestInfo::params`](./struct.RequestInfo.html#method.params) method in the post middlewares and the error handler.
+//! The values as they appear in the request path are accessed by `req.param_raw()` and
+//! [`RouteParams::get_raw`](./struct.RouteParams.html#method.get_raw).
+//!
 //! ### Scoping/Mounting Router
 //!
 //! The `routerify::Router` is a modular, lightweight and mountable router component. A router can be scoped in or mount to a
@@ -764,21 +769,33 @@
 //! # }
 //! # run();
 //! ```
+//!
+//! To only access the shared data, e.g. a config to render the error responses, use the
+//! [`err_handler_with_data`](./struct.RouterBuilder.html#method.err_handler_with_data) method instead.
 
-pub use self::error::{Error, RouteError};
+pub use self::error::{Error, HasStatusCode, RouteError, RouteErrorExt};
 pub use self::middleware::{Middleware, PostMiddleware, PreMiddleware};
-pub use self::route::Route;
-pub use self::router::{Router, RouterBuilder};
+pub use self::route::{BoxedHandlerFuture, Route, RouteBuilder};
+pub use self::router::{BuilderSnapshot, Router, RouterBuilder};
 #[doc(hidden)]
 pub use self::service::RequestService;
 pub use self::service::RequestServiceBuilder;
 pub use self::service::RouterService;
-pub use self::types::{RequestInfo, RouteParams};
+#[cfg(feature = "hyper1")]
+pub use self::service::{Hyper1Body, Hyper1Service};
+pub use self::service::{RemoteAddr, UNSPECIFIED_REMOTE_ADDR};
+pub use self::types::{
+    DisconnectToken, MatchStats, Matcher, ParamError, PreflightConfig, RequestInfo, RequestTimeoutError, ResponseHints,
+    RouteMatch, RouteParams, SkipPostMiddleware, SkipPostMiddlewareScopes, State, Warning,
+};
+pub use self::utility::response::{not_modified, reject, reject_with};
 
+pub mod body;
 mod constants;
 mod data_map;
 mod error;
 pub mod ext;
+pub mod extract;
 mod helpers;
 mod middleware;
 pub mod prelude;
@@ -786,7 +803,10 @@ mod regex_generator;
 mod route;
 mod router;
 mod service;
+#[cfg(feature = "test-util")]
+pub mod test;
 mod types;
+pub mod utility;
 
 /// A Result type often returned from methods that can have routerify errors.
 pub type Result<T> = std::result::Result<T, RouteError>;
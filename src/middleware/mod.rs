@@ -80,6 +80,66 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Middleware::post_with_path("/*", handler).unwrap()
     }
 
+    /// Creates a pre middleware with a handler at the `/*` path like the [`pre`](#method.pre) method, but with a name
+    /// shown in the `Debug` output of the middleware and of the router, e.g. to tell the middlewares apart while debugging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::pre_named("auth", |req| async move { /* Do some operations */ Ok(req) }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn pre_named<N, H, R>(name: N, handler: H) -> Middleware<B, E>
+    where
+        N: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+    {
+        let mut middleware = PreMiddleware::new("/*", handler).unwrap();
+        middleware.name = Some(name.into());
+        Middleware::Pre(middleware)
+    }
+
+    /// Creates a post middleware with a handler at the `/*` path like the [`post`](#method.post) method, but with a name
+    /// shown in the `Debug` output of the middleware and of the router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::post_named("logger", |res| async move { /* Do some operations */ Ok(res) }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn post_named<N, H, R>(name: N, handler: H) -> Middleware<B, E>
+    where
+        N: Into<String>,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let mut middleware = PostMiddleware::new("/*", handler).unwrap();
+        middleware.name = Some(name.into());
+        Middleware::Post(middleware)
+    }
+
     /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc. It should be used when the post middleware trandforms the response based on
     /// the request information.
     ///
@@ -169,6 +229,64 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Ok(Middleware::Post(PostMiddleware::new(path, handler)?))
     }
 
+    /// Creates a pre middleware with a handler which will be executed for any path starting with the specified prefix.
+    ///
+    /// Unlike the [`pre_with_path`](#method.pre_with_path) method, the `/api` prefix matches the `/api/users` path too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::{Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::pre_for_prefix("/api", |req| async move { /* Do some operations */ Ok(req) }).unwrap())
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn pre_for_prefix<P, H, R>(prefix: P, handler: H) -> crate::Result<Middleware<B, E>>
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+    {
+        Ok(Middleware::Pre(PreMiddleware::new_for_prefix(prefix, handler)?))
+    }
+
+    /// Creates a post middleware with a handler which will be executed for any path starting with the specified prefix.
+    ///
+    /// Unlike the [`post_with_path`](#method.post_with_path) method, the `/api` prefix matches the `/api/users` path too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::post_for_prefix("/api", |res| async move { /* Do some operations */ Ok(res) }).unwrap())
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn post_for_prefix<P, H, R>(prefix: P, handler: H) -> crate::Result<Middleware<B, E>>
+    where
+        P: Into<String>,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        Ok(Middleware::Post(PostMiddleware::new_for_prefix(prefix, handler)?))
+    }
+
     /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc. It should be used when the post middleware trandforms the response based on
     /// the request information.
     ///
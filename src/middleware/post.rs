@@ -1,4 +1,5 @@
-use crate::regex_generator::generate_exact_match_regex;
+use crate::helpers;
+use crate::regex_generator::{generate_exact_match_regex, generate_prefix_match_regex};
 use crate::types::RequestInfo;
 use crate::Error;
 use hyper::{body::HttpBody, Response};
@@ -6,12 +7,13 @@ use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-type HandlerWithoutInfo<B, E> = Box<dyn Fn(Response<B>) -> HandlerWithoutInfoReturn<B, E> + Send + Sync + 'static>;
+type HandlerWithoutInfo<B, E> = Arc<dyn Fn(Response<B>) -> HandlerWithoutInfoReturn<B, E> + Send + Sync + 'static>;
 type HandlerWithoutInfoReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
 type HandlerWithInfo<B, E> =
-    Box<dyn Fn(Response<B>, RequestInfo) -> HandlerWithInfoReturn<B, E> + Send + Sync + 'static>;
+    Arc<dyn Fn(Response<B>, RequestInfo) -> HandlerWithInfoReturn<B, E> + Send + Sync + 'static>;
 type HandlerWithInfoReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
 /// The post middleware type. Refer to [Post Middleware](./index.html#post-middleware) for more info.
@@ -30,6 +32,12 @@ pub struct PostMiddleware<B, E> {
     pub(crate) handler: Option<Handler<B, E>>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // Whether the path is matched as a prefix instead of an exact match.
+    pub(crate) prefix_match: bool,
+    // The middlewares run in the ascending order of their priorities, then in the registration order.
+    pub(crate) priority: i32,
+    // The optional name shown in the debug output, as the handlers are usually anonymous closures.
+    pub(crate) name: Option<String>,
 }
 
 pub(crate) enum Handler<B, E> {
@@ -37,6 +45,15 @@ pub(crate) enum Handler<B, E> {
     WithInfo(HandlerWithInfo<B, E>),
 }
 
+impl<B, E> Clone for Handler<B, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Handler::WithoutInfo(handler) => Handler::WithoutInfo(handler.clone()),
+            Handler::WithInfo(handler) => Handler::WithInfo(handler.clone()),
+        }
+    }
+}
+
 impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
     PostMiddleware<B, E>
 {
@@ -44,23 +61,47 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         path: P,
         handler: Handler<B, E>,
         scope_depth: u32,
+        prefix_match: bool,
+        regex_size_limit: Option<usize>,
     ) -> crate::Result<PostMiddleware<B, E>> {
         let path = path.into();
-        let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
-            Error::new(format!(
-                "Could not create an exact match regex for the post middleware path: {}",
-                e
-            ))
-        })?;
+        let re = gen_post_middleware_regex(path.as_str(), prefix_match, regex_size_limit)?;
 
         Ok(PostMiddleware {
             path,
             regex: re,
             handler: Some(handler),
             scope_depth,
+            prefix_match,
+            priority: 0,
+            name: None,
         })
     }
 
+    // Replaces the path of the middleware and regenerates its match regex, e.g. to append the trailing slash on build.
+    pub(crate) fn set_path(&mut self, path: String, regex_size_limit: Option<usize>) -> crate::Result<()> {
+        self.regex = gen_post_middleware_regex(path.as_str(), self.prefix_match, regex_size_limit)?;
+        self.path = path;
+        Ok(())
+    }
+
+    // Recreates the middleware with the handler under the path prefix at the scope depth and keeps the rest of its
+    // settings, it's used to mount a router into another router.
+    pub(crate) fn with_prefix(
+        &self,
+        prefix: &str,
+        handler: Handler<B, E>,
+        scope_depth: u32,
+        regex_size_limit: Option<usize>,
+    ) -> crate::Result<PostMiddleware<B, E>> {
+        let path = format!("{}{}", prefix, self.path.as_str());
+        let mut middleware =
+            PostMiddleware::new_with_boxed_handler(path, handler, scope_depth, self.prefix_match, regex_size_limit)?;
+        middleware.priority = self.priority;
+        middleware.name = self.name.clone();
+        Ok(middleware)
+    }
+
     /// Creates a post middleware with a handler at the specified path.
     ///
     /// # Examples
@@ -85,8 +126,8 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         H: Fn(Response<B>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let handler: HandlerWithoutInfo<B, E> = Box::new(move |res: Response<B>| Box::new(handler(res)));
-        PostMiddleware::new_with_boxed_handler(path, Handler::WithoutInfo(handler), 1)
+        let handler: HandlerWithoutInfo<B, E> = Arc::new(move |res: Response<B>| Box::new(handler(res)));
+        PostMiddleware::new_with_boxed_handler(path, Handler::WithoutInfo(handler), 1, false, None)
     }
 
     /// Creates a post middleware which can access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc. It should be used when the post middleware trandforms the response based on
@@ -123,8 +164,44 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         let handler: HandlerWithInfo<B, E> =
-            Box::new(move |res: Response<B>, req_info: RequestInfo| Box::new(handler(res, req_info)));
-        PostMiddleware::new_with_boxed_handler(path, Handler::WithInfo(handler), 1)
+            Arc::new(move |res: Response<B>, req_info: RequestInfo| Box::new(handler(res, req_info)));
+        PostMiddleware::new_with_boxed_handler(path, Handler::WithInfo(handler), 1, false, None)
+    }
+
+    /// Creates a post middleware with a handler which will be executed for any path starting with the specified prefix, e.g.
+    /// a middleware with the `/api` prefix will be executed for the `/api`, `/api/users` and `/api/users/1` paths but not
+    /// for the `/apis` path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, PostMiddleware};
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::Post(PostMiddleware::new_for_prefix("/api", |res| async move { /* Do some operations */ Ok(res) }).unwrap()))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn new_for_prefix<P, H, R>(prefix: P, handler: H) -> crate::Result<PostMiddleware<B, E>>
+    where
+        P: Into<String>,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let handler: HandlerWithoutInfo<B, E> = Arc::new(move |res: Response<B>| Box::new(handler(res)));
+        PostMiddleware::new_with_boxed_handler(
+            helpers::prefix_path(prefix),
+            Handler::WithoutInfo(handler),
+            1,
+            true,
+            None,
+        )
     }
 
     pub(crate) fn should_require_req_meta(&self) -> bool {
@@ -138,6 +215,45 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         }
     }
 
+    /// Returns the name of the middleware if it's created by the [`Middleware::post_named`](./enum.Middleware.html#method.post_named)
+    /// method.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Converts the error type of the handler, it's used by the `RouterBuilder::map_err` method.
+    pub(crate) fn map_err<E2, F>(self, f: Arc<F>) -> PostMiddleware<B, E2>
+    where
+        F: Fn(E) -> E2 + Send + Sync + 'static,
+    {
+        let handler = self.handler.map(|handler| -> Handler<B, E2> {
+            match handler {
+                Handler::WithoutInfo(handler) => Handler::WithoutInfo(Arc::new(move |res: Response<B>| {
+                    let fut = Pin::from(handler(res));
+                    let f = f.clone();
+                    Box::new(async move { fut.await.map_err(|e| f(e)) })
+                })),
+                Handler::WithInfo(handler) => {
+                    Handler::WithInfo(Arc::new(move |res: Response<B>, req_info: RequestInfo| {
+                        let fut = Pin::from(handler(res, req_info));
+                        let f = f.clone();
+                        Box::new(async move { fut.await.map_err(|e| f(e)) })
+                    }))
+                }
+            }
+        });
+
+        PostMiddleware {
+            path: self.path,
+            regex: self.regex,
+            handler,
+            scope_depth: self.scope_depth,
+            prefix_match: self.prefix_match,
+            priority: self.priority,
+            name: self.name,
+        }
+    }
+
     pub(crate) async fn process(&self, res: Response<B>, req_info: Option<RequestInfo>) -> crate::Result<Response<B>> {
         let handler = self
             .handler
@@ -153,8 +269,27 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
     }
 }
 
+fn gen_post_middleware_regex(path: &str, prefix_match: bool, size_limit: Option<usize>) -> crate::Result<Regex> {
+    let (re, _) = if prefix_match {
+        generate_prefix_match_regex(path, size_limit)
+    } else {
+        generate_exact_match_regex(path, size_limit)
+    }
+    .map_err(|e| {
+        Error::new(format!(
+            "Could not create a match regex for the post middleware path `{}`: {}",
+            path, e
+        ))
+    })?;
+    Ok(re)
+}
+
 impl<B, E> Debug for PostMiddleware<B, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
+        write!(
+            f,
+            "{{ name: {:?}, path: {:?}, regex: {:?}, prefix_match: {:?} }}",
+            self.name, self.path, self.regex, self.prefix_match
+        )
     }
 }
@@ -1,12 +1,14 @@
-use crate::regex_generator::generate_exact_match_regex;
+use crate::helpers;
+use crate::regex_generator::{generate_exact_match_regex, generate_prefix_match_regex};
 use crate::Error;
 use hyper::Request;
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-type Handler<E> = Box<dyn Fn(Request<hyper::Body>) -> HandlerReturn<E> + Send + Sync + 'static>;
+type Handler<E> = Arc<dyn Fn(Request<hyper::Body>) -> HandlerReturn<E> + Send + Sync + 'static>;
 type HandlerReturn<E> = Box<dyn Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static>;
 
 /// The pre middleware type. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
@@ -22,6 +24,12 @@ pub struct PreMiddleware<E> {
     pub(crate) handler: Option<Handler<E>>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // Whether the path is matched as a prefix instead of an exact match.
+    pub(crate) prefix_match: bool,
+    // The middlewares run in the ascending order of their priorities, then in the registration order.
+    pub(crate) priority: i32,
+    // The optional name shown in the debug output, as the handlers are usually anonymous closures.
+    pub(crate) name: Option<String>,
 }
 
 impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<E> {
@@ -29,12 +37,19 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
         path: P,
         handler: Handler<E>,
         scope_depth: u32,
+        prefix_match: bool,
+        regex_size_limit: Option<usize>,
     ) -> crate::Result<PreMiddleware<E>> {
         let path = path.into();
-        let (re, _) = generate_exact_match_regex(path.as_str()).map_err(|e| {
+        let (re, _) = if prefix_match {
+            generate_prefix_match_regex(path.as_str(), regex_size_limit)
+        } else {
+            generate_exact_match_regex(path.as_str(), regex_size_limit)
+        }
+        .map_err(|e| {
             Error::new(format!(
-                "Could not create an exact match regex for the pre middleware path: {}",
-                e
+                "Could not create a match regex for the pre middleware path `{}`: {}",
+                path, e
             ))
         })?;
 
@@ -43,9 +58,29 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
             regex: re,
             handler: Some(handler),
             scope_depth,
+            prefix_match,
+            priority: 0,
+            name: None,
         })
     }
 
+    // Recreates the middleware with the handler under the path prefix at the scope depth and keeps the rest of its
+    // settings, it's used to mount a router into another router.
+    pub(crate) fn with_prefix(
+        &self,
+        prefix: &str,
+        handler: Handler<E>,
+        scope_depth: u32,
+        regex_size_limit: Option<usize>,
+    ) -> crate::Result<PreMiddleware<E>> {
+        let path = format!("{}{}", prefix, self.path.as_str());
+        let mut middleware =
+            PreMiddleware::new_with_boxed_handler(path, handler, scope_depth, self.prefix_match, regex_size_limit)?;
+        middleware.priority = self.priority;
+        middleware.name = self.name.clone();
+        Ok(middleware)
+    }
+
     /// Creates a pre middleware with a handler at the specified path.
     ///
     /// # Examples
@@ -70,8 +105,68 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
         H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
     {
-        let handler: Handler<E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
-        PreMiddleware::new_with_boxed_handler(path, handler, 1)
+        let handler: Handler<E> = Arc::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        PreMiddleware::new_with_boxed_handler(path, handler, 1, false, None)
+    }
+
+    /// Creates a pre middleware with a handler which will be executed for any path starting with the specified prefix, e.g.
+    /// a middleware with the `/api` prefix will be executed for the `/api`, `/api/users` and `/api/users/1` paths but not
+    /// for the `/apis` path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, PreMiddleware};
+    /// use hyper::{Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::Pre(PreMiddleware::new_for_prefix("/api", |req| async move { /* Do some operations */ Ok(req) }).unwrap()))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn new_for_prefix<P, H, R>(prefix: P, handler: H) -> crate::Result<PreMiddleware<E>>
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+    {
+        let handler: Handler<E> = Arc::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        PreMiddleware::new_with_boxed_handler(helpers::prefix_path(prefix), handler, 1, true, None)
+    }
+
+    /// Returns the name of the middleware if it's created by the [`Middleware::pre_named`](./enum.Middleware.html#method.pre_named)
+    /// method.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Converts the error type of the handler, it's used by the `RouterBuilder::map_err` method.
+    pub(crate) fn map_err<E2, F>(self, f: Arc<F>) -> PreMiddleware<E2>
+    where
+        F: Fn(E) -> E2 + Send + Sync + 'static,
+    {
+        let handler = self.handler.map(|handler| -> Handler<E2> {
+            Arc::new(move |req: Request<hyper::Body>| {
+                let fut = Pin::from(handler(req));
+                let f = f.clone();
+                Box::new(async move { fut.await.map_err(|e| f(e)) })
+            })
+        });
+
+        PreMiddleware {
+            path: self.path,
+            regex: self.regex,
+            handler,
+            scope_depth: self.scope_depth,
+            prefix_match: self.prefix_match,
+            priority: self.priority,
+            name: self.name,
+        }
     }
 
     pub(crate) async fn process(&self, req: Request<hyper::Body>) -> crate::Result<Request<hyper::Body>> {
@@ -86,6 +181,10 @@ impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> PreMiddleware<
 
 impl<E> Debug for PreMiddleware<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
+        write!(
+            f,
+            "{{ name: {:?}, path: {:?}, regex: {:?}, prefix_match: {:?} }}",
+            self.name, self.path, self.regex, self.prefix_match
+        )
     }
 }
@@ -1 +1 @@
-pub use crate::ext::RequestExt;
+pub use crate::ext::{RequestBodyExt, RequestExt};
@@ -1,5 +1,6 @@
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
+use std::fmt::{self, Display, Formatter};
 
 lazy_static! {
     static ref PATH_PARAMS_RE: Regex = Regex::new(r"(?s)(?::([^/\.]+))|(?:\*)").unwrap();
@@ -34,18 +35,46 @@ fn generate_common_regex_str(path: &str) -> (String, Vec<String>) {
     (regex_str, param_names)
 }
 
-pub(crate) fn generate_exact_match_regex(path: &str) -> crate::Result<(Regex, Vec<String>)> {
+// The error of a generated regex which couldn't be compiled, it keeps the raw regex string to help finding the cause.
+#[derive(Debug)]
+pub(crate) struct RegexGenerationError {
+    regex: String,
+    source: regex::Error,
+}
+
+impl Display for RegexGenerationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (generated regex: `{}`)", self.source, self.regex)
+    }
+}
+
+// Compiles the generated regex, with the limit of its compiled program and of its lazy DFA cache if it's specified, so an
+// expensive path is rejected instead of slowing down the matching.
+fn compile_regex(re_str: String, size_limit: Option<usize>) -> Result<Regex, RegexGenerationError> {
+    let mut builder = RegexBuilder::new(re_str.as_str());
+    if let Some(size_limit) = size_limit {
+        builder.size_limit(size_limit).dfa_size_limit(size_limit);
+    }
+    builder
+        .build()
+        .map_err(|source| RegexGenerationError { regex: re_str, source })
+}
+
+pub(crate) fn generate_exact_match_regex(
+    path: &str,
+    size_limit: Option<usize>,
+) -> Result<(Regex, Vec<String>), RegexGenerationError> {
     let (common_regex_str, params) = generate_common_regex_str(path);
-    let re_str = format!("{}{}{}", r"(?s)^", common_regex_str, "$");
-    let re = Regex::new(re_str.as_str())?;
+    let re = compile_regex(format!("{}{}{}", r"(?s)^", common_regex_str, "$"), size_limit)?;
     Ok((re, params))
 }
 
-#[allow(dead_code)]
-pub(crate) fn generate_prefix_match_regex(path: &str) -> crate::Result<(Regex, Vec<String>)> {
+pub(crate) fn generate_prefix_match_regex(
+    path: &str,
+    size_limit: Option<usize>,
+) -> Result<(Regex, Vec<String>), RegexGenerationError> {
     let (common_regex_str, params) = generate_common_regex_str(path);
-    let re_str = format!("{}{}", r"(?s)^", common_regex_str);
-    let re = Regex::new(re_str.as_str())?;
+    let re = compile_regex(format!("{}{}", r"(?s)^", common_regex_str), size_limit)?;
     Ok((re, params))
 }
 
@@ -0,0 +1,88 @@
+use crate::route::{Handler, Route};
+use crate::Error;
+use hyper::{body::HttpBody, Method, Request, Response};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Builder for the [Route](./struct.Route.html) type, it can be created by the
+/// [`Route::builder`](./struct.Route.html#method.builder) method.
+///
+/// The built route can be added to a router by the [`RouterBuilder::route`](./struct.RouterBuilder.html#method.route)
+/// method.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, Route};
+/// use hyper::{Response, Body, Method};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let route = Route::builder("/upload")
+///     .methods(vec![Method::POST, Method::PUT])
+///     .max_size(1024 * 1024)
+///     .handler(|_| async move { Ok(Response::new(Body::from("Uploaded"))) })
+///     .build()
+///     .unwrap();
+///
+/// let router = Router::builder()
+///     .route(route)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub struct RouteBuilder<B, E> {
+    path: String,
+    methods: Vec<Method>,
+    handler: Option<Handler<B, E>>,
+    max_size: u64,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    RouteBuilder<B, E>
+{
+    pub(crate) fn new<P: Into<String>>(path: P) -> RouteBuilder<B, E> {
+        RouteBuilder {
+            path: path.into(),
+            methods: vec![Method::GET],
+            handler: None,
+            max_size: 0,
+        }
+    }
+
+    /// Sets the http method(s) of the route, it's `GET` by default.
+    pub fn methods(mut self, methods: Vec<Method>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Sets the handler of the route.
+    pub fn handler<H, R>(mut self, handler: H) -> Self
+    where
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.handler = Some(Arc::new(move |req: Request<hyper::Body>| Box::new(handler(req))));
+        self
+    }
+
+    /// Sets the maximum size of the request body in bytes, a larger request is rejected with `413 Payload Too Large`.
+    /// It's `0` by default which means unlimited.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Creates a new [Route](./struct.Route.html) instance from the added configuration.
+    pub fn build(self) -> crate::Result<Route<B, E>> {
+        // The router appends the trailing slash to the path on build unless the strict slash mode is on.
+        let path = self.path;
+        let handler = self
+            .handler
+            .ok_or_else(|| Error::new(format!("No handler is set for the route: {}", path)))?;
+
+        Route::new_with_boxed_handler(path, self.methods, handler, 1, self.max_size, None)
+    }
+}
@@ -1,20 +1,35 @@
+use crate::body::LimitedBody;
+use crate::constants;
 use crate::helpers;
 use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
 use crate::types::{RequestMeta, RouteParams};
 use crate::Error;
-use hyper::{body::HttpBody, Method, Request, Response};
+use hyper::{body::HttpBody, header, HeaderMap, Method, Request, Response};
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
-type Handler<B, E> = Box<dyn Fn(Request<hyper::Body>) -> HandlerReturn<B, E> + Send + Sync + 'static>;
+pub use self::builder::RouteBuilder;
+
+mod builder;
+
+pub(crate) type Handler<B, E> = Arc<dyn Fn(Request<hyper::Body>) -> HandlerReturn<B, E> + Send + Sync + 'static>;
 type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
+/// The boxed future returned by a route handler, so the handlers can be stored and dispatched dynamically, e.g. by a plugin
+/// system. Such handlers can be registered by the [`RouterBuilder::get_boxed`](./struct.RouterBuilder.html#method.get_boxed)
+/// and the [`RouterBuilder::add_boxed`](./struct.RouterBuilder.html#method.add_boxed) methods.
+pub type BoxedHandlerFuture<B, E> = Pin<Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>>;
+
 /// Represents a single route.
 ///
-/// A route consists of a path, http method type(s) and a handler. It shouldn't be created directly, use [RouterBuilder](./struct.RouterBuilder.html) methods
-/// to create a route.
+/// A route consists of a path, http method type(s) and a handler. It's usually created by the [RouterBuilder](./struct.RouterBuilder.html) methods,
+/// a route with custom settings can be created by the [`Route::builder`](#method.builder) method and added by the
+/// [`RouterBuilder::route`](./struct.RouterBuilder.html#method.route) method.
 ///
 /// This `Route<B, E>` type accepts two type parameters: `B` and `E`.
 ///
@@ -53,6 +68,15 @@ pub struct Route<B, E> {
     pub(crate) methods: Vec<Method>,
     // Scope depth with regards to the top level router.
     pub(crate) scope_depth: u32,
+    // The maximum size of the request body in bytes, zero means unlimited.
+    pub(crate) max_size: u64,
+    // The path prefix under which the route is mounted, it's empty for the routes of the top level router.
+    pub(crate) scope_prefix: String,
+    // The error handler of the router which is mounted by the `RouterBuilder::scope_at_root` method, it handles the errors
+    // of this route instead of the root error handler.
+    pub(crate) err_handler: Option<Arc<ErrHandler<B>>>,
+    // The tags which are attached by the `RouterBuilder::get_tagged` and `add_tagged` methods, e.g. to enforce permissions by a middleware.
+    pub(crate) tags: Arc<[String]>,
 }
 
 impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Route<B, E> {
@@ -61,14 +85,11 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         methods: Vec<Method>,
         handler: Handler<B, E>,
         scope_depth: u32,
+        max_size: u64,
+        regex_size_limit: Option<usize>,
     ) -> crate::Result<Route<B, E>> {
         let path = path.into();
-        let (re, params) = generate_exact_match_regex(path.as_str()).map_err(|e| {
-            Error::new(format!(
-                "Could not create an exact match regex for the route path: {}",
-                e
-            ))
-        })?;
+        let (re, params) = gen_route_regex(path.as_str(), regex_size_limit)?;
 
         Ok(Route {
             path,
@@ -77,25 +98,138 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             handler: Some(handler),
             methods,
             scope_depth,
+            max_size,
+            scope_prefix: String::new(),
+            err_handler: None,
+            tags: Arc::new([]),
         })
     }
 
-    pub(crate) fn new<P, H, R>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<B, E>>
+    /// Returns a [RouteBuilder](./struct.RouteBuilder.html) instance to build a `Route` with custom settings.
+    pub fn builder<P: Into<String>>(path: P) -> RouteBuilder<B, E> {
+        RouteBuilder::new(path)
+    }
+
+    pub(crate) fn new<P, H, R>(
+        path: P,
+        methods: Vec<Method>,
+        handler: H,
+        regex_size_limit: Option<usize>,
+    ) -> crate::Result<Route<B, E>>
     where
         P: Into<String>,
         H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
-        let handler: Handler<B, E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
-        Route::new_with_boxed_handler(path, methods, handler, 1)
+        let handler: Handler<B, E> = Arc::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        Route::new_with_boxed_handler(path, methods, handler, 1, 0, regex_size_limit)
+    }
+
+    // Replaces the path of the route and regenerates its match regex, e.g. to append the trailing slash on build.
+    pub(crate) fn set_path(&mut self, path: String, regex_size_limit: Option<usize>) -> crate::Result<()> {
+        let (re, params) = gen_route_regex(path.as_str(), regex_size_limit)?;
+        self.path = path;
+        self.regex = re;
+        self.route_params = params;
+        Ok(())
+    }
+
+    // Recreates the route with the handler under the path prefix at the scope depth and keeps the rest of its settings,
+    // it's used to mount a router into another router.
+    pub(crate) fn with_prefix(
+        &self,
+        prefix: &str,
+        handler: Handler<B, E>,
+        scope_depth: u32,
+        regex_size_limit: Option<usize>,
+    ) -> crate::Result<Route<B, E>> {
+        let mut route = Route::new_with_boxed_handler(
+            format!("{}{}", prefix, self.path.as_str()),
+            self.methods.clone(),
+            handler,
+            scope_depth,
+            self.max_size,
+            regex_size_limit,
+        )?;
+        route.scope_prefix = format!("{}{}", prefix, self.scope_prefix.as_str());
+        route.err_handler = self.err_handler.clone();
+        route.tags = self.tags.clone();
+        Ok(route)
+    }
+
+    // Converts the error type of the handler, it's used by the `RouterBuilder::map_err` method.
+    pub(crate) fn map_err<E2, F>(self, f: Arc<F>) -> Route<B, E2>
+    where
+        F: Fn(E) -> E2 + Send + Sync + 'static,
+    {
+        let handler = self.handler.map(|handler| -> Handler<B, E2> {
+            Arc::new(move |req: Request<hyper::Body>| {
+                let fut = Pin::from(handler(req));
+                let f = f.clone();
+                Box::new(async move { fut.await.map_err(|e| f(e)) })
+            })
+        });
+
+        Route {
+            path: self.path,
+            regex: self.regex,
+            route_params: self.route_params,
+            handler,
+            methods: self.methods,
+            scope_depth: self.scope_depth,
+            max_size: self.max_size,
+            scope_prefix: self.scope_prefix,
+            err_handler: self.err_handler,
+            tags: self.tags,
+        }
     }
 
     pub(crate) fn is_match_method(&self, method: &Method) -> bool {
         self.methods.contains(method)
     }
 
-    pub(crate) async fn process(&self, target_path: &str, mut req: Request<hyper::Body>) -> crate::Result<Response<B>> {
-        self.push_req_meta(target_path, &mut req);
+    // A catch-all route is registered by the `.any()` method to handle the non-existent routes.
+    pub(crate) fn is_catch_all(&self) -> bool {
+        self.path == "/*" && self.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]
+    }
+
+    // A catch-all route of a scoped router, e.g. `/api/*` for the `.any()` route of a router mounted at `/api`.
+    pub(crate) fn is_scoped_catch_all(&self) -> bool {
+        self.path.len() > 2
+            && self.path.ends_with("/*")
+            && self.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]
+    }
+
+    // Checks the declared length of the request body against the maximum size of the route.
+    pub(crate) fn is_body_too_large(&self, headers: &HeaderMap) -> bool {
+        if self.max_size == 0 {
+            return false;
+        }
+
+        headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse::<u64>().ok())
+            .map(|len| len > self.max_size)
+            .unwrap_or(false)
+    }
+
+    // The `exceeded` flag is set when the handler reads beyond the body size limit, so the router can reject the request
+    // instead of handling the error of the handler.
+    pub(crate) async fn process(
+        &self,
+        req_meta: RequestMeta,
+        mut req: Request<hyper::Body>,
+        exceeded: Arc<AtomicBool>,
+    ) -> crate::Result<Response<B>> {
+        self.update_req_meta(&mut req, req_meta);
+
+        // The body length might not be declared, e.g. for a chunked body, so enforce the limit while reading it too.
+        if self.max_size > 0 {
+            let max_size = self.max_size;
+            let exceeded = exceeded.clone();
+            req = req.map(|body| LimitedBody::new(body, max_size, exceeded).into_body());
+        }
 
         let handler = self
             .handler
@@ -105,43 +239,98 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Pin::from(handler(req)).await.map_err(Into::into)
     }
 
-    fn push_req_meta(&self, target_path: &str, req: &mut Request<hyper::Body>) {
-        self.update_req_meta(req, self.generate_req_meta(target_path));
-    }
-
     fn update_req_meta(&self, req: &mut Request<hyper::Body>, req_meta: RequestMeta) {
         helpers::update_req_meta_in_extensions(req.extensions_mut(), req_meta);
     }
 
-    fn generate_req_meta(&self, target_path: &str) -> RequestMeta {
+    // The `decode_params` flag is set when the route is matched against the raw path, so the captured values must be
+    // percent decoded individually. Otherwise the raw values of the parameters are captured from the raw target path,
+    // they fall back to the decoded ones if it can't be matched the same way, e.g. for an encoded slash in a parameter.
+    pub(crate) fn generate_req_meta(
+        &self,
+        target_path: &str,
+        raw_target_path: &str,
+        decode_params: bool,
+    ) -> RequestMeta {
+        let decode = |val: &str| {
+            if decode_params {
+                helpers::percent_decode_request_path(val).unwrap_or_else(|_| val.to_owned())
+            } else {
+                val.to_owned()
+            }
+        };
+
         let route_params_list = &self.route_params;
         let ln = route_params_list.len();
 
         let mut route_params = RouteParams::with_capacity(ln);
+        let mut captures = Vec::with_capacity(ln);
+        let mut glob_remainder = None;
+
+        if let Some(caps) = self.regex.captures(target_path) {
+            // The path is matched after decoding, so read the raw values at the same spans of the raw path. It keeps
+            // the values split at the same slashes even if the raw path has encoded slashes.
+            let raw_offsets = if decode_params {
+                None
+            } else {
+                Some(helpers::percent_decoded_offsets(raw_target_path))
+                    .filter(|offsets| offsets.len() == target_path.len() + 1)
+            };
 
-        if ln > 0 {
-            if let Some(caps) = self.regex.captures(target_path) {
-                let mut iter = caps.iter();
-                // Skip the first match because it's the whole path.
-                iter.next();
-                for param in route_params_list {
-                    if let Some(Some(g)) = iter.next() {
-                        route_params.set(param.clone(), g.as_str());
+            let mut iter = caps.iter();
+            // Skip the first match because it's the whole path.
+            iter.next();
+            for param in route_params_list {
+                if let Some(Some(g)) = iter.next() {
+                    let raw = raw_offsets
+                        .as_ref()
+                        .and_then(|offsets| raw_target_path.get(offsets[g.start()]..offsets[g.end()]))
+                        .unwrap_or(g.as_str());
+                    route_params.append_with_raw(param.clone(), decode(g.as_str()), raw);
+
+                    // The last `*` segment holds the unmatched remainder of the path. Strip the trailing
+                    // slash which is appended to the target path before matching.
+                    if param == "*" {
+                        let remainder = g.as_str();
+                        glob_remainder = Some(decode(remainder.strip_suffix('/').unwrap_or(remainder)));
                     }
                 }
             }
+
+            // Keep every capture group in order, including the ones which are not named.
+            captures.extend(
+                caps.iter()
+                    .skip(1)
+                    .map(|g| g.map(|g| decode(g.as_str())).unwrap_or_default()),
+            );
         }
 
-        RequestMeta::with_route_params(route_params)
+        let scope_prefix = if self.scope_prefix.is_empty() {
+            None
+        } else {
+            Some(self.scope_prefix.clone())
+        };
+
+        RequestMeta::with_route_params(route_params, captures, glob_remainder, scope_prefix, self.tags.clone())
     }
 }
 
+fn gen_route_regex(path: &str, size_limit: Option<usize>) -> crate::Result<(Regex, Vec<String>)> {
+    generate_exact_match_regex(path, size_limit).map_err(|e| {
+        Error::new(format!(
+            "Could not create an exact match regex for the route path `{}`: {}",
+            path, e
+        ))
+        .into()
+    })
+}
+
 impl<B, E> Debug for Route<B, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{ path: {:?}, regex: {:?}, route_params: {:?}, methods: {:?} }}",
-            self.path, self.regex, self.route_params, self.methods
+            "{{ path: {:?}, regex: {:?}, route_params: {:?}, methods: {:?}, max_size: {:?} }}",
+            self.path, self.regex, self.route_params, self.methods, self.max_size
         )
     }
 }
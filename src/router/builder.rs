@@ -1,14 +1,28 @@
+use crate::body::PrependedBody;
 use crate::constants;
 use crate::data_map::{DataMap, ScopedDataMap};
+use crate::ext::RequestExt;
+use crate::extract::{ExtractHandler, FromRequest};
+use crate::helpers;
 use crate::middleware::{Middleware, PostMiddleware, PreMiddleware};
-use crate::route::Route;
+use crate::route::{BoxedHandlerFuture, Route};
 use crate::router::Router;
-use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo};
-use crate::types::RequestInfo;
-use hyper::{body::HttpBody, Method, Request, Response};
+use crate::router::{
+    mounted_router_error, ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo, OnRequestHook, OnResponseHook,
+    PathRewrite, RejectionHandler, ResponseFactory, RouterOptions,
+};
+use crate::types::{Matcher, PreflightConfig, RequestInfo, RequestTimeoutError, State};
+use crate::utility::middlewares;
+use crate::{Error, HasStatusCode, RouteErrorExt};
+use hyper::header::{self, HeaderValue};
+use hyper::service::Service;
+use hyper::{body::HttpBody, HeaderMap, Method, Request, Response, StatusCode};
+use std::any::type_name;
 use std::collections::HashMap;
-use std::future::Future;
-use std::sync::Arc;
+use std::fmt::{self, Debug, Formatter};
+use std::future::{poll_fn, Future};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 /// Builder for the [Router](./struct.Router.html) type.
 ///
@@ -54,12 +68,84 @@ pub struct RouterBuilder<B, E> {
     inner: crate::Result<BuilderInner<B, E>>,
 }
 
+// Resolves the state of a route handler from the root data map on build, it fails if the state is not set.
+type StateResolver = Box<dyn FnOnce(Option<&DataMap>) -> crate::Result<()> + Send + Sync + 'static>;
+
 struct BuilderInner<B, E> {
     pre_middlewares: Vec<PreMiddleware<E>>,
     routes: Vec<Route<B, E>>,
     post_middlewares: Vec<PostMiddleware<B, E>>,
     data_maps: HashMap<String, Vec<DataMap>>,
+    // The data maps which are shared with another router mounted by the `scope_cloned` method.
+    shared_data_maps: Vec<(String, Arc<DataMap>)>,
+    // The states required by the route handlers, they are resolved from the root data map on build.
+    state_resolvers: Vec<StateResolver>,
+    strict: bool,
+    // The maximum size of the request body for the routes which don't set their own limit, zero means unlimited.
+    max_size: u64,
     err_handler: Option<ErrHandler<B>>,
+    options: RouterOptions,
+    fallback: Option<Box<Router<B, E>>>,
+    on_request: Option<OnRequestHook>,
+    on_response: Option<OnResponseHook<B>>,
+    rewrite: Option<PathRewrite>,
+    response_factory: Option<ResponseFactory<B>>,
+    on_payload_too_large: Option<RejectionHandler<B>>,
+    on_bad_request: Option<RejectionHandler<B>>,
+    // The prefix which is applied to all the routes, the middlewares and the scoped data maps on build.
+    base_path: String,
+    // The indices of the post middlewares added by the `deprecated` methods, their paths are normalized like the routes.
+    deprecation_idxs: Vec<usize>,
+}
+
+/// A read-only view of the configuration added to a [`RouterBuilder`](./struct.RouterBuilder.html) so far, it's passed to
+/// the callback of the [`inspect`](./struct.RouterBuilder.html#method.inspect) method.
+///
+/// The route paths are reported as they are registered, the trailing slash is only appended on build unless the
+/// [`strict_slash`](./struct.RouterBuilder.html#method.strict_slash) option is enabled.
+pub struct BuilderSnapshot<'a, B, E> {
+    inner: &'a BuilderInner<B, E>,
+}
+
+impl<'a, B, E> BuilderSnapshot<'a, B, E> {
+    /// Returns the number of the routes added so far, a route which handles multiple methods is counted once.
+    pub fn route_count(&self) -> usize {
+        self.inner.routes.len()
+    }
+
+    /// Returns the paths and the methods of the routes added so far in the registration order.
+    pub fn routes(&self) -> impl Iterator<Item = (&'a str, &'a [Method])> {
+        self.inner
+            .routes
+            .iter()
+            .map(|route| (route.path.as_str(), route.methods.as_slice()))
+    }
+
+    /// Returns the number of the pre middlewares added so far.
+    pub fn pre_middleware_count(&self) -> usize {
+        self.inner.pre_middlewares.len()
+    }
+
+    /// Returns the number of the post middlewares added so far.
+    pub fn post_middleware_count(&self) -> usize {
+        self.inner.post_middlewares.len()
+    }
+
+    /// Checks if an error handler is set.
+    pub fn has_err_handler(&self) -> bool {
+        self.inner.err_handler.is_some()
+    }
+}
+
+impl<B, E> Debug for BuilderSnapshot<'_, B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuilderSnapshot")
+            .field("routes", &self.routes().collect::<Vec<_>>())
+            .field("pre_middlewares", &self.pre_middleware_count())
+            .field("post_middlewares", &self.post_middleware_count())
+            .field("has_err_handler", &self.has_err_handler())
+            .finish()
+    }
 }
 
 impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
@@ -72,29 +158,160 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 
     /// Creates a new [Router](./struct.Router.html) instance from the added configuration.
     pub fn build(self) -> crate::Result<Router<B, E>> {
-        self.inner.and_then(|inner| {
+        self.inner.and_then(|mut inner| {
+            // The strict slash mode applies to all the routes regardless of the call order, so normalize the paths here.
+            let strict_slash = inner.options.strict_slash;
+            let regex_size_limit = inner.options.regex_size_limit;
+            for route in inner.routes.iter_mut() {
+                if let Some(path) = append_trailing_slash(route.path.as_str(), strict_slash) {
+                    route.set_path(path, regex_size_limit)?;
+                }
+            }
+            for idx in inner.deprecation_idxs.iter() {
+                let middleware = &mut inner.post_middlewares[*idx];
+                if let Some(path) = append_trailing_slash(middleware.path.as_str(), strict_slash) {
+                    middleware.set_path(path, regex_size_limit)?;
+                }
+            }
+
+            // The state can be set after the routes which require it, so every route sees the last one.
+            let root_data_map = inner.data_maps.get("/*").and_then(|data_map_arr| data_map_arr.first());
+            for resolve_state in inner.state_resolvers.drain(..) {
+                resolve_state(root_data_map)?;
+            }
+
+            // The timeout may be set more than once, so its status hint is registered here only once.
+            if inner.options.request_timeout.is_some() {
+                inner
+                    .options
+                    .status_hints
+                    .push(|err| err.status_hint::<RequestTimeoutError>());
+            }
+
+            if inner.strict {
+                check_overlapping_routes(&inner.routes, inner.options.strict_slash)?;
+            }
+
+            let base_path = inner.base_path;
+            let (pre_middlewares, routes, post_middlewares) = if base_path.is_empty() {
+                (inner.pre_middlewares, inner.routes, inner.post_middlewares)
+            } else {
+                apply_base_path(
+                    base_path.as_str(),
+                    inner.pre_middlewares,
+                    inner.routes,
+                    inner.post_middlewares,
+                    regex_size_limit,
+                )?
+            };
+
+            // The scoped routers' middlewares are already sorted, so only the ones of this router are sorted here.
+            let pre_middlewares = sort_by_priority(pre_middlewares, |m| m.scope_depth == 1, |m| m.priority);
+            let post_middlewares = sort_by_priority(post_middlewares, |m| m.scope_depth == 1, |m| m.priority);
+
             let scoped_data_maps = inner
                 .data_maps
                 .into_iter()
-                .map(|(path, data_map_arr)| {
+                .flat_map(|(path, data_map_arr)| {
                     data_map_arr
                         .into_iter()
-                        .map(|data_map| ScopedDataMap::new(path.clone(), Arc::new(data_map)))
+                        .map(|data_map| {
+                            ScopedDataMap::new(format!("{}{}", base_path, path), Arc::new(data_map), regex_size_limit)
+                        })
                         .collect::<Vec<crate::Result<ScopedDataMap>>>()
                 })
-                .flatten()
+                .chain(inner.shared_data_maps.into_iter().map(|(path, data_map)| {
+                    ScopedDataMap::new(format!("{}{}", base_path, path), data_map, regex_size_limit)
+                }))
                 .collect::<Result<Vec<ScopedDataMap>, crate::RouteError>>()?;
 
-            Ok(Router::new(
-                inner.pre_middlewares,
-                inner.routes,
-                inner.post_middlewares,
+            let mut router = Router::new(
+                pre_middlewares,
+                routes,
+                post_middlewares,
                 scoped_data_maps,
                 inner.err_handler,
-            ))
+                inner.options,
+                inner.fallback,
+            );
+            router.on_request = inner.on_request;
+            router.on_response = inner.on_response;
+            router.rewrite = inner.rewrite;
+            router.response_factory = inner.response_factory;
+            router.on_payload_too_large = inner.on_payload_too_large;
+            router.on_bad_request = inner.on_bad_request;
+
+            Ok(router)
         })
     }
 
+    /// Converts the error type of all the route handlers and the middlewares added so far, so a router with a different
+    /// error type can be mounted onto another router by the [`scope`](#method.scope) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::io;
+    ///
+    /// fn files_router() -> Router<Body, routerify::Error> {
+    ///     Router::builder()
+    ///         .get("/:name", |_| async move { Err::<Response<Body>, _>(io::Error::other("Not readable")) })
+    ///         .map_err(|e: io::Error| routerify::Error::new(e.to_string()))
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///     .scope("/files", files_router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn map_err<E2, F>(self, f: F) -> RouterBuilder<B, E2>
+    where
+        E2: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        F: Fn(E) -> E2 + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+
+        RouterBuilder {
+            inner: self.inner.map(move |inner| BuilderInner {
+                pre_middlewares: inner
+                    .pre_middlewares
+                    .into_iter()
+                    .map(|m| m.map_err(f.clone()))
+                    .collect(),
+                routes: inner.routes.into_iter().map(|r| r.map_err(f.clone())).collect(),
+                post_middlewares: inner
+                    .post_middlewares
+                    .into_iter()
+                    .map(|m| m.map_err(f.clone()))
+                    .collect(),
+                data_maps: inner.data_maps,
+                shared_data_maps: inner.shared_data_maps,
+                state_resolvers: inner.state_resolvers,
+                strict: inner.strict,
+                max_size: inner.max_size,
+                err_handler: inner.err_handler,
+                options: inner.options,
+                fallback: inner.fallback.map(|fallback| Box::new(fallback.map_err(f))),
+                on_request: inner.on_request,
+                on_response: inner.on_response,
+                rewrite: inner.rewrite,
+                response_factory: inner.response_factory,
+                on_payload_too_large: inner.on_payload_too_large,
+                on_bad_request: inner.on_bad_request,
+                base_path: inner.base_path,
+                deprecation_idxs: inner.deprecation_idxs,
+            }),
+        }
+    }
+
     fn and_then<F>(self, func: F) -> Self
     where
         F: FnOnce(BuilderInner<B, E>) -> crate::Result<BuilderInner<B, E>>,
@@ -138,6 +355,112 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         self.add(path, vec![Method::GET], handler)
     }
 
+    /// Adds a new route with `GET` method and the handler at the specified path, and attaches the specified tags to it,
+    /// e.g. `"admin"` to let an authorization middleware enforce the permissions without repeating the route paths.
+    ///
+    /// The tags are read by the [`route_tags`](./ext/trait.RequestExt.html#tymethod.route_tags) method in the pre
+    /// middlewares, the route handler and the post middlewares, and they are kept when the router is scoped into another
+    /// router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, RouteError};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, RouteError> {
+    /// let router = Router::builder()
+    ///     .middleware(Middleware::pre(|req| async move {
+    ///         let is_admin = req.headers().get("x-role").is_some_and(|role| role == "admin");
+    ///         if req.route_tags().iter().any(|tag| tag == "admin") && !is_admin {
+    ///             return Err("Forbidden".into());
+    ///         }
+    ///         Ok(req)
+    ///     }))
+    ///     .get_tagged("/admin/users", &["admin"], |_| async move { Ok(Response::new(Body::from("All users"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_tagged<P, H, R>(self, path: P, tags: &[&str], handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.add_tagged(path, vec![Method::GET], tags, handler)
+    }
+
+    /// Adds a new route with `GET` method and a handler which returns a [boxed future](./type.BoxedHandlerFuture.html),
+    /// so the handlers have a uniform type and can be stored by the external code, e.g. by a plugin system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{BoxedHandlerFuture, Router};
+    /// use hyper::{Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// type PluginHandler = Box<dyn Fn(Request<Body>) -> BoxedHandlerFuture<Body, Infallible> + Send + Sync>;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let plugins: Vec<(&str, PluginHandler)> = vec![
+    ///     ("/stats", Box::new(|_| Box::pin(async move { Ok(Response::new(Body::from("Stats"))) }))),
+    ///     ("/health", Box::new(|_| Box::pin(async move { Ok(Response::new(Body::from("OK"))) }))),
+    /// ];
+    ///
+    /// let mut builder = Router::builder();
+    /// for (path, handler) in plugins {
+    ///     builder = builder.get_boxed(path, handler);
+    /// }
+    /// let router = builder.build().unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_boxed<P, H>(self, path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> BoxedHandlerFuture<B, E> + Send + Sync + 'static,
+    {
+        self.add_boxed(path, vec![Method::GET], handler)
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path only if the condition is `true`, so the
+    /// feature-flagged routes don't break the builder chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let debug = cfg!(debug_assertions);
+    ///
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .get_if(debug, "/debug/info", |_| async move { Ok(Response::new(Body::from("Debug info"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_if<P, H, R>(self, cond: bool, path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.add_if(cond, path, vec![Method::GET], handler)
+    }
+
     /// Adds a new route with `GET` and `HEAD` methods and the handler at the specified path.
     ///
     /// # Examples
@@ -507,15 +830,169 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         self.and_then(move |mut inner| {
-            let mut path = path.into();
+            // The trailing slash is appended on build unless the strict slash mode is on.
+            let mut route = Route::new(path, methods, handler, inner.options.regex_size_limit)?;
+            route.max_size = inner.max_size;
+            inner.routes.push(route);
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Like the [`add`](#method.add) method, but it attaches the specified tags to the route. Refer to the
+    /// [`get_tagged`](#method.get_tagged) method for more info.
+    pub fn add_tagged<P, H, R>(self, path: P, methods: Vec<Method>, tags: &[&str], handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let tags = tags.iter().map(|tag| tag.to_string()).collect::<Arc<[String]>>();
 
-            if !path.ends_with('/') && !path.ends_with('*') {
-                path.push('/');
+        self.add(path, methods, handler).and_then(move |mut inner| {
+            if let Some(route) = inner.routes.last_mut() {
+                route.tags = tags;
             }
+            crate::Result::Ok(inner)
+        })
+    }
 
-            let route = Route::new(path, methods, handler)?;
-            inner.routes.push(route);
+    /// Like the [`add`](#method.add) method, but the route is added only if the condition is `true`. Refer to the
+    /// [`get_if`](#method.get_if) method for more info.
+    pub fn add_if<P, H, R>(self, cond: bool, path: P, methods: Vec<Method>, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        if cond {
+            self.add(path, methods, handler)
+        } else {
+            self
+        }
+    }
+
+    /// Like the [`add`](#method.add) method, but the handler returns a [boxed future](./type.BoxedHandlerFuture.html).
+    /// Refer to the [`get_boxed`](#method.get_boxed) method for more info.
+    pub fn add_boxed<P, H>(self, path: P, methods: Vec<Method>, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<hyper::Body>) -> BoxedHandlerFuture<B, E> + Send + Sync + 'static,
+    {
+        self.add(path, methods, handler)
+    }
+
+    /// Adds a new route with `GET` method and a handler which receives the typed state set by the
+    /// [`with_state`](#method.with_state) method.
+    ///
+    /// The [`build`](#method.build) method will return an error if the state is not set on this router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, State};
+    /// use hyper::{Response, Request, Body};
+    ///
+    /// struct AppState {
+    ///     name: String,
+    /// }
+    ///
+    /// async fn home_handler(state: State<AppState>, _: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    ///     Ok(Response::new(Body::from(format!("Welcome to {}", state.name))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, hyper::Error> {
+    /// let router = Router::builder()
+    ///     .with_state(AppState { name: "my app".to_owned() })
+    ///     .get_with_state("/", home_handler)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_with_state<P, S, H, R>(self, path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        H: Fn(State<S>, Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        self.add_with_state(path, vec![Method::GET], handler)
+    }
+
+    /// Adds a new route with the specified method(s) and a handler which receives the typed state set by the
+    /// [`with_state`](#method.with_state) method.
+    ///
+    /// The [`build`](#method.build) method will return an error if the state is not set on this router.
+    pub fn add_with_state<P, S, H, R>(self, path: P, methods: Vec<Method>, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        H: Fn(State<S>, Request<hyper::Body>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let state = Arc::new(OnceLock::new());
+        let resolved_state = state.clone();
 
+        self.and_then(move |mut inner| {
+            inner
+                .state_resolvers
+                .push(Box::new(move |root_data_map: Option<&DataMap>| {
+                    let state = root_data_map
+                        .and_then(|data_map| data_map.get::<State<S>>())
+                        .cloned()
+                        .ok_or_else(|| {
+                            Error::new(format!(
+                                "A route handler requires the state of type `{}`, but it's not set by `with_state`",
+                                type_name::<S>()
+                            ))
+                        })?;
+                    let _ = resolved_state.set(state);
+                    Ok(())
+                }));
+            crate::Result::Ok(inner)
+        })
+        .add(path, methods, move |req: Request<hyper::Body>| {
+            // A router can only be created by the `build` method, which fails if the state can't be resolved.
+            let state = state
+                .get()
+                .cloned()
+                .expect("Routerify: The state is resolved while building the router");
+            handler(state, req)
+        })
+    }
+
+    /// Adds a route which is created by the [`Route::builder`](./struct.Route.html#method.builder) method, e.g. to set
+    /// custom settings like the maximum size of the request body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Route};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .route(
+    ///         Route::builder("/users")
+    ///             .handler(|_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///             .build()
+    ///             .unwrap(),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn route(self, mut route: Route<B, E>) -> Self {
+        self.and_then(move |mut inner| {
+            if route.max_size == 0 {
+                route.max_size = inner.max_size;
+            }
+            inner.routes.push(route);
             crate::Result::Ok(inner)
         })
     }
@@ -560,20 +1037,26 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         let mut path = path.into();
 
         if path.ends_with('/') {
-            path = (&path[..path.len() - 1]).to_string();
+            path = path[..path.len() - 1].to_string();
         }
 
+        // The paths are prefixed right away, so they're compiled with the size limit which is set at this point.
+        let regex_size_limit = self
+            .inner
+            .as_ref()
+            .ok()
+            .and_then(|inner| inner.options.regex_size_limit);
         let mut builder = self;
 
         for pre_middleware in router.pre_middlewares.iter_mut() {
-            let new_pre_middleware = PreMiddleware::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), pre_middleware.path.as_str()),
+            let new_pre_middleware =
                 pre_middleware
                     .handler
                     .take()
-                    .expect("No handler found in one of the pre-middlewares"),
-                pre_middleware.scope_depth + 1,
-            );
+                    .ok_or_else(mounted_router_error)
+                    .and_then(|handler| {
+                        pre_middleware.with_prefix(&path, handler, pre_middleware.scope_depth + 1, regex_size_limit)
+                    });
             builder = builder.and_then(move |mut inner| {
                 inner.pre_middlewares.push(new_pre_middleware?);
                 crate::Result::Ok(inner)
@@ -581,27 +1064,30 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         }
 
         for route in router.routes.iter_mut() {
-            let new_route = Route::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), route.path.as_str()),
-                route.methods.clone(),
-                route.handler.take().expect("No handler found in one of the routes"),
-                route.scope_depth + 1,
-            );
+            let new_route = route
+                .handler
+                .take()
+                .ok_or_else(mounted_router_error)
+                .and_then(|handler| route.with_prefix(&path, handler, route.scope_depth + 1, regex_size_limit));
             builder = builder.and_then(move |mut inner| {
-                inner.routes.push(new_route?);
+                // A scoped route without its own limit inherits the limit of this router.
+                let mut new_route = new_route?;
+                if new_route.max_size == 0 {
+                    new_route.max_size = inner.max_size;
+                }
+                inner.routes.push(new_route);
                 crate::Result::Ok(inner)
             });
         }
 
         for post_middleware in router.post_middlewares.iter_mut() {
-            let new_post_middleware = PostMiddleware::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), post_middleware.path.as_str()),
-                post_middleware
-                    .handler
-                    .take()
-                    .expect("No handler found in one of the post-middlewares"),
-                post_middleware.scope_depth + 1,
-            );
+            let new_post_middleware = post_middleware
+                .handler
+                .take()
+                .ok_or_else(mounted_router_error)
+                .and_then(|handler| {
+                    post_middleware.with_prefix(&path, handler, post_middleware.scope_depth + 1, regex_size_limit)
+                });
             builder = builder.and_then(move |mut inner| {
                 inner.post_middlewares.push(new_post_middleware?);
                 crate::Result::Ok(inner)
@@ -610,15 +1096,18 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 
         for scoped_data_map in router.scoped_data_maps.iter_mut() {
             let new_path = format!("{}{}", path.as_str(), scoped_data_map.path.as_str());
-            let data_map = Arc::try_unwrap(
-                scoped_data_map
-                    .data_map
-                    .take()
-                    .expect("No data map found in one of the scoped data maps"),
-            )
-            .expect("Non-zero owner of the shared data map in one of the scoped data maps");
+            let data_map = scoped_data_map.data_map.take().ok_or_else(mounted_router_error);
 
             builder = builder.and_then(move |mut inner| {
+                // The data map is still shared if the router is also mounted by the `scope_cloned` method, so keep sharing it.
+                let data_map = match Arc::try_unwrap(data_map?) {
+                    Ok(data_map) => data_map,
+                    Err(data_map) => {
+                        inner.shared_data_maps.push((new_path, data_map));
+                        return crate::Result::Ok(inner);
+                    }
+                };
+
                 let data_maps = &mut inner.data_maps;
 
                 let data_map_arr = data_maps.get_mut(&new_path);
@@ -634,56 +1123,554 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
 
         builder
     }
-}
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    RouterBuilder<B, E>
-{
-    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
-    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
+    /// Like the [`scope`](#method.scope) method, but it mounts a copy of the router, so the same router can be mounted at
+    /// multiple paths. The handlers and the shared data are not cloned, they are shared between the copies.
     ///
     /// # Examples
     ///
     /// ```
-    /// use routerify::{Router, Middleware};
-    /// use hyper::{Response, Request, Body};
-    /// use std::convert::Infallible;
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    ///
+    /// fn api_router() -> Router<Body, hyper::Error> {
+    ///     Router::builder()
+    ///         .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, hyper::Error> {
+    /// let api = api_router();
     ///
-    /// # fn run() -> Router<Body, Infallible> {
     /// let router = Router::builder()
-    ///      // Create and attach a pre middleware.
-    ///      .middleware(Middleware::pre(|req| async move { /* Do some operations */ Ok(req) }))
-    ///      // Create and attach a post middleware.
-    ///      .middleware(Middleware::post(|res| async move { /* Do some operations */ Ok(res) }))
-    ///      .build()
-    ///      .unwrap();
+    ///     // Now, the app can handle requests on both `/v1/users` and `/v2/users` paths.
+    ///     .scope_cloned("/v1", &api)
+    ///     .scope_cloned("/v2", &api)
+    ///     .build()
+    ///     .unwrap();
     /// # router
     /// # }
     /// # run();
     /// ```
-    pub fn middleware(self, m: Middleware<B, E>) -> Self {
-        self.and_then(move |mut inner| {
-            match m {
-                Middleware::Pre(middleware) => {
-                    inner.pre_middlewares.push(middleware);
-                }
-                Middleware::Post(middleware) => {
-                    inner.post_middlewares.push(middleware);
-                }
-            }
-            crate::Result::Ok(inner)
-        })
-    }
+    pub fn scope_cloned<P>(self, path: P, router: &Router<B, E>) -> Self
+    where
+        P: Into<String>,
+    {
+        let mut path = path.into();
 
-    /// Specify app data to be shared across route handlers, middlewares and the error handler.
-    ///
-    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
-    pub fn data<T: Send + Sync + 'static>(self, data: T) -> Self {
-        self.and_then(move |mut inner| {
-            let data_maps = &mut inner.data_maps;
+        if path.ends_with('/') {
+            path = path[..path.len() - 1].to_string();
+        }
 
-            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
-            if let Some(data_map_arr) = data_map_arr {
+        // The paths are prefixed right away, so they're compiled with the size limit which is set at this point.
+        let regex_size_limit = self
+            .inner
+            .as_ref()
+            .ok()
+            .and_then(|inner| inner.options.regex_size_limit);
+        let mut builder = self;
+
+        for pre_middleware in router.pre_middlewares.iter() {
+            let new_pre_middleware = pre_middleware
+                .handler
+                .clone()
+                .ok_or_else(mounted_router_error)
+                .and_then(|handler| {
+                    pre_middleware.with_prefix(&path, handler, pre_middleware.scope_depth + 1, regex_size_limit)
+                });
+            builder = builder.and_then(move |mut inner| {
+                inner.pre_middlewares.push(new_pre_middleware?);
+                crate::Result::Ok(inner)
+            });
+        }
+
+        for route in router.routes.iter() {
+            let new_route = route
+                .handler
+                .clone()
+                .ok_or_else(mounted_router_error)
+                .and_then(|handler| route.with_prefix(&path, handler, route.scope_depth + 1, regex_size_limit));
+            builder = builder.and_then(move |mut inner| {
+                // A scoped route without its own limit inherits the limit of this router.
+                let mut new_route = new_route?;
+                if new_route.max_size == 0 {
+                    new_route.max_size = inner.max_size;
+                }
+                inner.routes.push(new_route);
+                crate::Result::Ok(inner)
+            });
+        }
+
+        for post_middleware in router.post_middlewares.iter() {
+            let new_post_middleware = post_middleware
+                .handler
+                .clone()
+                .ok_or_else(mounted_router_error)
+                .and_then(|handler| {
+                    post_middleware.with_prefix(&path, handler, post_middleware.scope_depth + 1, regex_size_limit)
+                });
+            builder = builder.and_then(move |mut inner| {
+                inner.post_middlewares.push(new_post_middleware?);
+                crate::Result::Ok(inner)
+            });
+        }
+
+        for scoped_data_map in router.scoped_data_maps.iter() {
+            let new_path = format!("{}{}", path.as_str(), scoped_data_map.path.as_str());
+            let data_map = scoped_data_map.data_map.clone().ok_or_else(mounted_router_error);
+
+            builder = builder.and_then(move |mut inner| {
+                inner.shared_data_maps.push((new_path, data_map?));
+                crate::Result::Ok(inner)
+            });
+        }
+
+        builder
+    }
+
+    /// Mounts the routes, the middlewares and the data of the router at the root path like the [`scope`](#method.scope)
+    /// method, but the error handler of the router is kept, so it handles the errors of the router's routes instead of
+    /// the error handler of this router. It lets a module own its error responses even when its routes aren't prefixed.
+    ///
+    /// Only the errors of the router's routes are handled by its error handler. The errors of its pre and post middlewares
+    /// are still handled by the error handler of this router, as the middlewares run for the requests of other routes too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteError};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// fn billing_router() -> Router<Body, Infallible> {
+    ///     Router::builder()
+    ///         .get("/invoices", |_| async move { Ok(Response::new(Body::from("Invoice list"))) })
+    ///         .err_handler(|err: RouteError| async move {
+    ///             Response::builder()
+    ///                 .status(StatusCode::SERVICE_UNAVAILABLE)
+    ///                 .body(Body::from(format!("Billing is unavailable: {}", err)))
+    ///                 .unwrap()
+    ///         })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .scope_at_root(billing_router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn scope_at_root(self, mut router: Router<B, E>) -> Self {
+        if let Some(err_handler) = router.err_handler.take().map(Arc::new) {
+            for route in router.routes.iter_mut() {
+                // The routes of a nested router which is also mounted at the root keep their own error handler.
+                if route.err_handler.is_none() {
+                    route.err_handler = Some(err_handler.clone());
+                }
+            }
+        }
+
+        self.scope("", router)
+    }
+
+    /// Mounts the router at the specified path like the [`scope`](#method.scope) method, but only if the condition is
+    /// `true`, e.g. to mount the debug endpoints in development only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// fn debug_router() -> Router<Body, Infallible> {
+    ///     Router::builder()
+    ///         .get("/info", |_| async move { Ok(Response::new(Body::from("Debug info"))) })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .scope_if(cfg!(debug_assertions), "/debug", debug_router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn scope_if<P>(self, cond: bool, path: P, router: Router<B, E>) -> Self
+    where
+        P: Into<String>,
+    {
+        if cond {
+            self.scope(path, router)
+        } else {
+            self
+        }
+    }
+
+    /// Mounts the router at the specified path like the [`scope`](#method.scope) method, but the specified middlewares
+    /// are added to the router first, so they only run for the requests within the scope, e.g. to protect an existing
+    /// module router without rebuilding it.
+    ///
+    /// The pre middlewares run before the router's own pre middlewares and the post middlewares run after the router's own
+    /// post middlewares, so they wrap the router. The added middlewares are ordered by their
+    /// [priorities](#method.middleware_with_priority) among themselves, as the scoped router's middlewares are already
+    /// sorted when it's built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// fn admin_router() -> Router<Body, Infallible> {
+    ///     Router::builder()
+    ///         .get("/stats", |_| async move { Ok(Response::new(Body::from("Admin stats"))) })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .scope_with_middleware(
+    ///         "/admin",
+    ///         vec![Middleware::pre(|req| async move {
+    ///             println!("Admin request: {}", req.uri().path());
+    ///             Ok(req)
+    ///         })],
+    ///         admin_router(),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn scope_with_middleware<P, I>(self, path: P, middlewares: I, mut router: Router<B, E>) -> Self
+    where
+        P: Into<String>,
+        I: IntoIterator<Item = Middleware<B, E>>,
+    {
+        let mut pre_middlewares = Vec::new();
+        let mut post_middlewares = Vec::new();
+        for middleware in middlewares {
+            match middleware {
+                Middleware::Pre(middleware) => pre_middlewares.push(middleware),
+                Middleware::Post(middleware) => post_middlewares.push(middleware),
+            }
+        }
+
+        // The scoped middlewares keep their order on the build of this router, so sort the added ones once here.
+        pre_middlewares.sort_by_key(|m| m.priority);
+        post_middlewares.sort_by_key(|m| m.priority);
+        router.pre_middlewares.splice(0..0, pre_middlewares);
+        router.post_middlewares.extend(post_middlewares);
+
+        self.scope(path, router)
+    }
+
+    /// Delegates all the requests at the specified path and under it to an arbitrary `hyper` service, e.g. to integrate a
+    /// handler built by another framework.
+    ///
+    /// The service is cloned for every request and it receives the request unchanged, i.e. with the full path. The
+    /// middlewares of this router run around it like around any other route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{service::service_fn, Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let legacy = service_fn(|req: Request<Body>| async move {
+    ///     Ok::<_, Infallible>(Response::new(Body::from(format!("Legacy app: {}", req.uri().path()))))
+    /// });
+    ///
+    /// let router = Router::builder()
+    ///     .scope_service("/legacy", legacy)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn scope_service<P, S>(self, path: P, service: S) -> Self
+    where
+        P: Into<String>,
+        S: Service<Request<hyper::Body>, Response = Response<B>> + Clone + Send + Sync + 'static,
+        S::Error: Into<E>,
+        S::Future: Send + 'static,
+    {
+        let mut path = path.into();
+        if path.ends_with('/') {
+            path.pop();
+        }
+
+        let handler = move |req: Request<hyper::Body>| {
+            let mut service = service.clone();
+            async move {
+                poll_fn(|cx| service.poll_ready(cx)).await.map_err(Into::into)?;
+                service.call(req).await.map_err(Into::into)
+            }
+        };
+
+        let builder = if path.is_empty() {
+            self
+        } else {
+            self.any_method(path.as_str(), handler.clone())
+        };
+        builder.any_method(format!("{}/*", path), handler)
+    }
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    RouterBuilder<B, E>
+{
+    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
+    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::{Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      // Create and attach a pre middleware.
+    ///      .middleware(Middleware::pre(|req| async move { /* Do some operations */ Ok(req) }))
+    ///      // Create and attach a post middleware.
+    ///      .middleware(Middleware::post(|res| async move { /* Do some operations */ Ok(res) }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn middleware(self, m: Middleware<B, E>) -> Self {
+        self.and_then(move |mut inner| {
+            match m {
+                Middleware::Pre(middleware) => {
+                    inner.pre_middlewares.push(middleware);
+                }
+                Middleware::Post(middleware) => {
+                    inner.post_middlewares.push(middleware);
+                }
+            }
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a single middleware with an explicit priority, so the execution order doesn't depend on the registration order.
+    ///
+    /// The pre and the post middlewares run in the ascending order of their priorities and the middlewares with the same
+    /// priority run in the registration order. The middlewares added by the [`middleware`](#method.middleware) method have
+    /// the priority `0`.
+    ///
+    /// The priorities only order the middlewares of the same scope level. The middlewares of a scoped router are ordered by
+    /// their priorities when that router is built and they keep the position where the router is scoped, while the
+    /// middlewares of this router are ordered around them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::pre(|req| async move { /* Runs second */ Ok(req) }))
+    ///      .middleware_with_priority(-10, Middleware::pre(|req| async move { /* Runs first */ Ok(req) }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn middleware_with_priority(self, priority: i32, m: Middleware<B, E>) -> Self {
+        self.and_then(move |mut inner| {
+            match m {
+                Middleware::Pre(mut middleware) => {
+                    middleware.priority = priority;
+                    inner.pre_middlewares.push(middleware);
+                }
+                Middleware::Post(mut middleware) => {
+                    middleware.priority = priority;
+                    inner.post_middlewares.push(middleware);
+                }
+            }
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds multiple middlewares in the specified order, e.g. the pre and the post middlewares returned by the
+    /// [`utility::middlewares`](./utility/middlewares/index.html) functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middlewares(vec![
+    ///          Middleware::pre(|req| async move { Ok(req) }),
+    ///          Middleware::post(|res| async move { Ok(res) }),
+    ///      ])
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn middlewares<I>(self, middlewares: I) -> Self
+    where
+        I: IntoIterator<Item = Middleware<B, E>>,
+    {
+        middlewares
+            .into_iter()
+            .fold(self, |builder, middleware| builder.middleware(middleware))
+    }
+
+    /// Marks the route at the specified path as deprecated, so its responses have the `Deprecation: true` header (RFC 8594).
+    /// With the `log` feature, the hits of the route are logged at the warn level along with the specified message.
+    ///
+    /// Use the [`deprecated_with_sunset`](#method.deprecated_with_sunset) method to also announce when the route will be
+    /// removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/v1/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .deprecated("/v1/users", "Use /v2/users instead")
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn deprecated<P: Into<String>, M: Into<String>>(self, path: P, message: M) -> Self {
+        self.add_deprecation(path.into(), message.into(), None)
+    }
+
+    /// Like the [`deprecated`](#method.deprecated) method, but the responses also have the `Sunset` header (RFC 8594) with
+    /// the specified HTTP date, e.g. `Sat, 31 Dec 2022 23:59:59 GMT`.
+    ///
+    /// The [`build`](#method.build) method will return an error if the sunset date is not a valid header value.
+    pub fn deprecated_with_sunset<P: Into<String>, M: Into<String>>(self, path: P, message: M, sunset: &str) -> Self {
+        self.add_deprecation(path.into(), message.into(), Some(sunset.to_owned()))
+    }
+
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+    fn add_deprecation(self, path: String, message: String, sunset: Option<String>) -> Self {
+        self.and_then(move |mut inner| {
+            let sunset = sunset
+                .map(|sunset| {
+                    HeaderValue::from_str(sunset.as_str())
+                        .map_err(|e| Error::new(format!("Invalid sunset date: {}: {}", sunset, e)))
+                })
+                .transpose()?;
+
+            #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+            let middleware =
+                PostMiddleware::new_with_info(path, move |mut res: Response<B>, req_info: RequestInfo| {
+                    #[cfg(feature = "log")]
+                    log::warn!(
+                        "Deprecated route is hit: {} {}: {}",
+                        req_info.method(),
+                        req_info.uri().path(),
+                        message
+                    );
+
+                    let headers = res.headers_mut();
+                    headers.insert("deprecation", HeaderValue::from_static("true"));
+                    if let Some(ref sunset) = sunset {
+                        headers.insert("sunset", sunset.clone());
+                    }
+
+                    async move { Ok(res) }
+                })?;
+            // Match the same paths as the route, so the path gets the same trailing slash on build.
+            inner.deprecation_idxs.push(inner.post_middlewares.len());
+            inner.post_middlewares.push(middleware);
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds the specified headers to every response. A header which is already set by the route handler or the previous
+    /// middlewares is not overwritten.
+    ///
+    /// It registers a post middleware at the `/*` path, so the order of registration matters with respect to the other
+    /// post middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{header, header::HeaderValue, Body, HeaderMap, Response};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    ///
+    /// let router = Router::builder()
+    ///      .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///      .default_headers(headers)
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn default_headers(self, headers: HeaderMap) -> Self {
+        let headers = Arc::new(headers);
+
+        self.middleware(Middleware::post(move |mut res: Response<B>| {
+            let headers = headers.clone();
+            async move {
+                let res_headers = res.headers_mut();
+                for name in headers.keys() {
+                    if !res_headers.contains_key(name) {
+                        for val in headers.get_all(name) {
+                            res_headers.append(name, val.clone());
+                        }
+                    }
+                }
+                Ok(res)
+            }
+        }))
+    }
+
+    /// Specify app data to be shared across route handlers, middlewares and the error handler.
+    ///
+    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
+    pub fn data<T: Send + Sync + 'static>(self, data: T) -> Self {
+        self.and_then(move |mut inner| {
+            let data_maps = &mut inner.data_maps;
+
+            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
+            if let Some(data_map_arr) = data_map_arr {
                 let first_data_map = data_map_arr.get_mut(0).unwrap();
                 first_data_map.insert(data);
             } else {
@@ -696,40 +1683,1502 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         })
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
-    /// for more info.
-    pub fn err_handler<H, R>(self, handler: H) -> Self
+    /// Like the [`data`](#method.data) method, but the data is shared by the specified name, so multiple values of the same
+    /// type can be shared, e.g. the connection pools of the primary and the replica databases. It's accessed by the
+    /// [`named_data`](./ext/trait.RequestExt.html#tymethod.named_data) method with the same name and type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .named_data("primary", String::from("postgres://primary"))
+    ///     .named_data("replica", String::from("postgres://replica"))
+    ///     .get("/users", |req| async move {
+    ///         let db_url = req.named_data::<String>("replica").unwrap();
+    ///         Ok(Response::new(Body::from(format!("Reading users from {}", db_url))))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn named_data<N: Into<String>, T: Send + Sync + 'static>(self, name: N, data: T) -> Self {
+        let name = name.into();
+
+        self.and_then(move |mut inner| {
+            inner
+                .data_maps
+                .entry("/*".to_owned())
+                .or_insert_with(|| vec![DataMap::new()])
+                .get_mut(0)
+                .unwrap()
+                .insert_named(name, data);
+
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Like the [`data`](#method.data) method, but the data is wrapped in an `Arc`, so it can be accessed as an owned
+    /// `Arc<T>` by the [`data_arc`](./ext/trait.RequestExt.html#tymethod.data_arc) method, e.g. to move it into a spawned
+    /// task.
+    pub fn data_shared<T: Send + Sync + 'static>(self, data: T) -> Self {
+        self.data(Arc::new(data))
+    }
+
+    /// Sets the typed state which is passed to the handlers registered by the [`get_with_state`](#method.get_with_state)
+    /// and the [`add_with_state`](#method.add_with_state) methods. It can be accessed by the
+    /// [`data`](./ext/trait.RequestExt.html#tymethod.data) method as `State<S>` too.
+    pub fn with_state<S: Send + Sync + 'static>(self, state: S) -> Self {
+        self.data(State::new(state))
+    }
+
+    /// Like the [`data`](#method.data) method, but the [`build`](#method.build) method will return an error if a data of the
+    /// same type is already shared by this router, instead of silently overwriting it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::Body;
+    /// use std::convert::Infallible;
+    ///
+    /// let result: routerify::Result<Router<Body, Infallible>> = Router::builder()
+    ///     .try_data(1u32)
+    ///     .try_data(2u32)
+    ///     .build();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn try_data<T: Send + Sync + 'static>(self, data: T) -> Self {
+        self.and_then(move |inner| {
+            let already_registered = inner
+                .data_maps
+                .get("/*")
+                .and_then(|data_map_arr| data_map_arr.first())
+                .map(|data_map| data_map.contains::<T>())
+                .unwrap_or(false);
+
+            if already_registered {
+                return Err(Error::new(format!(
+                    "A data of type `{}` is already registered in the router",
+                    type_name::<T>()
+                ))
+                .into());
+            }
+
+            crate::Result::Ok(inner)
+        })
+        .data(data)
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
+    /// for more info.
+    pub fn err_handler<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
+
+        self.and_then(move |mut inner| {
+            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares.
+    ///
+    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
+    ///
+    /// Please refer to [Error Handling](./index.html#error-handling) section
+    /// for more info.
+    pub fn err_handler_with_info<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::RouteError, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: ErrHandlerWithInfo<B> =
+            Box::new(move |err: crate::RouteError, req_info: RequestInfo| Box::new(handler(err, req_info)));
+
+        self.and_then(move |mut inner| {
+            inner.err_handler = Some(ErrHandler::WithInfo(handler));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares, which receives a clone of the data of
+    /// the specified type shared by the [`data`](#method.data) method, e.g. a config to render the error responses. The
+    /// data is `None` if it's not shared for the request path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteError};
+    /// use hyper::{Response, Body, StatusCode};
+    /// use std::sync::Arc;
+    /// # use std::convert::Infallible;
+    ///
+    /// struct ErrorConfig {
+    ///     support_email: String,
+    /// }
+    ///
+    /// async fn error_handler(err: RouteError, config: Option<Arc<ErrorConfig>>) -> Response<Body> {
+    ///     let contact = config.map(|config| config.support_email.clone()).unwrap_or_default();
+    ///     Response::builder()
+    ///         .status(StatusCode::INTERNAL_SERVER_ERROR)
+    ///         .body(Body::from(format!("Something went wrong: {}, contact: {}", err, contact)))
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .data(Arc::new(ErrorConfig { support_email: "help@example.com".to_owned() }))
+    ///     .err_handler_with_data(error_handler)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn err_handler_with_data<T, H, R>(self, handler: H) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+        H: Fn(crate::RouteError, Option<T>) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        self.err_handler_with_info(move |err: crate::RouteError, req_info: RequestInfo| {
+            handler(err, req_info.data::<T>().cloned())
+        })
+    }
+
+    /// Adds a function which rewrites the request path before matching, e.g. to strip a version prefix or to map the legacy
+    /// paths. If it returns `Some`, the request is routed by the rewritten path and the route handlers see the rewritten
+    /// uri, the query is kept. The received uri is still available by the
+    /// [`RequestExt::original_uri`](./ext/trait.RequestExt.html#tymethod.original_uri) method.
+    ///
+    /// The rewrite is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .rewrite(|path: &str| path.strip_prefix("/v0").map(|path| path.to_owned()))
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn rewrite<F>(self, rewrite: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.rewrite = Some(Box::new(rewrite));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a hook which is called with the [request info](./struct.RequestInfo.html) of every incoming request before it's
+    /// dispatched, e.g. to open a tracing span. Unlike a middleware, it's called even if no route matches the request.
+    ///
+    /// The hooks are only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RequestInfo};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .on_request(|req_info: &RequestInfo| println!("--> {} {}", req_info.method(), req_info.path()))
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn on_request<H>(self, hook: H) -> Self
+    where
+        H: Fn(&RequestInfo) + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.on_request = Some(Box::new(hook));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a hook which is called with the [request info](./struct.RequestInfo.html) and the final response of every
+    /// request, after the post middlewares and the error handler. It's not called if the request fails without a response,
+    /// i.e. when no error handler could handle the error.
+    ///
+    /// The hooks are only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RequestInfo};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .on_response(|req_info: &RequestInfo, res: &Response<Body>| {
+    ///         println!("<-- {} {} {}", req_info.method(), req_info.path(), res.status())
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn on_response<H>(self, hook: H) -> Self
+    where
+        H: Fn(&RequestInfo, &Response<B>) + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.on_response = Some(Box::new(hook));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the factory which builds the responses of the default `OPTIONS` route, the default 404 route and the default
+    /// error handler from a status and a plain text body. The text body is empty for the `OPTIONS` and `HEAD` requests.
+    ///
+    /// The defaults are only provided out of the box when the body type is `hyper::Body`, so a router with any other body
+    /// type needs this factory to get them, otherwise it only warns about the missing defaults. It's only respected on the
+    /// root router and it's inherited by the fallback router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::Response;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<String, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new("Home page".to_owned())) })
+    ///     .default_response_factory(|status, text| {
+    ///         Response::builder().status(status).body(text).unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn default_response_factory<F>(self, factory: F) -> Self
+    where
+        F: Fn(StatusCode, String) -> Response<B> + Send + Sync + 'static,
+    {
+        self.and_then(move |mut inner| {
+            inner.response_factory = Some(Arc::new(factory));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Registers an error type which suggests the response status by the [`HasStatusCode`](./trait.HasStatusCode.html)
+    /// trait, so the default error handler responds with that status instead of `500 Internal Server Error` when a route or
+    /// a middleware fails with this error. The errors caused by this error type are respected too.
+    ///
+    /// It has no effect if a custom error handler is set, use the
+    /// [`RouteErrorExt::status_hint`](./trait.RouteErrorExt.html#tymethod.status_hint) method there instead. It's only
+    /// respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{HasStatusCode, Router};
+    /// use hyper::{Response, Body, StatusCode};
+    ///
+    /// #[derive(Debug)]
+    /// struct ValidationError;
+    ///
+    /// impl std::fmt::Display for ValidationError {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    ///         write!(f, "Invalid input")
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for ValidationError {}
+    ///
+    /// impl HasStatusCode for ValidationError {
+    ///     fn status_code(&self) -> StatusCode {
+    ///         StatusCode::BAD_REQUEST
+    ///     }
+    /// }
+    ///
+    /// # fn run() -> Router<Body, ValidationError> {
+    /// let router = Router::builder()
+    ///     // It responds with `400 Bad Request`.
+    ///     .post("/users", |_| async move { Err::<Response<Body>, _>(ValidationError) })
+    ///     .error_status::<ValidationError>()
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn error_status<T: HasStatusCode + std::error::Error + 'static>(self) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.status_hints.push(|err| err.status_hint::<T>());
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Collapses the consecutive slashes in the request path into a single one before matching the routes, so a request to
+    /// `/users//42` will be handled by the `/users/:id` route. It's disabled by default.
+    ///
+    /// The encoded slashes (`%2F`) are left untouched. This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .merge_slashes(true)
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn merge_slashes(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.merge_slashes = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the base path which prefixes all the routes, the middlewares and the scoped data of this router on build, like
+    /// mounting them by the [`scope`](#method.scope) method without an extra nesting level. It's useful when the same
+    /// router is deployed at different base paths.
+    ///
+    /// The default routes, e.g. the default 404 route, are not prefixed, so they still handle the requests outside the base
+    /// path. The base path is reported as the scope prefix of the routes by the
+    /// [`RequestExt::scope_prefix`](./ext/trait.RequestExt.html#tymethod.scope_prefix) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let base_path = std::env::var("BASE_PATH").unwrap_or_else(|_| "/api/v1".to_owned());
+    ///
+    /// let router = Router::builder()
+    ///     .base_path(base_path)
+    ///     // It handles the `/api/v1/users` requests by default.
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn base_path<P: Into<String>>(self, path: P) -> Self {
+        self.and_then(move |mut inner| {
+            let mut path = path.into();
+            if path.ends_with('/') {
+                path.pop();
+            }
+
+            inner.base_path = path;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the request path is percent decoded before matching the routes. It's enabled by default.
+    ///
+    /// When it's disabled, the routes are matched against the raw path and the route parameters are decoded afterwards, so
+    /// an encoded slash (`%2F`) doesn't split a path segment, e.g. a request to `/files/a%2Fb/meta` will be handled by
+    /// the `/files/:name/meta` route with the `a/b` parameter value. This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .decode_before_match(false)
+    ///     .get("/files/:name/meta", |req| async move {
+    ///         let name = req.param("name").unwrap().to_owned();
+    ///         Ok(Response::new(Body::from(name)))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn decode_before_match(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.decode_before_match = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the backend which matches the request paths against the routes. It's [`Matcher::RegexSet`](./enum.Matcher.html)
+    /// by default.
+    ///
+    /// The [`Matcher::Trie`](./enum.Matcher.html) backend matches the routes with literal paths by a path trie and falls
+    /// back to the regex matching only for the routes with parameters or `*` globs, which is faster for the large route
+    /// tables. Both backends match the same routes. This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Matcher, Router};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .matcher(Matcher::Trie)
+    ///     .get("/about", |_| async move { Ok(Response::new(Body::from("About page"))) })
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn matcher(self, matcher: Matcher) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.matcher = matcher;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the maximum size in bytes of the compiled path regexes, so a path which is too expensive to match, e.g. one
+    /// with lots of parameters, is rejected with an error. The limit applies to both the compiled program and the lazy DFA
+    /// cache of a regex. There's no limit by default besides the ones of the `regex` crate.
+    ///
+    /// The regexes are compiled with the limit as the routes added after it are created, so the [`build`](#method.build)
+    /// method returns the error. It applies to the routers scoped after it too. The regex set which matches all the paths
+    /// of the router at once is also compiled with the limit when the router is warmed up or starts serving, so a path
+    /// which is created outside of the builder, e.g. by the [`Middleware::pre`](./enum.Middleware.html#method.pre)
+    /// method, is covered by it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// let result: routerify::Result<Router<Body, Infallible>> = Router::builder()
+    ///     .regex_size_limit(64)
+    ///     .get("/:a/:b/:c/:d/:e/:f/:g/:h", |_| async move { Ok(Response::new(Body::from("Too many params"))) })
+    ///     .build();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn regex_size_limit(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.regex_size_limit = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the maximum length in bytes of the request path, a request with a longer path is rejected with the
+    /// `414 URI Too Long` response before matching. The path is checked as it's received, i.e. before it's rewritten,
+    /// decoded or its slashes are merged. There's no limit by default.
+    ///
+    /// Like the other options, it's only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .max_path_length(1024)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn max_path_length(self, bytes: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.max_path_length = Some(bytes);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the maximum number of non-empty segments of the request path, a request with more segments is rejected
+    /// with the `414 URI Too Long` response before matching, e.g. `/users/1/books` has three segments. The path is
+    /// checked as it's received, like the [`max_path_length`](#method.max_path_length) option. There's no limit by default.
+    ///
+    /// Like the other options, it's only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .max_path_segments(16)
+    ///     .get("/users/:userId/books", |_| async move { Ok(Response::new(Body::from("User books"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn max_path_segments(self, segments: usize) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.max_path_segments = Some(segments);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the paths are matched exactly, so a trailing slash is significant, e.g. the `/users` route doesn't match
+    /// the `/users/` request path. It's disabled by default, which means a trailing slash is appended to the route paths
+    /// and to the request paths before matching.
+    ///
+    /// It applies to all the routes of the router regardless of the call order, including the routes added by the
+    /// [`route`](#method.route) method. Like the other options, it's only respected on the root router, so enable it on
+    /// the scoped routers too to register their routes without a trailing slash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .strict_slash(true)
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .get("/users/", |_| async move { Ok(Response::new(Body::from("User directory"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn strict_slash(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.strict_slash = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the `Connection: keep-alive` header is added to the HTTP/1.x responses. It's disabled by default.
+    ///
+    /// The header is not added to the HTTP/2 responses, as the connection-specific headers are not allowed there, and it
+    /// doesn't override a `Connection` header set by a handler or a middleware. This option is only respected on the root
+    /// router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .keep_alive(true)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn keep_alive(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.keep_alive = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets whether the default 404 route and the default error handler respond with `application/json` bodies instead of
+    /// the `text/plain` ones. The body has the `status` and the `error` fields, and the `message` field with the error
+    /// message for the default error handler, e.g. `{"error":"Not Found","status":404}`.
+    ///
+    /// It's available only if the `json` feature is enabled and it's only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .json_errors(true)
+    ///     .get("/api/users", |_| async move { Ok(Response::new(Body::from("[]"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn json_errors(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.json_errors = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Appends the specified charset to the text-like `Content-Type` headers of the responses which don't have a charset, e.g.
+    /// `text/html` becomes `text/html; charset=utf-8`. The `text/*`, the JSON, the JavaScript and the XML types are
+    /// considered as text-like, the other types e.g. `application/octet-stream` are left as is.
+    ///
+    /// This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{header, Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .default_charset("utf-8")
+    ///     .get("/", |_| async move {
+    ///         Ok(Response::builder()
+    ///             .header(header::CONTENT_TYPE, "text/html")
+    ///             .body(Body::from("<h1>Home page</h1>"))
+    ///             .unwrap())
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn default_charset<C: Into<String>>(self, charset: C) -> Self {
+        let charset = charset.into();
+
+        self.and_then(move |mut inner| {
+            if charset.is_empty() || HeaderValue::from_str(charset.as_str()).is_err() || charset.contains(';') {
+                return Err(Error::new(format!("Invalid default charset: {:?}", charset)).into());
+            }
+
+            inner.options.default_charset = Some(charset);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Trusts the headers set by a reverse proxy or a TLS terminator, e.g. the `X-Forwarded-Proto` header will be used by the
+    /// [`RequestExt::scheme`](./ext/trait.RequestExt.html#tymethod.scheme) method to determine the original request scheme.
+    /// It's disabled by default, enable it only if the app is running behind a trusted proxy.
+    ///
+    /// This option is only respected on the root router.
+    pub fn trust_proxy_headers(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.trust_proxy_headers = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Fails the request body stream if no bytes arrive within the specified duration after the last chunk, which protects
+    /// the server from the clients which send the body too slowly. It's disabled by default.
+    ///
+    /// The timeout error is surfaced to the route handler while reading the body. It requires the tokio runtime with the
+    /// time driver enabled. This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    ///
+    /// # fn run() -> Router<Body, hyper::Error> {
+    /// let router = Router::builder()
+    ///     .body_read_timeout(Duration::from_secs(10))
+    ///     .post("/upload", |req| async move {
+    ///         let body = hyper::body::to_bytes(req.into_body()).await?;
+    ///         Ok(Response::new(Body::from(format!("Received {} bytes", body.len()))))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn body_read_timeout(self, timeout: Duration) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.body_read_timeout = Some(timeout);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Limits the time to process a request as a whole, i.e. the pre middlewares, the route handler and the post
+    /// middlewares together, unlike the [`body_read_timeout`](#method.body_read_timeout) option which only limits the
+    /// pauses while reading the body. It's disabled by default.
+    ///
+    /// When the timeout expires, the processing is dropped and a [`RequestTimeoutError`](./struct.RequestTimeoutError.html)
+    /// is passed to the error handler. The default error handler responds with `504 Gateway Timeout`, use the
+    /// [`request_timeout_status`](#method.request_timeout_status) method to change it. It requires the tokio runtime with
+    /// the time driver enabled. This option is only respected on the root router.
+    ///
+    /// The [`RequestTimeoutError`](./struct.RequestTimeoutError.html) is always passed to the error handler of the root
+    /// router, not to the error handler of a scoped router, because the timeout can expire before any route is selected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .request_timeout(Duration::from_secs(30))
+    ///     .get("/report", |_| async move { Ok(Response::new(Body::from("Monthly report"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn request_timeout(self, timeout: Duration) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.request_timeout = Some(timeout);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the status suggested by the [`RequestTimeoutError`](./struct.RequestTimeoutError.html) when a request exceeds
+    /// the [`request_timeout`](#method.request_timeout), e.g. `503 Service Unavailable`. It's `504 Gateway Timeout` by
+    /// default. This option is only respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, StatusCode};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .request_timeout(Duration::from_secs(30))
+    ///     .request_timeout_status(StatusCode::SERVICE_UNAVAILABLE)
+    ///     .get("/report", |_| async move { Ok(Response::new(Body::from("Monthly report"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn request_timeout_status(self, status: StatusCode) -> Self {
+        self.and_then(move |mut inner| {
+            inner.options.request_timeout_status = status;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Sets the maximum size of the request body in bytes for the routes added after it, a larger request is rejected
+    /// with `413 Payload Too Large`. It's `0` by default which means unlimited.
+    ///
+    /// A route which sets its own limit by the [`RouteBuilder::max_size`](./struct.RouteBuilder.html#method.max_size)
+    /// method keeps it. The routes of a router mounted by the [`scope`](#method.scope) method without their own limit
+    /// inherit the limit of this router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .max_size(1024 * 1024)
+    ///     .post("/upload", |_| async move { Ok(Response::new(Body::from("Uploaded"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn max_size(self, max_size: u64) -> Self {
+        self.and_then(move |mut inner| {
+            inner.max_size = max_size;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler which builds the response to a request whose body exceeds the [size limit](#method.max_size) of its
+    /// route, instead of the plain text `413 Payload Too Large` response. It's called both when the declared
+    /// `Content-Length` is too large and when the route handler fails while reading an oversized body.
+    ///
+    /// It's only respected on the root router and it's inherited by the fallback router. It only covers the body, the
+    /// `431 Request Header Fields Too Large` response to oversized headers is generated by the HTTP parser of hyper and
+    /// can't be customized here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RequestInfo};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .max_size(1024)
+    ///     .post("/upload", |_| async move { Ok(Response::new(Body::from("Uploaded"))) })
+    ///     .on_payload_too_large(|req_info: RequestInfo| async move {
+    ///         Response::builder()
+    ///             .status(StatusCode::PAYLOAD_TOO_LARGE)
+    ///             .body(Body::from(format!("The upload to {} is limited to 1 KiB", req_info.uri().path())))
+    ///             .unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn on_payload_too_large<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: RejectionHandler<B> = Arc::new(move |req_info: RequestInfo| Box::pin(handler(req_info)));
+
+        self.and_then(move |mut inner| {
+            inner.on_payload_too_large = Some(handler);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a handler which builds the response to a request with a malformed path, e.g. an invalid percent encoding,
+    /// instead of the plain text `400 Bad Request` response. No route is matched for such a request, so the
+    /// [request info](./struct.RequestInfo.html) passed to the handler doesn't carry the shared data.
+    ///
+    /// It's only respected on the root router. The `431 Request Header Fields Too Large` response to a request with
+    /// oversized headers is generated by the HTTP parser of hyper before the request reaches the router, so it can't be
+    /// customized by this handler or any other hook of the router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RequestInfo};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .on_bad_request(|_: RequestInfo| async move {
+    ///         Response::builder()
+    ///             .status(StatusCode::BAD_REQUEST)
+    ///             .body(Body::from("The request path is malformed"))
+    ///             .unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn on_bad_request<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(RequestInfo) -> R + Send + Sync + 'static,
+        R: Future<Output = Response<B>> + Send + 'static,
+    {
+        let handler: RejectionHandler<B> = Arc::new(move |req_info: RequestInfo| Box::pin(handler(req_info)));
+
+        self.and_then(move |mut inner| {
+            inner.on_bad_request = Some(handler);
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Makes the [`build`](#method.build) method return an error if two routes with a common method could both match
+    /// some path, e.g. the `/users/:id` and the `/users/me` routes for the `GET` method. It's disabled by default and then
+    /// the route registered first wins.
+    ///
+    /// The catch-all `/*` routes are not checked as they always have the lowest priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// let result: routerify::Result<Router<Body, Infallible>> = Router::builder()
+    ///     .strict(true)
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .get("/users/me", |_| async move { Ok(Response::new(Body::from("Me"))) })
+    ///     .build();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn strict(self, enable: bool) -> Self {
+        self.and_then(move |mut inner| {
+            inner.strict = enable;
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Adds a fallback router which will handle any request that couldn't be matched by the routes of this router, i.e.
+    /// the requests which would otherwise end up in the `404` route.
+    ///
+    /// The fallback router works like a root router: its own middlewares, `404` route and error handler are used for the
+    /// delegated requests. The request is delegated after the pre middlewares of this router and the response passes
+    /// through its post middlewares, so the global headers apply to the fallback responses too. The fallback is only
+    /// respected on the root router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// fn static_router() -> Router<Body, Infallible> {
+    ///     Router::builder()
+    ///         .get("/static/:file", |_| async move { Ok(Response::new(Body::from("Static file"))) })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/api/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .fallback_to(static_router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn fallback_to(self, router: Router<B, E>) -> Self {
+        self.and_then(move |mut inner| {
+            inner.fallback = Some(Box::new(router));
+            crate::Result::Ok(inner)
+        })
+    }
+
+    /// Delegates the requests which no route could match to an arbitrary `hyper` service, e.g. to serve the static assets
+    /// of a single page app behind an API router. It works like the [`fallback_to`](#method.fallback_to) method with a
+    /// router which only consists of the [`scope_service`](#method.scope_service) at `/`, so it replaces any fallback
+    /// router set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{service::service_fn, Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let spa = service_fn(|_req: Request<Body>| async move {
+    ///     Ok::<_, Infallible>(Response::new(Body::from("<!doctype html>")))
+    /// });
+    ///
+    /// let router = Router::builder()
+    ///     .get("/api/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .fallback_service(spa)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn fallback_service<S>(self, service: S) -> Self
     where
-        H: Fn(crate::RouteError) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<B>> + Send + 'static,
+        S: Service<Request<hyper::Body>, Response = Response<B>> + Clone + Send + Sync + 'static,
+        S::Error: Into<E>,
+        S::Future: Send + 'static,
     {
-        let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::RouteError| Box::new(handler(err)));
-
         self.and_then(move |mut inner| {
-            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
+            let router = RouterBuilder::new().scope_service("/", service).build()?;
+            inner.fallback = Some(Box::new(router));
             crate::Result::Ok(inner)
         })
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares.
+    /// Calls the specified function with a read-only [snapshot](./struct.BuilderSnapshot.html) of the configuration added
+    /// so far, e.g. to assert how many routes are registered at some point of the builder chain in a test. The function
+    /// isn't called if an earlier builder method has failed.
     ///
-    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
+    /// # Examples
     ///
-    /// Please refer to [Error Handling](./index.html#error-handling) section
-    /// for more info.
-    pub fn err_handler_with_info<H, R>(self, handler: H) -> Self
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .post("/users", |_| async move { Ok(Response::new(Body::from("User created"))) })
+    ///     .inspect(|snapshot| assert_eq!(snapshot.route_count(), 2))
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn inspect<F: FnOnce(&BuilderSnapshot<'_, B, E>)>(self, f: F) -> Self {
+        if let Ok(inner) = self.inner.as_ref() {
+            f(&BuilderSnapshot { inner });
+        }
+
+        self
+    }
+}
+
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RouterBuilder<hyper::Body, E> {
+    /// Adds a new route with `GET` method and a handler which takes the [extractors](./extract/index.html) as arguments,
+    /// e.g. the [`Path`](./extract/struct.Path.html) and the [`Query`](./extract/struct.Query.html) extractors.
+    ///
+    /// If an argument can't be extracted, the handler is not called and the request is rejected with the status of the
+    /// [`ExtractError`](./extract/struct.ExtractError.html), e.g. `400 Bad Request` for an invalid route parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, State};
+    /// use routerify::extract::Path;
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// struct AppState {
+    ///     name: String,
+    /// }
+    ///
+    /// async fn user_handler(Path((id,)): Path<(u32,)>, state: State<AppState>) -> Result<Response<Body>, Infallible> {
+    ///     Ok(Response::new(Body::from(format!("User {} of {}", id, state.name))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .with_state(AppState { name: "my app".to_owned() })
+    ///     .get_ext("/users/:id", user_handler)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_ext<P, H, T>(self, path: P, handler: H) -> Self
     where
-        H: Fn(crate::RouteError, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<B>> + Send + 'static,
+        P: Into<String>,
+        T: FromRequest,
+        H: ExtractHandler<T, E>,
     {
-        let handler: ErrHandlerWithInfo<B> =
-            Box::new(move |err: crate::RouteError, req_info: RequestInfo| Box::new(handler(err, req_info)));
+        self.add_ext(path, vec![Method::GET], handler)
+    }
+
+    /// Like the [`add`](#method.add) method, but the handler takes the [extractors](./extract/index.html) as arguments.
+    /// Refer to the [`get_ext`](#method.get_ext) method for more info.
+    pub fn add_ext<P, H, T>(self, path: P, methods: Vec<Method>, handler: H) -> Self
+    where
+        P: Into<String>,
+        T: FromRequest,
+        H: ExtractHandler<T, E>,
+    {
+        let handler = Arc::new(handler);
+
+        self.add(path, methods, move |mut req: Request<hyper::Body>| {
+            let args = T::from_request(&mut req);
+            let handler = handler.clone();
+
+            async move {
+                match args.await {
+                    Ok(args) => handler.call(args).await,
+                    Err(err) => Ok(err.into_response()),
+                }
+            }
+        })
+    }
+
+    /// Adds a route which redirects the requests at the `from` path to the `to` location with the specified redirection
+    /// status, e.g. `301 Moved Permanently` or `308 Permanent Redirect`. The route accepts any method.
+    ///
+    /// The route parameters of the `from` path can be interpolated into the `to` location by their names, the values are
+    /// percent encoded. The [`build`](#method.build) method will return an error if the status is not a redirection status
+    /// or the `to` location is not a valid header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .redirect("/home", "/", StatusCode::PERMANENT_REDIRECT)
+    ///     .redirect("/u/:id", "/users/:id", StatusCode::MOVED_PERMANENTLY)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn redirect(self, from: &str, to: &str, status: StatusCode) -> Self {
+        let location = to.to_owned();
+        let to = to.to_owned();
+
+        let builder = self.and_then(move |inner| {
+            if !status.is_redirection() {
+                return Err(
+                    Error::new(format!("The redirect status must be a 3xx status, but got: {}", status)).into(),
+                );
+            }
+
+            HeaderValue::from_str(to.as_str())
+                .map_err(|e| Error::new(format!("Invalid redirect location: {}: {}", to, e)))?;
 
-        self.and_then(move |mut inner| {
-            inner.err_handler = Some(ErrHandler::WithInfo(handler));
             crate::Result::Ok(inner)
+        });
+
+        builder.add(from, constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), move |req| {
+            let location = helpers::interpolate_path_params(location.as_str(), req.params());
+            async move {
+                Ok(Response::builder()
+                    .status(status)
+                    .header(header::LOCATION, location)
+                    .body(hyper::Body::empty())
+                    .expect("Couldn't create the redirect response"))
+            }
+        })
+    }
+
+    /// Sets the `Content-Type` header of the responses which don't have one by sniffing the start of their bodies, e.g. for
+    /// the handlers which respond with raw strings. A body which starts with a `<` tag is sent as
+    /// `text/html; charset=utf-8` and any other UTF-8 text as `text/plain; charset=utf-8`. The binary and the XML bodies
+    /// are left as is. It's disabled by default.
+    ///
+    /// It registers a post middleware at the `/*` path which buffers only the first chunk of the body, so the order of
+    /// registration matters with respect to the other post middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .sniff_content_type(true)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("<h1>Home page</h1>"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn sniff_content_type(self, enable: bool) -> Self {
+        if !enable {
+            return self;
+        }
+
+        self.middleware(Middleware::post(|res: Response<hyper::Body>| async move {
+            if res.headers().contains_key(header::CONTENT_TYPE) {
+                return Ok(res);
+            }
+
+            let (mut parts, mut body) = res.into_parts();
+            let first = match body.data().await {
+                Some(Ok(first)) => first,
+                // The body failed, so pass the error on to the connection.
+                Some(Err(e)) => {
+                    return Ok(Response::from_parts(
+                        parts,
+                        PrependedBody::new(Err(e), body).into_body(),
+                    ))
+                }
+                None => return Ok(Response::from_parts(parts, body)),
+            };
+
+            if let Some(content_type) = helpers::sniff_content_type(&first) {
+                parts
+                    .headers
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+            }
+
+            // Keep the exact size of the body if it's a single chunk, so it's not sent as a chunked body.
+            let body = if body.is_end_stream() {
+                hyper::Body::from(first)
+            } else {
+                PrependedBody::new(Ok(first), body).into_body()
+            };
+
+            Ok(Response::from_parts(parts, body))
+        }))
+    }
+
+    /// Adds an `OPTIONS` route which answers the CORS preflight requests at the specified path with `204 No Content`,
+    /// so that no handler needs to be written for them.
+    ///
+    /// The response allows the requested origin, method and headers only if the [config](./struct.PreflightConfig.html)
+    /// lists them, otherwise no `Access-Control-Allow-*` header is added and the browser doesn't send the actual request.
+    /// The `Vary` header lists the request headers which the response depends on. Any CORS headers of the actual responses
+    /// are still left to the route handlers or to a CORS middleware, e.g. [routerify-cors](https://github.com/routerify/routerify-cors).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{PreflightConfig, Router};
+    /// use hyper::{header, Response, Body, Method};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .put("/api/users/:id", |_| async move { Ok(Response::new(Body::from("Updated"))) })
+    ///     .allow_preflight(
+    ///         "/api/users/:id",
+    ///         PreflightConfig::new()
+    ///             .allow_origin("https://example.com")
+    ///             .allow_methods(&[Method::PUT])
+    ///             .allow_headers(&[header::CONTENT_TYPE]),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn allow_preflight<P: Into<String>>(self, path: P, config: PreflightConfig) -> Self {
+        let config = Arc::new(config);
+
+        self.options(path, move |req| {
+            let config = config.clone();
+            async move {
+                let mut res = Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(hyper::Body::empty())
+                    .expect("Couldn't create the preflight response");
+
+                config.apply(req.headers(), res.headers_mut());
+
+                middlewares::append_vary(
+                    res.headers_mut(),
+                    &[
+                        header::ORIGIN,
+                        header::ACCESS_CONTROL_REQUEST_METHOD,
+                        header::ACCESS_CONTROL_REQUEST_HEADERS,
+                    ],
+                );
+
+                Ok(res)
+            }
+        })
+    }
+}
+
+// Returns the route path with the trailing slash appended unless it already ends with a slash or a wildcard, the request
+// paths get the same slash before matching. It returns `None` if the path is kept, e.g. in the strict slash mode.
+fn append_trailing_slash(path: &str, strict_slash: bool) -> Option<String> {
+    if strict_slash || path.ends_with('/') || path.ends_with('*') {
+        return None;
+    }
+
+    Some(format!("{}/", path))
+}
+
+// Stably sorts the middlewares of the router itself by priority into their own slots, so the middlewares of the scoped
+// routers, which are sorted on their own build, keep their positions.
+fn sort_by_priority<M>(middlewares: Vec<M>, is_own: impl Fn(&M) -> bool, priority: impl Fn(&M) -> i32) -> Vec<M> {
+    let mut own = Vec::new();
+    let mut slots = Vec::with_capacity(middlewares.len());
+    for middleware in middlewares {
+        if is_own(&middleware) {
+            own.push(middleware);
+            slots.push(None);
+        } else {
+            slots.push(Some(middleware));
+        }
+    }
+
+    own.sort_by_key(&priority);
+    let mut own = own.into_iter();
+    slots
+        .into_iter()
+        .map(|slot| {
+            slot.or_else(|| own.next())
+                .expect("Every empty slot must have a middleware")
+        })
+        .collect()
+}
+
+type BuilderItems<B, E> = (Vec<PreMiddleware<E>>, Vec<Route<B, E>>, Vec<PostMiddleware<B, E>>);
+
+// Prefixes the routes and the middlewares with the base path. Unlike the scoping, it keeps their scope depths.
+fn apply_base_path<B, E>(
+    base_path: &str,
+    pre_middlewares: Vec<PreMiddleware<E>>,
+    routes: Vec<Route<B, E>>,
+    post_middlewares: Vec<PostMiddleware<B, E>>,
+    regex_size_limit: Option<usize>,
+) -> crate::Result<BuilderItems<B, E>>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let pre_middlewares = pre_middlewares
+        .into_iter()
+        .map(|mut pre_middleware| {
+            let handler = pre_middleware.handler.take().ok_or_else(mounted_router_error)?;
+            pre_middleware.with_prefix(base_path, handler, pre_middleware.scope_depth, regex_size_limit)
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    let routes = routes
+        .into_iter()
+        .map(|mut route| {
+            let handler = route.handler.take().ok_or_else(mounted_router_error)?;
+            route.with_prefix(base_path, handler, route.scope_depth, regex_size_limit)
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    let post_middlewares = post_middlewares
+        .into_iter()
+        .map(|mut post_middleware| {
+            let handler = post_middleware.handler.take().ok_or_else(mounted_router_error)?;
+            post_middleware.with_prefix(base_path, handler, post_middleware.scope_depth, regex_size_limit)
         })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    Ok((pre_middlewares, routes, post_middlewares))
+}
+
+fn check_overlapping_routes<B, E>(routes: &[Route<B, E>], strict_slash: bool) -> crate::Result<()>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    // The catch-all routes handle the non-existent routes, so they're expected to overlap the others.
+    let routes = routes
+        .iter()
+        .filter(|route| !route.is_catch_all() && !route.is_scoped_catch_all())
+        .collect::<Vec<_>>();
+
+    for (idx, route) in routes.iter().enumerate() {
+        for other_route in routes[idx + 1..].iter() {
+            let has_common_method = route.methods.iter().any(|method| other_route.methods.contains(method));
+
+            if has_common_method && is_overlapping_path(route.path.as_str(), other_route.path.as_str(), strict_slash) {
+                return Err(Error::new(format!(
+                    "The routes `{}` and `{}` could both match the same request",
+                    route.path, other_route.path
+                ))
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks whether two route paths could match a common path. A segment with a parameter is assumed to match any non-empty
+// segment with the same literal text around the parameters and a segment with a `*` is assumed to match the rest of the
+// path. In the strict slash mode, the trailing slash is kept as an empty segment, so `/users` and `/users/` don't overlap.
+fn is_overlapping_path(path: &str, other_path: &str, strict_slash: bool) -> bool {
+    let segments = path_segments(path, strict_slash);
+    let other_segments = path_segments(other_path, strict_slash);
+
+    let mut idx = 0;
+    loop {
+        match (segments.get(idx), other_segments.get(idx)) {
+            (None, None) => return true,
+            (Some(s), _) | (_, Some(s)) if s.contains('*') => return true,
+            (Some(s), Some(other_s)) => {
+                if !is_overlapping_segment(s, other_s) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+
+        idx += 1;
+    }
+}
+
+// Checks whether two path segments could match a common segment by the literal text before the first parameter and after
+// the last parameter, e.g. `:name.json` and `:name.xml` don't overlap. The literal text between the parameters is ignored.
+fn is_overlapping_segment(segment: &str, other_segment: &str) -> bool {
+    match (segment_affixes(segment), segment_affixes(other_segment)) {
+        (None, None) => segment == other_segment,
+        (Some((prefix, suffix)), None) => is_matching_literal_segment(other_segment, prefix, suffix),
+        (None, Some((prefix, suffix))) => is_matching_literal_segment(segment, prefix, suffix),
+        (Some((prefix, suffix)), Some((other_prefix, other_suffix))) => {
+            (prefix.starts_with(other_prefix) || other_prefix.starts_with(prefix))
+                && (suffix.ends_with(other_suffix) || other_suffix.ends_with(suffix))
+        }
+    }
+}
+
+// Checks whether a literal segment could be matched by a segment with parameters, a parameter matches one character at least.
+fn is_matching_literal_segment(segment: &str, prefix: &str, suffix: &str) -> bool {
+    segment.len() > prefix.len() + suffix.len() && segment.starts_with(prefix) && segment.ends_with(suffix)
+}
+
+// Returns the literal text before the first parameter and after the last parameter of a segment, or `None` if the segment
+// has no parameter. A parameter name ends at a `.`, like in the generated regex.
+fn segment_affixes(segment: &str) -> Option<(&str, &str)> {
+    let first_param = segment.find(':')?;
+    let last_param = segment.rfind(':').unwrap_or(first_param);
+    let suffix = segment[last_param..]
+        .find('.')
+        .map_or("", |idx| &segment[last_param + idx..]);
+
+    Some((&segment[..first_param], suffix))
+}
+
+fn path_segments(path: &str, strict_slash: bool) -> Vec<&str> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty()).collect::<Vec<_>>();
+    if strict_slash && path.len() > 1 && path.ends_with('/') {
+        segments.push("");
     }
+    segments
 }
 
 impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> Default
@@ -742,8 +3191,112 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
                 routes: Vec::new(),
                 post_middlewares: Vec::new(),
                 data_maps: HashMap::new(),
+                shared_data_maps: Vec::new(),
+                strict: false,
+                max_size: 0,
                 err_handler: None,
+                options: RouterOptions::default(),
+                fallback: None,
+                on_request: None,
+                on_response: None,
+                rewrite: None,
+                response_factory: None,
+                on_payload_too_large: None,
+                on_bad_request: None,
+                base_path: String::new(),
+                deprecation_idxs: Vec::new(),
+                state_resolvers: Vec::new(),
             }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Middleware, Router};
+    use hyper::{Body, Response};
+    use std::time::Duration;
+
+    #[test]
+    fn should_return_error_when_scoping_mounted_router() {
+        // Mounting a router moves its handlers out, so fake a router which is already mounted.
+        let mounted_router = || {
+            let mut router: Router<Body, Error> = Router::builder()
+                .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+                .build()
+                .unwrap();
+            router.routes[0].handler.take();
+            router
+        };
+        let expected =
+            "routerify::Error: The router is already mounted into another router, so it can't be scoped again";
+
+        let err = Router::builder()
+            .scope_cloned("/api", &mounted_router())
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), expected);
+
+        let err = Router::builder().scope("/api", mounted_router()).build().unwrap_err();
+        assert_eq!(err.to_string(), expected);
+
+        let err = Router::builder()
+            .build()
+            .unwrap()
+            .mount("/api", mounted_router())
+            .unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[test]
+    fn should_register_request_timeout_status_hint_once() {
+        let router: Router<Body, Error> = Router::builder()
+            .request_timeout(Duration::from_secs(10))
+            .request_timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        assert_eq!(router.options.status_hints.len(), 1);
+    }
+
+    #[test]
+    fn should_reject_too_large_regex_on_build() {
+        let path = (0..16).map(|idx| format!("/:param{}", idx)).collect::<String>();
+        let build = |size_limit: usize| {
+            Router::<Body, Error>::builder()
+                .regex_size_limit(size_limit)
+                .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+                .get(
+                    path.as_str(),
+                    |_| async move { Ok(Response::new(Body::from("Params"))) },
+                )
+                .build()
+        };
+
+        assert!(build(10 * 1024 * 1024).is_ok());
+
+        let err = build(1024).unwrap_err().to_string();
+        assert!(
+            err.starts_with(
+                "routerify::Error: Could not create an exact match regex for the route path `/:param0/:param1/"
+            ),
+            "{}",
+            err
+        );
+        assert!(err.contains("exceeds size limit"), "{}", err);
+    }
+
+    #[test]
+    fn should_apply_regex_size_limit_to_regex_set() {
+        let path = (0..16).map(|idx| format!("/:param{}", idx)).collect::<String>();
+        let middleware = Middleware::pre_with_path(path.as_str(), |req| async move { Ok(req) }).unwrap();
+        let mut router: Router<Body, Error> = Router::builder()
+            .regex_size_limit(1024)
+            .middleware(middleware)
+            .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+            .build()
+            .unwrap();
+
+        let err = router.warmup().unwrap_err().to_string();
+        assert!(err.contains("Couldn't create router RegexSet"), "{}", err);
+    }
+}
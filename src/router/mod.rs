@@ -1,20 +1,36 @@
 use crate::constants;
-use crate::data_map::ScopedDataMap;
+use crate::data_map::{ScopedDataMap, SharedDataMap};
+use crate::helpers;
 use crate::middleware::{PostMiddleware, PreMiddleware};
 use crate::route::Route;
-use crate::types::RequestInfo;
+use crate::service::{RequestService, RequestServiceBuilder};
+use crate::types::{
+    MatchStats, Matcher, RequestInfo, RequestMeta, RequestTimeoutError, RouteMatch, SkipPostMiddleware,
+    SkipPostMiddlewareScopes, Warning,
+};
+use crate::utility::response::NotModified;
 use crate::Error;
 use crate::RouteError;
-use hyper::{body::HttpBody, header, Method, Request, Response, StatusCode};
-use regex::RegexSet;
+use hyper::{
+    body::HttpBody, header, header::HeaderValue, server::conn::AddrStream, Method, Request, Response, StatusCode,
+    Version,
+};
+use regex::{RegexSet, RegexSetBuilder};
 use std::any::Any;
+use std::convert::Infallible;
 use std::fmt::{self, Debug, Formatter};
-use std::future::Future;
+use std::future::{ready, Future, Ready};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-pub use self::builder::RouterBuilder;
+pub use self::builder::{BuilderSnapshot, RouterBuilder};
+pub(crate) use self::options::RouterOptions;
+use self::trie::RouteTrie;
 
 mod builder;
+mod options;
+mod trie;
 
 pub(crate) type ErrHandlerWithoutInfo<B> =
     Box<dyn Fn(RouteError) -> ErrHandlerWithoutInfoReturn<B> + Send + Sync + 'static>;
@@ -69,18 +85,56 @@ pub struct Router<B, E> {
     // Any error handler attached to scoped router will be ignored.
     pub(crate) err_handler: Option<ErrHandler<B>>,
 
+    // Like the error handler, the options are only respected on the root Router.
+    pub(crate) options: RouterOptions,
+
+    // The router which handles the requests that no route in this router could match.
+    // Like the error handler, it's only respected on the root Router.
+    pub(crate) fallback: Option<Box<Router<B, E>>>,
+
+    // The hooks which observe every request and response, they are only respected on the root Router.
+    pub(crate) on_request: Option<OnRequestHook>,
+    pub(crate) on_response: Option<OnResponseHook<B>>,
+
+    // Rewrites the request path before matching, it's only respected on the root Router.
+    pub(crate) rewrite: Option<PathRewrite>,
+
+    // Builds the responses of the default OPTIONS route, the default 404 route and the default error handler.
+    // It's required only when the body type is not `hyper::Body`.
+    pub(crate) response_factory: Option<ResponseFactory<B>>,
+
+    // Build the responses of the requests which are rejected by the router itself instead of the canned plain text ones,
+    // they are only respected on the root Router.
+    pub(crate) on_payload_too_large: Option<RejectionHandler<B>>,
+    pub(crate) on_bad_request: Option<RejectionHandler<B>>,
+
     // We'll initialize it from the RouterService via Router::init_regex_set() method.
     regex_set: Option<RegexSet>,
+    // The indices of the routes which are matched by the regex set, the rest of them are matched by the route trie.
+    regex_set_route_idxs: Vec<usize>,
+    route_trie: Option<RouteTrie>,
 
     // We'll initialize it from the RouterService via Router::init_req_info_gen() method.
     pub(crate) should_gen_req_info: Option<bool>,
 }
 
+pub(crate) type OnRequestHook = Box<dyn Fn(&RequestInfo) + Send + Sync + 'static>;
+pub(crate) type OnResponseHook<B> = Box<dyn Fn(&RequestInfo, &Response<B>) + Send + Sync + 'static>;
+pub(crate) type PathRewrite = Box<dyn Fn(&str) -> Option<String> + Send + Sync + 'static>;
+pub(crate) type ResponseFactory<B> = Arc<dyn Fn(StatusCode, String) -> Response<B> + Send + Sync + 'static>;
+pub(crate) type RejectionHandler<B> =
+    Arc<dyn Fn(RequestInfo) -> Pin<Box<dyn Future<Output = Response<B>> + Send + 'static>> + Send + Sync + 'static>;
+
 pub(crate) enum ErrHandler<B> {
     WithoutInfo(ErrHandlerWithoutInfo<B>),
     WithInfo(ErrHandlerWithInfo<B>),
 }
 
+// The handlers of a router are moved out when it's mounted into another router, so it can't be mounted again.
+pub(crate) fn mounted_router_error() -> RouteError {
+    Error::new("The router is already mounted into another router, so it can't be scoped again").into()
+}
+
 impl<B: HttpBody + Send + Sync + 'static> ErrHandler<B> {
     pub(crate) async fn execute(&self, err: RouteError, req_info: Option<RequestInfo>) -> Response<B> {
         match self {
@@ -99,6 +153,8 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         post_middlewares: Vec<PostMiddleware<B, E>>,
         scoped_data_maps: Vec<ScopedDataMap>,
         err_handler: Option<ErrHandler<B>>,
+        options: RouterOptions,
+        fallback: Option<Box<Router<B, E>>>,
     ) -> Self {
         Router {
             pre_middlewares,
@@ -106,32 +162,127 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             post_middlewares,
             scoped_data_maps,
             err_handler,
+            options,
+            fallback,
+            on_request: None,
+            on_response: None,
+            rewrite: None,
+            response_factory: None,
+            on_payload_too_large: None,
+            on_bad_request: None,
             regex_set: None,
+            regex_set_route_idxs: Vec::new(),
+            route_trie: None,
             should_gen_req_info: None,
         }
     }
 
+    // Converts the error type of all the handlers, it's used by the `RouterBuilder::map_err` method.
+    pub(crate) fn map_err<E2, F>(self, f: Arc<F>) -> Router<B, E2>
+    where
+        E2: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        F: Fn(E) -> E2 + Send + Sync + 'static,
+    {
+        Router {
+            pre_middlewares: self.pre_middlewares.into_iter().map(|m| m.map_err(f.clone())).collect(),
+            routes: self.routes.into_iter().map(|r| r.map_err(f.clone())).collect(),
+            post_middlewares: self
+                .post_middlewares
+                .into_iter()
+                .map(|m| m.map_err(f.clone()))
+                .collect(),
+            scoped_data_maps: self.scoped_data_maps,
+            err_handler: self.err_handler,
+            options: self.options,
+            fallback: self.fallback.map(|fallback| Box::new(fallback.map_err(f))),
+            on_request: self.on_request,
+            on_response: self.on_response,
+            rewrite: self.rewrite,
+            response_factory: self.response_factory,
+            on_payload_too_large: self.on_payload_too_large,
+            on_bad_request: self.on_bad_request,
+            regex_set: self.regex_set,
+            regex_set_route_idxs: self.regex_set_route_idxs,
+            route_trie: self.route_trie,
+            should_gen_req_info: self.should_gen_req_info,
+        }
+    }
+
     pub(crate) fn init_regex_set(&mut self) -> crate::Result<()> {
+        self.route_trie = None;
+        self.regex_set_route_idxs = (0..self.routes.len()).collect();
+
+        if self.options.matcher == Matcher::Trie {
+            let mut route_trie = RouteTrie::default();
+            self.regex_set_route_idxs.clear();
+
+            for (idx, route) in self.routes.iter().enumerate() {
+                if RouteTrie::is_literal(route.path.as_str()) {
+                    route_trie.insert(route.path.as_str(), idx);
+                } else {
+                    self.regex_set_route_idxs.push(idx);
+                }
+            }
+
+            self.route_trie = Some(route_trie);
+        }
+
         let regex_iter = self
             .pre_middlewares
             .iter()
             .map(|m| m.regex.as_str())
-            .chain(self.routes.iter().map(|r| r.regex.as_str()))
+            .chain(
+                self.regex_set_route_idxs
+                    .iter()
+                    .map(|idx| self.routes[*idx].regex.as_str()),
+            )
             .chain(self.post_middlewares.iter().map(|m| m.regex.as_str()))
             .chain(self.scoped_data_maps.iter().map(|d| d.regex.as_str()));
 
-        self.regex_set =
-            Some(RegexSet::new(regex_iter).map_err(|e| Error::new(format!("Couldn't create router RegexSet: {}", e)))?);
+        // The set holds every path at once, so the size limit must apply to it too, not only to the separate regexes.
+        let mut regex_set_builder = RegexSetBuilder::new(regex_iter);
+        if let Some(size_limit) = self.options.regex_size_limit {
+            regex_set_builder.size_limit(size_limit).dfa_size_limit(size_limit);
+        }
+        self.regex_set = Some(
+            regex_set_builder
+                .build()
+                .map_err(|e| Error::new(format!("Couldn't create router RegexSet: {}", e)))?,
+        );
 
         Ok(())
     }
 
     pub(crate) fn init_req_info_gen(&mut self) {
+        if let Some(true) = self.fallback.as_ref().and_then(|fallback| fallback.should_gen_req_info) {
+            self.should_gen_req_info = Some(true);
+            return;
+        }
+
         if let Some(ErrHandler::WithInfo(_)) = self.err_handler {
             self.should_gen_req_info = Some(true);
             return;
         }
 
+        let route_err_handlers = self.routes.iter().filter_map(|route| route.err_handler.as_deref());
+        for err_handler in route_err_handlers {
+            if let ErrHandler::WithInfo(_) = err_handler {
+                self.should_gen_req_info = Some(true);
+                return;
+            }
+        }
+
+        if self.on_request.is_some()
+            || self.on_response.is_some()
+            || self.on_payload_too_large.is_some()
+            || self.on_bad_request.is_some()
+        {
+            self.should_gen_req_info = Some(true);
+            return;
+        }
+
+        // The request info is generated for every request even if only a few of them reach such a middleware, so it's also
+        // available to the error handler when the middleware fails.
         for post_middleware in self.post_middlewares.iter() {
             if post_middleware.should_require_req_meta() {
                 self.should_gen_req_info = Some(true);
@@ -142,18 +293,77 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         self.should_gen_req_info = Some(false);
     }
 
-    // pub(crate) fn init_keep_alive_middleware(&mut self) {
-    //     let keep_alive_post_middleware = PostMiddleware::new("/*", |mut res| async move {
-    //         res.headers_mut()
-    //             .insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
-    //         Ok(res)
-    //     })
-    //     .unwrap();
+    pub(crate) fn init_fallback_router(&mut self, warnings: &mut Vec<Warning>) -> crate::Result<()> {
+        if let Some(ref mut fallback) = self.fallback {
+            // The fallback router receives the same target path, so it must treat it the same way.
+            fallback.options.decode_before_match = self.options.decode_before_match;
+            fallback.options.json_errors = self.options.json_errors;
+            fallback.options.matcher = self.options.matcher;
+            if fallback.response_factory.is_none() {
+                fallback.response_factory = self.response_factory.clone();
+            }
+            if fallback.on_payload_too_large.is_none() {
+                fallback.on_payload_too_large = self.on_payload_too_large.clone();
+            }
+
+            warnings.extend(fallback.init_global_options_route());
+            warnings.extend(fallback.init_default_404_route());
+
+            warnings.extend(fallback.init_err_handler());
+
+            fallback.init_fallback_router(warnings)?;
+            fallback.init_regex_set()?;
+            fallback.init_req_info_gen();
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn init_keep_alive_middleware(&mut self) {
+        if !self.options.keep_alive {
+            return;
+        }
 
-    //     self.post_middlewares.push(keep_alive_post_middleware);
-    // }
+        let keep_alive_post_middleware = PostMiddleware::new_with_info("/*", |mut res, req_info| async move {
+            // The connection-specific headers are not allowed in HTTP/2, and a `Connection` header set by the handler wins.
+            let is_http1 = matches!(req_info.version(), Version::HTTP_10 | Version::HTTP_11);
+            if is_http1 && !res.headers().contains_key(header::CONNECTION) {
+                res.headers_mut()
+                    .insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
+            }
+            Ok(res)
+        })
+        .unwrap();
 
-    pub(crate) fn init_global_options_route(&mut self) {
+        self.post_middlewares.push(keep_alive_post_middleware);
+    }
+
+    pub(crate) fn init_default_charset_middleware(&mut self) {
+        let charset: Arc<str> = match self.options.default_charset {
+            Some(ref charset) => Arc::from(charset.as_str()),
+            None => return,
+        };
+
+        let default_charset_post_middleware = PostMiddleware::new("/*", move |mut res| {
+            let content_type = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| helpers::add_charset_to_content_type(val, &charset))
+                .and_then(|val| HeaderValue::from_str(val.as_str()).ok());
+
+            if let Some(content_type) = content_type {
+                res.headers_mut().insert(header::CONTENT_TYPE, content_type);
+            }
+
+            async move { Ok(res) }
+        })
+        .unwrap();
+
+        self.post_middlewares.push(default_charset_post_middleware);
+    }
+
+    pub(crate) fn init_global_options_route(&mut self) -> Option<Warning> {
         let options_method = vec![Method::OPTIONS];
         let found = self
             .routes
@@ -161,89 +371,373 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             .any(|route| route.path == "/*" && route.methods.as_slice() == options_method.as_slice());
 
         if found {
-            return;
+            return None;
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let options_route: Route<hyper::Body, E> = Route::new("/*", options_method, |_req| async move {
-                Ok(Response::builder()
-                    .status(StatusCode::NO_CONTENT)
-                    .body(hyper::Body::empty())
-                    .expect("Couldn't create the default OPTIONS response"))
-            })
-            .unwrap();
+        let response_factory = match self.response_factory() {
+            Some(response_factory) => response_factory,
+            None => return Some(Warning::NoOptionsRoute),
+        };
 
-            router.routes.push(options_route);
-        } else {
-            eprintln!(
-                "Warning: No global `options method` route added. It is recommended to send response to any `options` request.\n\
-                Please add one by calling `.options(\"/*\", handler)` method of the root router builder.\n"
-            );
-        }
+        let options_route = Route::new(
+            "/*",
+            options_method,
+            move |_req| {
+                let res = response_factory(StatusCode::NO_CONTENT, String::new());
+                async move { Ok(res) }
+            },
+            None,
+        )
+        .unwrap();
+
+        self.routes.push(options_route);
+        None
     }
 
-    pub(crate) fn init_default_404_route(&mut self) {
-        let found = self
-            .routes
-            .iter()
-            .any(|route| route.path == "/*" && route.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..]);
+    pub(crate) fn init_default_404_route(&mut self) -> Option<Warning> {
+        let found = self.routes.iter().any(|route| route.is_catch_all());
 
         if found {
-            return;
+            return None;
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let default_404_route: Route<hyper::Body, E> =
-                Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), |_req| async move {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(hyper::Body::from(StatusCode::NOT_FOUND.canonical_reason().unwrap()))
-                        .expect("Couldn't create the default 404 response"))
-                })
-                .unwrap();
-            router.routes.push(default_404_route);
-        } else {
-            eprintln!(
-                "Warning: No default 404 route added. It is recommended to send 404 response to any non-existent route.\n\
-                Please add one by calling `.any(handler)` method of the root router builder.\n"
-            );
-        }
+        let response_factory = match self.response_factory() {
+            Some(response_factory) => response_factory,
+            None => return Some(Warning::No404Route),
+        };
+
+        let json_errors = self.options.json_errors;
+
+        let default_404_route = Route::new(
+            "/*",
+            constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(),
+            move |req| {
+                let (body, content_type) = helpers::gen_default_error_body(StatusCode::NOT_FOUND, None, json_errors);
+                let mut res = response_factory(StatusCode::NOT_FOUND, body);
+
+                // A response to a `HEAD` request must not have a body, but it keeps the headers of the `GET` response. The
+                // length of the `GET` response is known only if the factory creates a body of an exact size.
+                if req.method() == Method::HEAD {
+                    let content_length = res.body().size_hint().exact();
+                    res = response_factory(StatusCode::NOT_FOUND, String::new());
+                    if let Some(content_length) = content_length {
+                        res.headers_mut()
+                            .entry(header::CONTENT_LENGTH)
+                            .or_insert_with(|| HeaderValue::from(content_length));
+                    }
+                }
+                // Keep the content type set by the factory, e.g. for an HTML body.
+                res.headers_mut()
+                    .entry(header::CONTENT_TYPE)
+                    .or_insert_with(|| HeaderValue::from_static(content_type));
+
+                async move { Ok(res) }
+            },
+            None,
+        )
+        .unwrap();
+
+        self.routes.push(default_404_route);
+        None
     }
 
-    pub(crate) fn init_err_handler(&mut self) {
+    pub(crate) fn init_err_handler(&mut self) -> Option<Warning> {
         let found = self.err_handler.is_some();
 
         if found {
-            return;
+            return None;
+        }
+
+        let response_factory = match self.response_factory() {
+            Some(response_factory) => response_factory,
+            None => return Some(Warning::NoErrHandler),
+        };
+        let status_hints = self.options.status_hints.clone();
+        let json_errors = self.options.json_errors;
+
+        let handler: ErrHandler<B> = ErrHandler::WithoutInfo(Box::new(move |err: RouteError| {
+            // The registered error types can suggest a more specific status, e.g. `400 Bad Request`.
+            let status = status_hints
+                .iter()
+                .find_map(|status_hint| status_hint(&err))
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+            let (body, content_type) = helpers::gen_default_error_body(status, Some(err.to_string()), json_errors);
+            let mut res = response_factory(status, body);
+            res.headers_mut()
+                .entry(header::CONTENT_TYPE)
+                .or_insert_with(|| HeaderValue::from_static(content_type));
+
+            Box::new(async move { res })
+        }));
+        self.err_handler = Some(handler);
+        None
+    }
+
+    // Responds to a request which is rejected by the router itself, e.g. for an oversized body, by the custom rejection
+    // handler if it's set, otherwise by a plain text response which is only possible for the `hyper::Body` body type.
+    pub(crate) async fn reject(
+        handler: Option<&RejectionHandler<B>>,
+        status: StatusCode,
+        req_info: Option<RequestInfo>,
+        err: RouteError,
+    ) -> crate::Result<Response<B>> {
+        match (handler, req_info) {
+            (Some(handler), Some(req_info)) => Ok(handler(req_info).await),
+            _ => helpers::gen_client_error_response(status).ok_or(err),
+        }
+    }
+
+    // Responds to a request which fails before the routing, e.g. for a too long path, by the response factory like the
+    // default error handler does, so it works for any body type with a factory. It fails with the error otherwise.
+    pub(crate) fn gen_error_response(&self, status: StatusCode, err: RouteError) -> crate::Result<Response<B>> {
+        let response_factory = self.response_factory().ok_or(err)?;
+
+        let (body, content_type) = helpers::gen_default_error_body(status, None, self.options.json_errors);
+        let mut res = response_factory(status, body);
+        res.headers_mut()
+            .entry(header::CONTENT_TYPE)
+            .or_insert_with(|| HeaderValue::from_static(content_type));
+
+        Ok(res)
+    }
+
+    // Returns the user-supplied response factory, or the built-in one if the body type is `hyper::Body`.
+    fn response_factory(&self) -> Option<ResponseFactory<B>> {
+        if let Some(ref response_factory) = self.response_factory {
+            return Some(response_factory.clone());
         }
 
-        if let Some(router) = self.downcast_to_hyper_body_type() {
-            let handler: ErrHandler<hyper::Body> = ErrHandler::WithoutInfo(Box::new(move |err: RouteError| {
-                Box::new(async move {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(hyper::Body::from(format!(
-                            "{}: {}",
-                            StatusCode::INTERNAL_SERVER_ERROR.canonical_reason().unwrap(),
-                            err
-                        )))
-                        .expect("Couldn't create a response while handling the server error")
-                })
-            }));
-            router.err_handler = Some(handler);
+        let hyper_body_factory: ResponseFactory<hyper::Body> = Arc::new(|status, text| {
+            Response::builder()
+                .status(status)
+                .body(hyper::Body::from(text))
+                .expect("Couldn't create the default response")
+        });
+        let any_obj: Box<dyn Any> = Box::new(hyper_body_factory);
+        any_obj
+            .downcast::<ResponseFactory<B>>()
+            .ok()
+            .map(|response_factory| *response_factory)
+    }
+
+    /// Returns how many regexes of the routes, the middlewares and the scoped data maps match the specified path without
+    /// executing any handler. It helps to find the pathological glob patterns in a large route table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .get("/users/*", |_| async move { Ok(Response::new(Body::from("Users"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(router.match_stats("/users/1").candidates, 2);
+    /// ```
+    pub fn match_stats(&self, path: &str) -> MatchStats {
+        let mut target_path = path.to_owned();
+        if target_path.is_empty() || (!self.options.strict_slash && !target_path.ends_with('/')) {
+            target_path.push('/');
+        }
+
+        let candidates = if self.regex_set.is_some() {
+            let (pre, routes, post, data_maps) = self.match_regex_set(target_path.as_str());
+            pre.len() + routes.len() + post.len() + data_maps.len()
+        } else {
+            // The regex set is initialized only when the router starts serving, so match the regexes one by one.
+            self.pre_middlewares
+                .iter()
+                .map(|m| &m.regex)
+                .chain(self.routes.iter().map(|r| &r.regex))
+                .chain(self.post_middlewares.iter().map(|m| &m.regex))
+                .chain(self.scoped_data_maps.iter().map(|d| &d.regex))
+                .filter(|re| re.is_match(target_path.as_str()))
+                .count()
+        };
+
+        MatchStats { candidates }
+    }
+
+    /// Finds the route which would handle a request with the specified method and path without executing any handler or
+    /// middleware, e.g. to reuse the route matching for a custom transport. It returns the route of this router which would
+    /// serve the request, i.e. the first matching one in the registration order with the catch-all routes last, along with
+    /// the extracted route parameters, the fallback router is not consulted.
+    ///
+    /// The path is normalized by the router options like the path of a request, it returns `None` if it can't be percent
+    /// decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, Method};
+    /// use std::convert::Infallible;
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let route_match = router.match_route(&Method::GET, "/users/10").unwrap();
+    /// assert_eq!(route_match.pattern, "/users/:id");
+    /// assert_eq!(route_match.params.get("id").unwrap(), "10");
+    /// ```
+    pub fn match_route(&self, method: &Method, path: &str) -> Option<RouteMatch> {
+        let rewritten_path = self.rewrite.as_ref().and_then(|rewrite| rewrite(path));
+        let path = rewritten_path.as_deref().unwrap_or(path);
+
+        let (target_path, raw_target_path) = self.normalize_path(path).ok()?;
+
+        let mut matched_route_idxs: Vec<usize> = if self.regex_set.is_some() {
+            self.match_regex_set(target_path.as_str()).1
         } else {
-            eprintln!(
-                "Warning: No error handler added. It is recommended to add one to see what went wrong if any route or middleware fails.\n\
-                Please add one by calling `.err_handler(handler)` method of the root router builder.\n"
-            );
+            self.routes
+                .iter()
+                .enumerate()
+                .filter(|(_, route)| route.regex.is_match(target_path.as_str()))
+                .map(|(idx, _)| idx)
+                .collect()
+        };
+
+        // Keep the priority of the dispatch, the catch-all routes are the last resort.
+        matched_route_idxs.sort_by_key(|idx| self.routes[*idx].is_catch_all());
+
+        matched_route_idxs
+            .into_iter()
+            .find(|idx| self.routes[*idx].is_match_method(method))
+            .map(|idx| {
+                let route = &self.routes[idx];
+                let req_meta = route.generate_req_meta(
+                    target_path.as_str(),
+                    raw_target_path.as_str(),
+                    !self.options.decode_before_match,
+                );
+
+                // The trailing slash is appended to the route paths unless the strict slash matching is enabled.
+                let mut pattern = route.path.clone();
+                if !self.options.strict_slash && pattern.len() > 1 && pattern.ends_with('/') {
+                    pattern.pop();
+                }
+
+                RouteMatch {
+                    index: idx,
+                    pattern,
+                    params: req_meta.route_params().cloned().unwrap_or_default(),
+                }
+            })
+    }
+
+    /// Compiles the regexes of the routes, the middlewares and the scoped data maps eagerly, so a misconfigured router fails
+    /// at startup, e.g. in a health check, instead of when it starts serving.
+    ///
+    /// It's done lazily by the [RequestServiceBuilder](./struct.RequestServiceBuilder.html) otherwise. It's idempotent,
+    /// so calling it more than once or serving a warmed up router is fine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> routerify::Result<Router<Body, Infallible>> {
+    /// let mut router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()?;
+    ///
+    /// router.warmup()?;
+    /// # Ok(router)
+    /// # }
+    /// # run().unwrap();
+    /// ```
+    pub fn warmup(&mut self) -> crate::Result<()> {
+        if let Some(ref mut fallback) = self.fallback {
+            fallback.warmup()?;
         }
+
+        self.init_regex_set()?;
+        self.init_req_info_gen();
+
+        Ok(())
     }
 
-    fn downcast_to_hyper_body_type(&mut self) -> Option<&mut Router<hyper::Body, E>> {
-        let any_obj: &mut dyn Any = self;
-        any_obj.downcast_mut::<Router<hyper::Body, E>>()
+    /// Mounts an already built router at the specified prefix, so the routers can be composed at runtime without going
+    /// back through the [RouterBuilder](./struct.RouterBuilder.html), e.g. to mount the routers of the plugins which are
+    /// loaded at runtime.
+    ///
+    /// The routes, the middlewares and the scoped data of the mounted router are re-prefixed and appended to this router,
+    /// like the [`RouterBuilder::scope`](./struct.RouterBuilder.html#method.scope) method does. The error handler, the
+    /// options and the fallback of the mounted router are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> routerify::Result<Router<Body, Infallible>> {
+    /// let plugin: Router<Body, Infallible> = Router::builder()
+    ///     .get("/status", |_| async move { Ok(Response::new(Body::from("Plugin status"))) })
+    ///     .build()?;
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()?
+    ///     .mount("/plugins/stats", plugin)?;
+    /// # Ok(router)
+    /// # }
+    /// # run().unwrap();
+    /// ```
+    pub fn mount<P: Into<String>>(mut self, prefix: P, mut router: Router<B, E>) -> crate::Result<Self> {
+        let mut prefix = prefix.into();
+
+        if prefix.ends_with('/') {
+            prefix.pop();
+        }
+
+        let regex_size_limit = self.options.regex_size_limit;
+
+        for pre_middleware in router.pre_middlewares.iter_mut() {
+            let handler = pre_middleware.handler.take().ok_or_else(mounted_router_error)?;
+            let new_pre_middleware =
+                pre_middleware.with_prefix(&prefix, handler, pre_middleware.scope_depth + 1, regex_size_limit)?;
+            self.pre_middlewares.push(new_pre_middleware);
+        }
+
+        for route in router.routes.iter_mut() {
+            let handler = route.handler.take().ok_or_else(mounted_router_error)?;
+            self.routes
+                .push(route.with_prefix(&prefix, handler, route.scope_depth + 1, regex_size_limit)?);
+        }
+
+        for post_middleware in router.post_middlewares.iter_mut() {
+            let handler = post_middleware.handler.take().ok_or_else(mounted_router_error)?;
+            let new_post_middleware =
+                post_middleware.with_prefix(&prefix, handler, post_middleware.scope_depth + 1, regex_size_limit)?;
+            self.post_middlewares.push(new_post_middleware);
+        }
+
+        for scoped_data_map in router.scoped_data_maps.iter_mut() {
+            self.scoped_data_maps.push(ScopedDataMap::new(
+                format!("{}{}", prefix.as_str(), scoped_data_map.path.as_str()),
+                scoped_data_map.data_map.take().ok_or_else(mounted_router_error)?,
+                regex_size_limit,
+            )?);
+        }
+
+        // The regex set indices must match the new routes and middlewares, so rebuild it if it's already initialized.
+        if self.regex_set.is_some() {
+            self.init_regex_set()?;
+            self.init_req_info_gen();
+        }
+
+        Ok(self)
     }
 
     /// Return a [RouterBuilder](./struct.RouterBuilder.html) instance to build a `Router`.
@@ -251,18 +745,158 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         builder::RouterBuilder::new()
     }
 
+    /// Converts the router into a function which can be passed directly to the hyper
+    /// [`make_service_fn`](https://docs.rs/hyper/0.14.4/hyper/service/fn.make_service_fn.html) to create a service for every
+    /// incoming connection. It's an alternative to the [RouterService](./struct.RouterService.html) type.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use hyper::{service::make_service_fn, Body, Response, Server};
+    /// use routerify::Router;
+    /// use std::convert::Infallible;
+    /// use std::net::SocketAddr;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let router: Router<Body, Infallible> = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    ///     let server = Server::bind(&addr).serve(make_service_fn(router.into_service_fn().unwrap()));
+    ///
+    ///     if let Err(err) = server.await {
+    ///         eprintln!("Server error: {}", err);
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn into_service_fn(
+        self,
+    ) -> crate::Result<
+        impl FnMut(&AddrStream) -> Ready<Result<RequestService<B, E>, Infallible>> + Clone + Send + 'static,
+    > {
+        let builder = RequestServiceBuilder::new(self)?;
+        Ok(move |conn: &AddrStream| ready(Ok(builder.build(conn.remote_addr()))))
+    }
+
+    // Normalizes a request path by the router options into the target path and the raw target path which are passed to the
+    // `process` method. It fails if the path can't be percent decoded, even if it's matched without decoding.
+    pub(crate) fn normalize_path(&self, path: &str) -> crate::Result<(String, String)> {
+        let raw_path = if self.options.merge_slashes {
+            helpers::merge_slashes(path)
+        } else {
+            path.to_owned()
+        };
+
+        let decoded_path = helpers::percent_decode_request_path(raw_path.as_str())?;
+        let mut target_path = if self.options.decode_before_match {
+            decoded_path
+        } else {
+            raw_path.clone()
+        };
+
+        let mut raw_target_path = raw_path;
+        for path in [&mut target_path, &mut raw_target_path] {
+            if path.is_empty() || (!self.options.strict_slash && !path.ends_with('/')) {
+                path.push('/');
+            }
+        }
+
+        Ok((target_path, raw_target_path))
+    }
+
+    // The target path is the normalized request path which is matched against the routes, the raw target path is the
+    // same path before percent decoding, the raw values of the route parameters are captured from it.
     pub(crate) async fn process(
         &self,
         target_path: &str,
+        raw_target_path: &str,
+        req: Request<hyper::Body>,
+        mut req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        let (pre_idxs, route_idxs, post_idxs, data_map_idxs) = self.match_regex_set(target_path);
+        let matched_idxs = (pre_idxs, route_idxs, post_idxs);
+        let shared_data_maps = self.shared_data_maps(&data_map_idxs);
+
+        // Capture the matched data up front, so it's also available to the hooks and to the error handler when the request
+        // never reaches a route, e.g. on timeout.
+        if let Some(ref mut req_info) = req_info {
+            req_info.shared_data_maps = Some(shared_data_maps.clone());
+        }
+
+        if let (Some(on_request), Some(req_info)) = (self.on_request.as_ref(), req_info.as_ref()) {
+            on_request(req_info);
+        }
+
+        let mut res = match self.options.request_timeout {
+            Some(timeout) => {
+                let dispatch = self.dispatch(
+                    target_path,
+                    raw_target_path,
+                    req,
+                    req_info.clone(),
+                    matched_idxs,
+                    shared_data_maps,
+                );
+                match tokio::time::timeout(timeout, dispatch).await {
+                    Ok(res) => res,
+                    // The timeout can expire before a route is selected, so it's always handled by the root error
+                    // handler.
+                    Err(_) => {
+                        let err: RouteError =
+                            RequestTimeoutError::new(timeout, self.options.request_timeout_status).into();
+                        match self.err_handler {
+                            Some(ref err_handler) => Ok(err_handler.execute(err, req_info.clone()).await),
+                            None => Err(err),
+                        }
+                    }
+                }
+            }
+            None => {
+                self.dispatch(
+                    target_path,
+                    raw_target_path,
+                    req,
+                    req_info.clone(),
+                    matched_idxs,
+                    shared_data_maps,
+                )
+                .await
+            }
+        };
+
+        // A `not_modified()` response has no body, so drop the content type a post middleware could have set for it.
+        if let Ok(ref mut res) = res {
+            if res.status() == StatusCode::NOT_MODIFIED && res.extensions().get::<NotModified>().is_some() {
+                res.headers_mut().remove(header::CONTENT_TYPE);
+            }
+        }
+
+        if let (Some(on_response), Some(req_info), Ok(res)) =
+            (self.on_response.as_ref(), req_info.as_ref(), res.as_ref())
+        {
+            on_response(req_info, res);
+        }
+
+        res
+    }
+
+    async fn dispatch(
+        &self,
+        target_path: &str,
+        raw_target_path: &str,
         mut req: Request<hyper::Body>,
         mut req_info: Option<RequestInfo>,
+        matched_idxs: (Vec<usize>, Vec<usize>, Vec<usize>),
+        shared_data_maps: Vec<SharedDataMap>,
     ) -> crate::Result<Response<B>> {
-        let (
-            matched_pre_middleware_idxs,
-            matched_route_idxs,
-            matched_post_middleware_idxs,
-            matched_scoped_data_map_idxs,
-        ) = self.match_regex_set(target_path);
+        let (matched_pre_middleware_idxs, mut matched_route_idxs, matched_post_middleware_idxs) = matched_idxs;
+
+        // The catch-all routes must have the lowest priority regardless of their registration order.
+        matched_route_idxs.sort_by_key(|idx| self.routes[*idx].is_catch_all());
 
         let mut route_scope_depth = None;
         for idx in &matched_route_idxs {
@@ -272,20 +906,20 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             // an actual route match, not a catch-all "/*".
             if route.is_match_method(req.method()) && route.path != "/*" {
                 route_scope_depth = Some(route.scope_depth);
+                // Expose the tags of the route to the pre middlewares, e.g. to enforce permissions before the handler runs.
+                if !route.tags.is_empty() {
+                    helpers::update_req_meta_in_extensions(
+                        req.extensions_mut(),
+                        RequestMeta::with_route_tags(route.tags.clone()),
+                    );
+                }
                 break;
             }
         }
 
-        let shared_data_maps = matched_scoped_data_map_idxs
-            .into_iter()
-            .map(|idx| self.scoped_data_maps[idx].clone_data_map())
-            .collect::<Vec<_>>();
-
-        if let Some(ref mut req_info) = req_info {
-            if !shared_data_maps.is_empty() {
-                req_info.shared_data_maps.replace(shared_data_maps.clone());
-            }
-        }
+        // Delegate the request to the fallback router if nothing but the catch-all routes could match it. It's delegated
+        // after the pre middlewares, so the middlewares of this router apply to the fallback responses too.
+        let fallback = self.fallback.as_ref().filter(|_| route_scope_depth.is_none());
 
         let ext = req.extensions_mut();
         ext.insert(shared_data_maps);
@@ -299,18 +933,72 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         // (because Router.err_handler is set), then skip directly to post
         // middleware.
         let mut resp = None;
-        match res_pre {
-            Ok(transformed_req) => {
+        let mut is_error_response = false;
+        match (res_pre, fallback) {
+            (Ok(transformed_req), Some(fallback)) => {
+                let (pre_idxs, route_idxs, post_idxs, data_map_idxs) = fallback.match_regex_set(target_path);
+                let fallback_shared_data_maps = fallback.shared_data_maps(&data_map_idxs);
+
+                // The fallback router's data takes over if it has any for the request.
+                let mut fallback_req_info = req_info.clone();
+                if let Some(ref mut req_info) = fallback_req_info {
+                    if !fallback_shared_data_maps.is_empty() {
+                        req_info.shared_data_maps = Some(fallback_shared_data_maps.clone());
+                    }
+                }
+
+                let fallback_resp = Box::pin(fallback.dispatch(
+                    target_path,
+                    raw_target_path,
+                    transformed_req,
+                    fallback_req_info,
+                    (pre_idxs, route_idxs, post_idxs),
+                    fallback_shared_data_maps,
+                ))
+                .await?;
+                resp = Some(fallback_resp);
+            }
+            (Ok(transformed_req), None) => {
                 for idx in matched_route_idxs {
                     let route = &self.routes[idx];
 
                     if route.is_match_method(transformed_req.method()) {
-                        let route_resp_res = route.process(target_path, transformed_req).await;
+                        let req_meta =
+                            route.generate_req_meta(target_path, raw_target_path, !self.options.decode_before_match);
+                        if let Some(ref mut req_info) = req_info {
+                            req_info.route_params = req_meta.route_params().cloned();
+                        }
+
+                        let route_resp_res = if route.is_body_too_large(transformed_req.headers()) {
+                            Self::reject(
+                                self.on_payload_too_large.as_ref(),
+                                StatusCode::PAYLOAD_TOO_LARGE,
+                                req_info.clone(),
+                                Error::new("The request body is too large").into(),
+                            )
+                            .await
+                        } else {
+                            let exceeded = Arc::new(AtomicBool::new(false));
+                            match route.process(req_meta, transformed_req, exceeded.clone()).await {
+                                // The handler failed as it couldn't read the oversized body.
+                                Err(err) if exceeded.load(Ordering::SeqCst) => {
+                                    Self::reject(
+                                        self.on_payload_too_large.as_ref(),
+                                        StatusCode::PAYLOAD_TOO_LARGE,
+                                        req_info.clone(),
+                                        err,
+                                    )
+                                    .await
+                                }
+                                route_resp_res => route_resp_res,
+                            }
+                        };
 
                         let route_resp = match route_resp_res {
                             Ok(route_resp) => route_resp,
                             Err(err) => {
-                                if let Some(ref err_handler) = self.err_handler {
+                                if let Some(err_handler) = route.err_handler.as_deref().or(self.err_handler.as_ref()) {
+                                    is_error_response = true;
                                     err_handler.execute(err, req_info.clone()).await
                                 } else {
                                     return Err(err);
@@ -323,19 +1011,39 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
                     }
                 }
             }
-            Err(err_response) => {
+            (Err(err_response), _) => {
+                is_error_response = true;
                 resp = Some(err_response);
             }
         };
 
+        if let Some(ref mut req_info) = req_info {
+            req_info.is_error_response = is_error_response;
+        }
+
         if resp.is_none() {
             let e = "No handlers added to handle non-existent routes. Tips: Please add an '.any' route at the bottom to handle any routes.";
             return Err(crate::Error::new(e).into());
         }
 
         let mut transformed_res = resp.unwrap();
+
+        // The handler has asked to send its response as is.
+        if transformed_res.extensions().get::<SkipPostMiddleware>().is_some() {
+            return Ok(transformed_res);
+        }
+
+        // The handler has asked to skip the post middlewares of some scopes.
+        let skipped_scopes = transformed_res.extensions().get::<SkipPostMiddlewareScopes>().cloned();
+
         for idx in matched_post_middleware_idxs {
             let post_middleware = &self.post_middlewares[idx];
+            if let Some(ref skipped_scopes) = skipped_scopes {
+                if skipped_scopes.is_skipped(post_middleware.scope_depth) {
+                    continue;
+                }
+            }
+
             // Do not execute middleware with the same prefix but from a deeper scope.
             if route_scope_depth.is_none() || post_middleware.scope_depth <= route_scope_depth.unwrap() {
                 match post_middleware.process(transformed_res, req_info.clone()).await {
@@ -385,6 +1093,13 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Ok(Ok(transformed_req))
     }
 
+    // Shares the matched scoped data maps with the request, they're cloned only once per router.
+    fn shared_data_maps(&self, idxs: &[usize]) -> Vec<SharedDataMap> {
+        idxs.iter()
+            .map(|idx| self.scoped_data_maps[*idx].clone_data_map())
+            .collect()
+    }
+
     fn match_regex_set(&self, target_path: &str) -> (Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>) {
         let matches = self
             .regex_set
@@ -394,7 +1109,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             .into_iter();
 
         let pre_middlewares_len = self.pre_middlewares.len();
-        let routes_len = self.routes.len();
+        let routes_len = self.regex_set_route_idxs.len();
         let post_middlewares_len = self.post_middlewares.len();
         let scoped_data_maps_len = self.scoped_data_maps.len();
 
@@ -407,7 +1122,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             if idx < pre_middlewares_len {
                 matched_pre_middleware_idxs.push(idx);
             } else if idx >= pre_middlewares_len && idx < (pre_middlewares_len + routes_len) {
-                matched_route_idxs.push(idx - pre_middlewares_len);
+                matched_route_idxs.push(self.regex_set_route_idxs[idx - pre_middlewares_len]);
             } else if idx >= (pre_middlewares_len + routes_len)
                 && idx < (pre_middlewares_len + routes_len + post_middlewares_len)
             {
@@ -419,6 +1134,12 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
             }
         }
 
+        // Keep the registration order of the routes, as if all of them were matched by the regex set.
+        if let Some(ref route_trie) = self.route_trie {
+            matched_route_idxs.extend_from_slice(route_trie.find(target_path));
+            matched_route_idxs.sort_unstable();
+        }
+
         (
             matched_pre_middleware_idxs,
             matched_route_idxs,
@@ -432,12 +1153,14 @@ impl<B, E> Debug for Router<B, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{ Pre-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, ScopedDataMaps: {:?}, ErrHandler: {:?}, ShouldGenReqInfo: {:?} }}",
+            "{{ Pre-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, ScopedDataMaps: {:?}, ErrHandler: {:?}, Options: {:?}, Fallback: {:?}, ShouldGenReqInfo: {:?} }}",
             self.pre_middlewares,
             self.routes,
             self.post_middlewares,
             self.scoped_data_maps,
             self.err_handler.is_some(),
+            self.options,
+            self.fallback,
             self.should_gen_req_info
         )
     }
@@ -0,0 +1,54 @@
+use crate::types::Matcher;
+use crate::RouteError;
+use hyper::StatusCode;
+use std::time::Duration;
+
+// Derives the suggested response status of an error, it's registered by the `RouterBuilder::error_status` method.
+pub(crate) type StatusHint = fn(&RouteError) -> Option<StatusCode>;
+
+// The options which are applied on the incoming requests by the RequestService.
+// These options are only respected on the root Router, any options set on a scoped router will be ignored.
+#[derive(Debug, Clone)]
+pub(crate) struct RouterOptions {
+    pub(crate) merge_slashes: bool,
+    pub(crate) trust_proxy_headers: bool,
+    pub(crate) body_read_timeout: Option<Duration>,
+    // The timeout of the whole pipeline of a request, i.e. the pre middlewares, the handler and the post middlewares.
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) request_timeout_status: StatusCode,
+    pub(crate) decode_before_match: bool,
+    pub(crate) strict_slash: bool,
+    pub(crate) keep_alive: bool,
+    pub(crate) default_charset: Option<String>,
+    pub(crate) status_hints: Vec<StatusHint>,
+    // Whether the default 404 route and the default error handler respond with JSON bodies, it requires the `json` feature.
+    pub(crate) json_errors: bool,
+    pub(crate) matcher: Matcher,
+    // The maximum compiled size of the regex of a route or a middleware path in bytes, it's checked on build.
+    pub(crate) regex_size_limit: Option<usize>,
+    // The limits of the request path, a longer request path is rejected with `414 URI Too Long` before matching.
+    pub(crate) max_path_length: Option<usize>,
+    pub(crate) max_path_segments: Option<usize>,
+}
+
+impl Default for RouterOptions {
+    fn default() -> Self {
+        RouterOptions {
+            merge_slashes: false,
+            trust_proxy_headers: false,
+            body_read_timeout: None,
+            request_timeout: None,
+            request_timeout_status: StatusCode::GATEWAY_TIMEOUT,
+            decode_before_match: true,
+            strict_slash: false,
+            keep_alive: false,
+            default_charset: None,
+            status_hints: Vec::new(),
+            json_errors: false,
+            matcher: Matcher::default(),
+            regex_size_limit: None,
+            max_path_length: None,
+            max_path_segments: None,
+        }
+    }
+}
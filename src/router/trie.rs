@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+// A trie of the literal route paths keyed by the path segments, it maps a path to the indices of the routes with that
+// exact path. Splitting by `/` keeps the empty segments, so a lookup matches the same paths as the exact match regexes.
+#[derive(Debug, Default)]
+pub(crate) struct RouteTrie {
+    root: Node,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: HashMap<String, Node>,
+    route_idxs: Vec<usize>,
+}
+
+impl RouteTrie {
+    // Checks whether the route path can be matched by the trie, i.e. it has no parameter or `*` glob.
+    pub(crate) fn is_literal(path: &str) -> bool {
+        !path.contains(':') && !path.contains('*')
+    }
+
+    pub(crate) fn insert(&mut self, path: &str, route_idx: usize) {
+        let node = path.split('/').fold(&mut self.root, |node, segment| {
+            node.children.entry(segment.to_owned()).or_default()
+        });
+        node.route_idxs.push(route_idx);
+    }
+
+    pub(crate) fn find(&self, path: &str) -> &[usize] {
+        let mut node = &self.root;
+        for segment in path.split('/') {
+            match node.children.get(segment) {
+                Some(child) => node = child,
+                None => return &[],
+            }
+        }
+        node.route_idxs.as_slice()
+    }
+}
@@ -0,0 +1,268 @@
+use crate::service::request_service::{RequestService, RequestServiceBuilder};
+use crate::Error;
+use futures_core::Stream;
+use hyper::body::{Bytes, HttpBody};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A hyper 1.x [`Service`](https://docs.rs/hyper/1/hyper/service/trait.Service.html) which processes the requests of a
+/// single connection, e.g. the `Request<Incoming>` requests yielded by the hyper 1.x server connections.
+///
+/// It can be created by the [`RequestServiceBuilder::build_hyper1`](./struct.RequestServiceBuilder.html#method.build_hyper1)
+/// method with the remote address of the accepted connection. The requests and the responses are converted from and into
+/// the hyper 0.14 types which are used by the router. The request trailers are dropped.
+///
+/// It's available only if the `hyper1` feature is enabled.
+///
+/// # Examples
+///
+/// ```no_run
+/// use hyper::{Body, Response};
+/// use routerify::{RequestServiceBuilder, Router};
+/// use std::convert::Infallible;
+/// use tokio::net::TcpListener;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let router: Router<Body, Infallible> = Router::builder()
+///         .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///         .build()
+///         .unwrap();
+///
+///     let builder = RequestServiceBuilder::new(router).unwrap();
+///     let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+///
+///     loop {
+///         let (stream, remote_addr) = listener.accept().await.unwrap();
+///         let service = builder.build_hyper1(remote_addr);
+///
+///         // Serve the connection with a hyper 1.x server connection, e.g. with the `hyper-util` crate:
+///         // http1::Builder::new().serve_connection(TokioIo::new(stream), service)
+///         # drop((stream, service));
+///     }
+/// }
+/// ```
+pub struct Hyper1Service<E> {
+    builder: RequestServiceBuilder<hyper::Body, E>,
+    remote_addr: SocketAddr,
+}
+
+impl<E> Clone for Hyper1Service<E> {
+    fn clone(&self) -> Self {
+        Hyper1Service {
+            builder: self.builder.clone(),
+            remote_addr: self.remote_addr,
+        }
+    }
+}
+
+impl<E> Debug for Hyper1Service<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ remote_addr: {:?} }}", self.remote_addr)
+    }
+}
+
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static> RequestServiceBuilder<hyper::Body, E> {
+    /// Creates a new [`Hyper1Service`](./struct.Hyper1Service.html) for a hyper 1.x connection from the specified remote
+    /// address.
+    pub fn build_hyper1(&self, remote_addr: SocketAddr) -> Hyper1Service<E> {
+        Hyper1Service {
+            builder: self.clone(),
+            remote_addr,
+        }
+    }
+}
+
+impl<E, ReqBody> hyper1::service::Service<http1::Request<ReqBody>> for Hyper1Service<E>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    ReqBody: http_body1::Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Response = http1::Response<Hyper1Body>;
+    type Error = crate::RouteError;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn call(&self, req: http1::Request<ReqBody>) -> Self::Future {
+        let mut service: RequestService<hyper::Body, E> = self.builder.build(self.remote_addr);
+
+        Box::pin(async move {
+            let req = convert_request(req)?;
+            let res = hyper::service::Service::call(&mut service, req).await?;
+            convert_response(res)
+        })
+    }
+}
+
+fn convert_request<B>(req: http1::Request<B>) -> crate::Result<hyper::Request<hyper::Body>>
+where
+    B: http_body1::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = req.into_parts();
+
+    let mut builder = hyper::Request::builder()
+        .method(parts.method.as_str())
+        .uri(parts.uri.to_string())
+        .version(match parts.version {
+            http1::Version::HTTP_09 => hyper::Version::HTTP_09,
+            http1::Version::HTTP_10 => hyper::Version::HTTP_10,
+            http1::Version::HTTP_2 => hyper::Version::HTTP_2,
+            http1::Version::HTTP_3 => hyper::Version::HTTP_3,
+            _ => hyper::Version::HTTP_11,
+        });
+
+    for (name, value) in parts.headers.iter() {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+
+    builder
+        .body(hyper::Body::wrap_stream(DataStream(Box::pin(body))))
+        .map_err(|e| Error::new(format!("Couldn't convert the hyper 1.x request: {}", e)).into())
+}
+
+fn convert_response(res: hyper::Response<hyper::Body>) -> crate::Result<http1::Response<Hyper1Body>> {
+    let (parts, body) = res.into_parts();
+
+    let mut builder = http1::Response::builder().status(parts.status.as_u16());
+
+    for (name, value) in parts.headers.iter() {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+
+    builder.body(Hyper1Body { body, data_done: false }).map_err(|e| {
+        Error::new(format!(
+            "Couldn't convert the response into a hyper 1.x response: {}",
+            e
+        ))
+        .into()
+    })
+}
+
+// Yields the data frames of a hyper 1.x request body, the trailers are skipped.
+struct DataStream<B>(Pin<Box<B>>);
+
+impl<B> Stream for DataStream<B>
+where
+    B: http_body1::Body<Data = Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            return match self.0.as_mut().poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => Poll::Ready(Some(Ok(data))),
+                    Err(_) => continue,
+                },
+                Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
+
+/// The response body of the [`Hyper1Service`](./struct.Hyper1Service.html), it wraps the hyper 0.14 body returned by the
+/// router.
+pub struct Hyper1Body {
+    body: hyper::Body,
+    data_done: bool,
+}
+
+impl Debug for Hyper1Body {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.body)
+    }
+}
+
+impl http_body1::Body for Hyper1Body {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body1::Frame<Self::Data>, Self::Error>>> {
+        if !self.data_done {
+            match Pin::new(&mut self.body).poll_data(cx) {
+                Poll::Ready(Some(Ok(data))) => return Poll::Ready(Some(Ok(http_body1::Frame::data(data)))),
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => self.data_done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        match Pin::new(&mut self.body).poll_trailers(cx) {
+            Poll::Ready(Ok(Some(trailers))) => {
+                let mut headers = http1::HeaderMap::with_capacity(trailers.len());
+                for (name, value) in trailers.iter() {
+                    if let (Ok(name), Ok(value)) = (
+                        http1::HeaderName::from_bytes(name.as_str().as_bytes()),
+                        http1::HeaderValue::from_bytes(value.as_bytes()),
+                    ) {
+                        headers.append(name, value);
+                    }
+                }
+                Poll::Ready(Some(Ok(http_body1::Frame::trailers(headers))))
+            }
+            Poll::Ready(Ok(None)) => Poll::Ready(None),
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data_done && self.body.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, RequestServiceBuilder, Router};
+    use hyper::{Body, Response};
+    use hyper1::service::Service;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    #[tokio::test]
+    async fn should_route_hyper1_request() {
+        let router: Router<Body, Error> = Router::builder()
+            .post("/echo/:name", |req| async move {
+                let name = crate::ext::RequestExt::param(&req, "name").unwrap().to_owned();
+                let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                Ok(Response::builder()
+                    .header("x-name", name)
+                    .body(Body::from(body))
+                    .unwrap())
+            })
+            .build()
+            .unwrap();
+        let service = RequestServiceBuilder::new(router)
+            .unwrap()
+            .build_hyper1(([127, 0, 0, 1], 8080).into());
+
+        let req = http1::Request::builder()
+            .method("POST")
+            .uri("/echo/alice")
+            .body(String::from("hello"))
+            .unwrap();
+        let resp = service.call(req).await.unwrap();
+        assert_eq!(resp.status(), http1::StatusCode::OK);
+        assert_eq!(resp.headers()["x-name"], "alice");
+
+        let mut body = resp.into_body();
+        let mut data = Vec::new();
+        while let Some(frame) = poll_fn(|cx| http_body1::Body::poll_frame(Pin::new(&mut body), cx)).await {
+            if let Ok(chunk) = frame.unwrap().into_data() {
+                data.extend_from_slice(&chunk);
+            }
+        }
+        assert_eq!(data, b"hello");
+    }
+}
@@ -1,5 +1,9 @@
+#[cfg(feature = "hyper1")]
+pub use hyper1::{Hyper1Body, Hyper1Service};
 pub use request_service::{RequestService, RequestServiceBuilder};
-pub use router_service::RouterService;
+pub use router_service::{RemoteAddr, RouterService, UNSPECIFIED_REMOTE_ADDR};
 
+#[cfg(feature = "hyper1")]
+mod hyper1;
 mod request_service;
 mod router_service;
@@ -1,8 +1,10 @@
+use crate::body::TimeoutBody;
 use crate::helpers;
-use crate::router::Router;
-use crate::types::{RequestContext, RequestInfo, RequestMeta};
+use crate::router::{Router, RouterOptions};
+use crate::service::router_service::UNSPECIFIED_REMOTE_ADDR;
+use crate::types::{DisconnectToken, RequestContext, RequestInfo, RequestMeta, Warning};
 use crate::Error;
-use hyper::{body::HttpBody, service::Service, Request, Response};
+use hyper::{body::HttpBody, service::Service, Request, Response, StatusCode};
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -26,78 +28,246 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, mut req: Request<hyper::Body>) -> Self::Future {
+    fn call(&mut self, req: Request<hyper::Body>) -> Self::Future {
         let router = self.router.clone();
         let remote_addr = self.remote_addr;
 
-        let fut = async move {
-            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+        Box::pin(async move { serve(router, remote_addr, req, false).await.map(|(resp, _)| resp) })
+    }
+}
 
-            let mut target_path = helpers::percent_decode_request_path(req.uri().path())
-                .map_err(|e| Error::new(format!("Couldn't percent decode request path: {}", e)))?;
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    RequestService<B, E>
+{
+    /// Handles the request like the [`Service::call`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html#tymethod.call)
+    /// method does, and returns the [request info](./struct.RequestInfo.html) of the request along with the response, so
+    /// an embedder which dispatches the requests by itself can log the method, the path and the status together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RequestServiceBuilder, Router};
+    /// use hyper::{Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # async fn run() {
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let service = RequestServiceBuilder::new(router).unwrap().build(([127, 0, 0, 1], 0).into());
+    /// let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+    ///
+    /// let (resp, req_info) = service.handle_with_info(req).await.unwrap();
+    /// println!("{} {} {}", req_info.method(), req_info.uri().path(), resp.status());
+    /// # }
+    /// ```
+    pub async fn handle_with_info(&self, req: Request<hyper::Body>) -> crate::Result<(Response<B>, RequestInfo)> {
+        let (resp, req_info) = serve(self.router.clone(), self.remote_addr, req, true).await?;
+        Ok((resp, req_info.expect("The request info must be generated")))
+    }
+}
 
-            if target_path.is_empty() || target_path.as_bytes()[target_path.len() - 1] != b'/' {
-                target_path.push('/');
-            }
+// Runs the request through the router. The request info is generated if the router requires it or if `with_info` is set,
+// and it's returned along with the response.
+async fn serve<B, E>(
+    router: Arc<Router<B, E>>,
+    remote_addr: SocketAddr,
+    mut req: Request<hyper::Body>,
+    with_info: bool,
+) -> crate::Result<(Response<B>, Option<RequestInfo>)>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let disconnect_token = DisconnectToken::new();
 
-            let mut req_info = None;
-            let should_gen_req_info = router
-                .should_gen_req_info
-                .expect("The `should_gen_req_info` flag in Router is not initialized");
+    helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+    req.extensions_mut().insert(disconnect_token.clone());
+    // The pre middlewares may rewrite the uri, so keep the original one, e.g. to verify a signature over it.
+    let original_uri = req.uri().clone();
+    helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_original_uri(original_uri));
 
-            let context = RequestContext::new();
+    if is_path_too_long(req.uri().path(), &router.options) {
+        let req_info = with_info.then(|| RequestInfo::new_from_req(&req, RequestContext::new()));
+        let err = Error::new(format!("The request path is too long: {}", req.uri().path())).into();
+        let resp = router.gen_error_response(StatusCode::URI_TOO_LONG, err)?;
+        return Ok((resp, req_info));
+    }
 
-            if should_gen_req_info {
-                req_info = Some(RequestInfo::new_from_req(&req, context.clone()));
+    if let Some(rewritten_path) = router.rewrite.as_ref().and_then(|rewrite| rewrite(req.uri().path())) {
+        match helpers::replace_uri_path(req.uri(), rewritten_path.as_str()) {
+            Ok(uri) => *req.uri_mut() = uri,
+            // The rewritten path is produced by the application, so an invalid one is a server error.
+            Err(err) => {
+                let req_info = with_info.then(|| RequestInfo::new_from_req(&req, RequestContext::new()));
+                let resp = router.gen_error_response(StatusCode::INTERNAL_SERVER_ERROR, err)?;
+                return Ok((resp, req_info));
             }
+        }
+    }
+
+    helpers::update_req_meta_in_extensions(
+        req.extensions_mut(),
+        RequestMeta::with_trust_proxy_headers(router.options.trust_proxy_headers),
+    );
+
+    if let Some(timeout) = router.options.body_read_timeout {
+        req = req.map(|body| TimeoutBody::new(body, timeout).into_body());
+    }
+
+    // A malformed request path is a client error, so respond with `400 Bad Request` instead of failing.
+    let (target_path, raw_target_path) = match router.normalize_path(req.uri().path()) {
+        Ok(paths) => paths,
+        Err(e) => {
+            let req_info = (with_info || router.on_bad_request.is_some())
+                .then(|| RequestInfo::new_from_req(&req, RequestContext::new()));
+            let err = Error::new(format!("Couldn't percent decode request path: {}", e)).into();
+            let resp = Router::<B, E>::reject(
+                router.on_bad_request.as_ref(),
+                StatusCode::BAD_REQUEST,
+                req_info.clone(),
+                err,
+            )
+            .await?;
+            return Ok((resp, req_info));
+        }
+    };
 
-            req.extensions_mut().insert(context);
+    let mut req_info = None;
+    let should_gen_req_info = router
+        .should_gen_req_info
+        .expect("The `should_gen_req_info` flag in Router is not initialized");
 
-            router.process(target_path.as_str(), req, req_info.clone()).await
-        };
+    let context = RequestContext::new();
 
-        Box::pin(fut)
+    if should_gen_req_info || with_info {
+        req_info = Some(RequestInfo::new_from_req(&req, context.clone()));
     }
+
+    req.extensions_mut().insert(context);
+
+    // Hyper drops this future when the client closes the connection, the guard then flags the token.
+    let guard = disconnect_token.guard();
+    let resp = router
+        .process(target_path.as_str(), raw_target_path.as_str(), req, req_info.clone())
+        .await;
+    guard.disarm();
+
+    Ok((resp?, req_info))
+}
+
+// Checks the request path as it's received against the length and the segment count limits of the router.
+fn is_path_too_long(path: &str, options: &RouterOptions) -> bool {
+    let too_many_bytes = options.max_path_length.is_some_and(|max_len| path.len() > max_len);
+    let too_many_segments = options
+        .max_path_segments
+        .is_some_and(|max_segments| path.split('/').filter(|seg| !seg.is_empty()).count() > max_segments);
+
+    too_many_bytes || too_many_segments
 }
 
+/// A builder which creates a [`RequestService`](./struct.RequestService.html) for every incoming connection.
+///
+/// The router is shared behind an `Arc`, so the builder is cheap to clone. A cloned builder can be used to serve the same
+/// router on multiple servers, e.g. on an HTTP and an HTTPS address, via [`RouterService::from_builder`](./struct.RouterService.html#method.from_builder).
 #[derive(Debug)]
 pub struct RequestServiceBuilder<B, E> {
     router: Arc<Router<B, E>>,
 }
 
+impl<B, E> Clone for RequestServiceBuilder<B, E> {
+    fn clone(&self) -> Self {
+        RequestServiceBuilder {
+            router: self.router.clone(),
+        }
+    }
+}
+
 impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
     RequestServiceBuilder<B, E>
 {
-    pub fn new(mut router: Router<B, E>) -> crate::Result<Self> {
-        // router.init_keep_alive_middleware();
+    /// Creates a new builder from the provided router and initializes the router so that it's ready to serve requests.
+    ///
+    /// The [warnings](./enum.Warning.html) reported while initializing the router are printed to the standard error, use
+    /// the [`new_with_warnings`](#method.new_with_warnings) method to handle them instead.
+    pub fn new(router: Router<B, E>) -> crate::Result<Self> {
+        let (builder, warnings) = Self::new_with_warnings(router)?;
 
-        router.init_global_options_route();
-        router.init_default_404_route();
+        for warning in warnings {
+            eprintln!("Warning: {}\n", warning);
+        }
+
+        Ok(builder)
+    }
+
+    /// Like the [`new`](#method.new) method, but it returns the [warnings](./enum.Warning.html) reported while initializing
+    /// the router instead of printing them, so they can be logged through the app's own logging system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{RequestServiceBuilder, Router};
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let (builder, warnings) = RequestServiceBuilder::new_with_warnings(router).unwrap();
+    /// for warning in warnings {
+    ///     println!("Router warning: {}", warning);
+    /// }
+    /// # drop(builder);
+    /// ```
+    pub fn new_with_warnings(mut router: Router<B, E>) -> crate::Result<(Self, Vec<Warning>)> {
+        let mut warnings = Vec::new();
 
-        router.init_err_handler();
+        router.init_keep_alive_middleware();
+        router.init_default_charset_middleware();
 
+        warnings.extend(router.init_global_options_route());
+        warnings.extend(router.init_default_404_route());
+
+        warnings.extend(router.init_err_handler());
+
+        router.init_fallback_router(&mut warnings)?;
         router.init_regex_set()?;
         router.init_req_info_gen();
-        Ok(Self {
-            router: Arc::from(router),
-        })
+        Ok((
+            Self {
+                router: Arc::from(router),
+            },
+            warnings,
+        ))
     }
 
+    /// Creates a new [`RequestService`](./struct.RequestService.html) for a connection from the specified remote address.
     pub fn build(&self, remote_addr: SocketAddr) -> RequestService<B, E> {
         RequestService {
             router: self.router.clone(),
             remote_addr,
         }
     }
+
+    /// Creates a new [`RequestService`](./struct.RequestService.html) for a connection without a socket address, e.g. a
+    /// Unix domain socket connection. The remote address of its requests is the
+    /// [`UNSPECIFIED_REMOTE_ADDR`](./constant.UNSPECIFIED_REMOTE_ADDR.html) placeholder.
+    pub fn build_without_addr(&self) -> RequestService<B, E> {
+        self.build(UNSPECIFIED_REMOTE_ADDR)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Error, RequestServiceBuilder, RouteError, Router};
+    use crate::{Error, RequestServiceBuilder, RouteError, Router, Warning};
     use futures::future::poll_fn;
     use http::Method;
     use hyper::service::Service;
-    use hyper::{Body, Request, Response};
+    use hyper::{Body, Request, Response, StatusCode};
     use std::net::SocketAddr;
     use std::str::FromStr;
     use std::task::Poll;
@@ -125,4 +295,166 @@ mod tests {
         let body = String::from_utf8(hyper::body::to_bytes(body).await.unwrap().to_vec()).unwrap();
         assert_eq!(RESPONSE_TEXT, body)
     }
+
+    #[tokio::test]
+    async fn should_route_request_from_cloned_builder() {
+        const RESPONSE_TEXT: &str = "Hello world!";
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<hyper::body::Body, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(Body::from(RESPONSE_TEXT))) })
+            .build()
+            .unwrap();
+        let builder = RequestServiceBuilder::new(router).unwrap();
+
+        for builder in [builder.clone(), builder] {
+            let req = Request::builder()
+                .method(Method::GET)
+                .uri("/")
+                .body(hyper::Body::empty())
+                .unwrap();
+            let mut service = builder.build(remote_addr);
+            let resp: Response<hyper::body::Body> = service.call(req).await.unwrap();
+            let body = resp.into_body();
+            let body = String::from_utf8(hyper::body::to_bytes(body).await.unwrap().to_vec()).unwrap();
+            assert_eq!(RESPONSE_TEXT, body)
+        }
+    }
+
+    #[test]
+    fn should_return_warnings_for_non_hyper_body() {
+        let router: Router<String, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(String::from("Hello world!"))) })
+            .build()
+            .unwrap();
+        let (_, warnings) = RequestServiceBuilder::new_with_warnings(router).unwrap();
+        assert_eq!(
+            warnings,
+            vec![Warning::NoOptionsRoute, Warning::No404Route, Warning::NoErrHandler]
+        );
+
+        let router: Router<hyper::Body, Error> = Router::builder().build().unwrap();
+        let (_, warnings) = RequestServiceBuilder::new_with_warnings(router).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_add_defaults_for_custom_body_with_response_factory() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<String, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(String::from("Hello world!"))) })
+            .default_response_factory(|status, text| Response::builder().status(status).body(text).unwrap())
+            .build()
+            .unwrap();
+        let (builder, warnings) = RequestServiceBuilder::new_with_warnings(router).unwrap();
+        assert!(warnings.is_empty());
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("/missing")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let resp: Response<String> = builder.build(remote_addr).call(req).await.unwrap();
+        assert_eq!(resp.status(), hyper::StatusCode::NOT_FOUND);
+        assert_eq!(resp.into_body(), "Not Found");
+    }
+
+    #[tokio::test]
+    async fn should_reject_too_long_path_for_custom_body_with_response_factory() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<String, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(String::from("Hello world!"))) })
+            .max_path_length(8)
+            .default_response_factory(|status, text| Response::builder().status(status).body(text).unwrap())
+            .build()
+            .unwrap();
+        let builder = RequestServiceBuilder::new(router).unwrap();
+
+        let req = Request::builder()
+            .uri("/too/long/path")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let resp: Response<String> = builder.build(remote_addr).call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+        assert_eq!(resp.headers()["content-type"], "text/plain");
+        assert_eq!(resp.into_body(), "URI Too Long");
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_rewritten_path_for_custom_body_with_response_factory() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<String, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(String::from("Hello world!"))) })
+            .rewrite(|path: &str| Some(format!("{} invalid", path)))
+            .default_response_factory(|status, text| Response::builder().status(status).body(text).unwrap())
+            .build()
+            .unwrap();
+        let builder = RequestServiceBuilder::new(router).unwrap();
+
+        let req = Request::builder().uri("/old").body(hyper::Body::empty()).unwrap();
+        let resp: Response<String> = builder.build(remote_addr).call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(resp.into_body(), "Internal Server Error");
+    }
+
+    #[tokio::test]
+    async fn should_return_request_info_with_response() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<hyper::body::Body, Error> = Router::builder()
+            .post(
+                "/users/:id",
+                |_| async move { Ok(Response::new(Body::from("Updated"))) },
+            )
+            .build()
+            .unwrap();
+        let service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/users/1?notify=true")
+            .header("x-request-id", "42")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let (resp, req_info) = service.handle_with_info(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(req_info.method(), Method::POST);
+        assert_eq!(req_info.uri(), "/users/1?notify=true");
+        assert_eq!(req_info.headers()["x-request-id"], "42");
+
+        let req = Request::builder().uri("/unknown").body(hyper::Body::empty()).unwrap();
+        let (resp, req_info) = service.handle_with_info(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(req_info.uri().path(), "/unknown");
+    }
+
+    #[tokio::test]
+    async fn should_return_request_info_with_too_long_path() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<hyper::body::Body, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+            .max_path_segments(2)
+            .build()
+            .unwrap();
+        let service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+
+        let req = Request::builder().uri("/a/b/c").body(hyper::Body::empty()).unwrap();
+        let (resp, req_info) = service.handle_with_info(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+        assert_eq!(req_info.uri().path(), "/a/b/c");
+    }
+
+    #[tokio::test]
+    async fn should_return_request_info_with_invalid_rewritten_path() {
+        let remote_addr = SocketAddr::from_str("0.0.0.0:8080").unwrap();
+        let router: Router<hyper::body::Body, Error> = Router::builder()
+            .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+            .rewrite(|path: &str| Some(format!("{} invalid", path)))
+            .build()
+            .unwrap();
+        let service = RequestServiceBuilder::new(router).unwrap().build(remote_addr);
+
+        let req = Request::builder().uri("/old").body(hyper::Body::empty()).unwrap();
+        let (resp, req_info) = service.handle_with_info(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(req_info.uri().path(), "/old");
+    }
 }
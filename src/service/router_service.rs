@@ -3,8 +3,37 @@ use crate::service::request_service::{RequestService, RequestServiceBuilder};
 use hyper::{body::HttpBody, server::conn::AddrStream, service::Service};
 use std::convert::Infallible;
 use std::future::{ready, Ready};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::task::{Context, Poll};
 
+/// The placeholder remote address, `0.0.0.0:0`, which is reported for the connections without a socket address, e.g. the
+/// Unix domain socket connections.
+pub const UNSPECIFIED_REMOTE_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+
+/// A connection which the [`RouterService`](./struct.RouterService.html) can serve, it provides the remote address which
+/// is returned by the [`RequestExt::remote_addr`](./ext/trait.RequestExt.html#tymethod.remote_addr) method.
+///
+/// It's implemented for the TCP connections of the hyper server and for the Unix domain socket connections, which report
+/// the [`UNSPECIFIED_REMOTE_ADDR`](./constant.UNSPECIFIED_REMOTE_ADDR.html) placeholder. Implement it for any other
+/// connection type which is accepted by a custom hyper [`Accept`](https://docs.rs/hyper/0.14.4/hyper/server/accept/trait.Accept.html).
+pub trait RemoteAddr {
+    /// Returns the address of the peer of the connection.
+    fn remote_addr(&self) -> SocketAddr;
+}
+
+impl RemoteAddr for AddrStream {
+    fn remote_addr(&self) -> SocketAddr {
+        AddrStream::remote_addr(self)
+    }
+}
+
+#[cfg(unix)]
+impl RemoteAddr for tokio::net::UnixStream {
+    fn remote_addr(&self) -> SocketAddr {
+        UNSPECIFIED_REMOTE_ADDR
+    }
+}
+
 /// A [`Service`](https://docs.rs/hyper/0.14.4/hyper/service/trait.Service.html) to process incoming requests.
 ///
 /// This `RouterService<B, E>` type accepts two type parameters: `B` and `E`.
@@ -52,6 +81,10 @@ use std::task::{Context, Poll};
 ///    }
 /// }
 /// ```
+///
+/// It serves the Unix domain socket connections too, the remote address of their requests is the
+/// [`UNSPECIFIED_REMOTE_ADDR`](./constant.UNSPECIFIED_REMOTE_ADDR.html) placeholder. Refer to the `unix_socket` example
+/// for how to accept them.
 #[derive(Debug)]
 pub struct RouterService<B, E> {
     builder: RequestServiceBuilder<B, E>,
@@ -66,10 +99,45 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         let builder = RequestServiceBuilder::new(router)?;
         Ok(RouterService { builder })
     }
+
+    /// Creates a new service from an existing [`RequestServiceBuilder`](./struct.RequestServiceBuilder.html).
+    ///
+    /// As the builder is cheap to clone, it allows serving the same router on multiple servers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{RequestServiceBuilder, Router, RouterService};
+    /// use std::convert::Infallible;
+    /// use std::net::SocketAddr;
+    ///
+    /// # async fn run() {
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let builder = RequestServiceBuilder::new(router).unwrap();
+    ///
+    /// let server1 = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 3000)))
+    ///     .serve(RouterService::from_builder(builder.clone()));
+    /// let server2 = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 3001)))
+    ///     .serve(RouterService::from_builder(builder));
+    ///
+    /// let _ = futures::join!(server1, server2);
+    /// # }
+    /// ```
+    pub fn from_builder(builder: RequestServiceBuilder<B, E>) -> RouterService<B, E> {
+        RouterService { builder }
+    }
 }
 
-impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
-    Service<&AddrStream> for RouterService<B, E>
+impl<'a, C, B, E> Service<&'a C> for RouterService<B, E>
+where
+    C: RemoteAddr,
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
 {
     type Response = RequestService<B, E>;
     type Error = Infallible;
@@ -79,7 +147,7 @@ impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Se
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, conn: &AddrStream) -> Self::Future {
+    fn call(&mut self, conn: &'a C) -> Self::Future {
         let req_service = self.builder.build(conn.remote_addr());
 
         ready(Ok(req_service))
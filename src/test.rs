@@ -0,0 +1,77 @@
+//! A lightweight harness to test a router without binding a socket.
+//!
+//! It's available only if the `test-util` feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::Router;
+//! use routerify::test::TestClient;
+//! use hyper::{Response, Body, StatusCode};
+//! use std::convert::Infallible;
+//!
+//! # async fn run() {
+//! let router: Router<Body, Infallible> = Router::builder()
+//!     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+//!     .build()
+//!     .unwrap();
+//!
+//! let client = TestClient::new(router).unwrap();
+//! let resp = client.get("/users/1").await.unwrap();
+//!
+//! assert_eq!(resp.status(), StatusCode::OK);
+//! # }
+//! ```
+
+use crate::{RequestServiceBuilder, Router};
+use hyper::{body::HttpBody, service::Service, Body, Method, Request, Response};
+use std::net::SocketAddr;
+
+/// A client which sends the requests directly to the router, as if they were coming from the `127.0.0.1:0` address.
+#[derive(Debug, Clone)]
+pub struct TestClient<B, E> {
+    builder: RequestServiceBuilder<B, E>,
+    remote_addr: SocketAddr,
+}
+
+impl<B: HttpBody + Send + Sync + 'static, E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static>
+    TestClient<B, E>
+{
+    /// Creates a new client and initializes the router the same way as the [`RouterService`](../struct.RouterService.html)
+    /// does, e.g. the default `404` route and the error handler are added.
+    pub fn new(router: Router<B, E>) -> crate::Result<TestClient<B, E>> {
+        Ok(TestClient {
+            builder: RequestServiceBuilder::new(router)?,
+            remote_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+        })
+    }
+
+    /// Sets the remote address of the requests sent by this client.
+    pub fn remote_addr(mut self, remote_addr: SocketAddr) -> Self {
+        self.remote_addr = remote_addr;
+        self
+    }
+
+    /// Sends the specified request to the router and returns the response.
+    pub async fn request(&self, req: Request<Body>) -> crate::Result<Response<B>> {
+        self.builder.build(self.remote_addr).call(req).await
+    }
+
+    /// Sends a `GET` request with an empty body to the specified path.
+    pub async fn get(&self, path: &str) -> crate::Result<Response<B>> {
+        self.request(Self::gen_request(Method::GET, path, Body::empty())).await
+    }
+
+    /// Sends a `POST` request with the specified body to the specified path.
+    pub async fn post<T: Into<Body>>(&self, path: &str, body: T) -> crate::Result<Response<B>> {
+        self.request(Self::gen_request(Method::POST, path, body.into())).await
+    }
+
+    fn gen_request(method: Method, path: &str, body: Body) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(body)
+            .expect("Couldn't create the test request")
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token which tells whether the client went away before the response to its request was sent.
+///
+/// Hyper drops the future which serves a request when the connection is closed, so a handler is cancelled at its next
+/// `.await` point. The token can be cloned into the work which outlives the handler, e.g. a spawned task, to stop it
+/// once nobody is waiting for the result anymore. It's returned by the
+/// [`RequestExt::disconnect_token`](./ext/trait.RequestExt.html#tymethod.disconnect_token) method.
+#[derive(Debug, Clone, Default)]
+pub struct DisconnectToken {
+    disconnected: Arc<AtomicBool>,
+}
+
+impl DisconnectToken {
+    pub(crate) fn new() -> DisconnectToken {
+        DisconnectToken::default()
+    }
+
+    /// Returns `true` if the request was dropped before its response was completed, which happens when the client
+    /// closes the connection.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn guard(&self) -> DisconnectGuard {
+        DisconnectGuard {
+            token: Some(self.clone()),
+        }
+    }
+}
+
+/// Marks the token as disconnected when it's dropped without being disarmed, i.e. when the request future is dropped
+/// before it resolves.
+pub(crate) struct DisconnectGuard {
+    token: Option<DisconnectToken>,
+}
+
+impl DisconnectGuard {
+    pub(crate) fn disarm(mut self) {
+        self.token = None;
+    }
+}
+
+impl Drop for DisconnectGuard {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.take() {
+            token.disconnected.store(true, Ordering::SeqCst);
+        }
+    }
+}
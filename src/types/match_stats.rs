@@ -0,0 +1,7 @@
+/// Represents the statistics of matching a path against the regexes of a router, it's returned by the
+/// [`Router::match_stats`](./struct.Router.html#method.match_stats) method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchStats {
+    /// The number of the routes, the middlewares and the scoped data maps whose regex matched the path.
+    pub candidates: usize,
+}
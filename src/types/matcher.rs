@@ -0,0 +1,11 @@
+/// The backend which matches the request paths against the routes, it's set by the
+/// [`RouterBuilder::matcher`](./struct.RouterBuilder.html#method.matcher) method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Matcher {
+    /// Matches all the routes by a single `RegexSet`, it's the default backend.
+    #[default]
+    RegexSet,
+    /// Matches the routes with literal paths, i.e. without any parameter or `*` glob, by a path trie, and the rest of the
+    /// routes by a `RegexSet`. It's faster for the large route tables which mostly consist of literal paths.
+    Trie,
+}
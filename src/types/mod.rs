@@ -1,9 +1,31 @@
+pub use disconnect_token::DisconnectToken;
+pub use match_stats::MatchStats;
+pub use matcher::Matcher;
+pub use param_error::ParamError;
+pub use preflight_config::PreflightConfig;
 pub(crate) use request_context::RequestContext;
 pub use request_info::RequestInfo;
 pub(crate) use request_meta::RequestMeta;
+pub use request_timeout_error::RequestTimeoutError;
+pub use response_hints::ResponseHints;
+pub use route_match::RouteMatch;
 pub use route_params::RouteParams;
+pub use skip_post_middleware::{SkipPostMiddleware, SkipPostMiddlewareScopes};
+pub use state::State;
+pub use warning::Warning;
 
+mod disconnect_token;
+mod match_stats;
+mod matcher;
+mod param_error;
+mod preflight_config;
 mod request_context;
 mod request_info;
 mod request_meta;
+mod request_timeout_error;
+mod response_hints;
+mod route_match;
 mod route_params;
+mod skip_post_middleware;
+mod state;
+mod warning;
@@ -0,0 +1,55 @@
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+
+/// The error returned by the [`RouteParams::get_as`](./struct.RouteParams.html#method.get_as) method when a route parameter
+/// is missing or couldn't be parsed into the requested type.
+#[derive(Debug)]
+pub enum ParamError {
+    /// The route parameter with the name doesn't exist.
+    Missing {
+        /// The name of the route parameter.
+        name: String,
+    },
+    /// The value of the route parameter couldn't be parsed.
+    Invalid {
+        /// The name of the route parameter.
+        name: String,
+        /// The raw value of the route parameter.
+        value: String,
+        /// The underlying parse error.
+        source: Box<dyn StdError + Send + Sync + 'static>,
+    },
+}
+
+impl ParamError {
+    /// Returns the name of the route parameter which caused the error.
+    pub fn name(&self) -> &str {
+        match self {
+            ParamError::Missing { name } | ParamError::Invalid { name, .. } => name.as_str(),
+        }
+    }
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Missing { name } => write!(f, "Missing route parameter `{}`", name),
+            ParamError::Invalid { name, value, source } => {
+                write!(
+                    f,
+                    "Invalid route parameter `{}` with value {:?}: {}",
+                    name, value, source
+                )
+            }
+        }
+    }
+}
+
+impl StdError for ParamError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            ParamError::Missing { .. } => None,
+            ParamError::Invalid { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
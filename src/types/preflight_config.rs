@@ -0,0 +1,119 @@
+use hyper::header::{self, HeaderName, HeaderValue};
+use hyper::{HeaderMap, Method};
+
+/// The cross-origin policy of the preflight route added by the
+/// [`RouterBuilder::allow_preflight`](./struct.RouterBuilder.html#method.allow_preflight) method.
+///
+/// Nothing is allowed by default, so the origins, the methods and the headers must be listed explicitly. Only the values
+/// requested by a preflight request which are listed here are echoed in the `Access-Control-Allow-*` headers.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::PreflightConfig;
+/// use hyper::{header, Method};
+///
+/// let config = PreflightConfig::new()
+///     .allow_origin("https://example.com")
+///     .allow_methods(&[Method::PUT, Method::DELETE])
+///     .allow_headers(&[header::CONTENT_TYPE]);
+/// # drop(config);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PreflightConfig {
+    any_origin: bool,
+    origins: Vec<String>,
+    methods: Vec<Method>,
+    headers: Vec<HeaderName>,
+}
+
+impl PreflightConfig {
+    /// Creates a new config which allows nothing.
+    pub fn new() -> PreflightConfig {
+        PreflightConfig::default()
+    }
+
+    /// Allows the specified origin, e.g. `https://example.com`.
+    pub fn allow_origin<O: Into<String>>(mut self, origin: O) -> Self {
+        self.origins.push(origin.into());
+        self
+    }
+
+    /// Allows any origin, the origin of the preflight request is then echoed as it is.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.any_origin = true;
+        self
+    }
+
+    /// Allows the specified methods.
+    pub fn allow_methods(mut self, methods: &[Method]) -> Self {
+        self.methods.extend_from_slice(methods);
+        self
+    }
+
+    /// Allows the specified request headers.
+    pub fn allow_headers(mut self, headers: &[HeaderName]) -> Self {
+        self.headers.extend_from_slice(headers);
+        self
+    }
+
+    // Fills the `Access-Control-Allow-*` headers of the response by the requested values which this config allows. Nothing
+    // is added if the origin or the method is not allowed, so the browser rejects the actual request.
+    pub(crate) fn apply(&self, req_headers: &HeaderMap, res_headers: &mut HeaderMap) {
+        let origin = match req_headers.get(header::ORIGIN) {
+            Some(origin) if self.is_origin_allowed(origin) => origin.clone(),
+            _ => return,
+        };
+
+        let method_allowed = req_headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse::<Method>().ok())
+            .is_some_and(|method| self.methods.contains(&method));
+        if !method_allowed {
+            return;
+        }
+
+        let requested_headers = req_headers
+            .get_all(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        let headers_allowed = requested_headers.iter().all(|name| {
+            self.headers
+                .iter()
+                .any(|allowed| allowed.as_str().eq_ignore_ascii_case(name))
+        });
+        if !headers_allowed {
+            return;
+        }
+
+        res_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+
+        let methods = self.methods.iter().map(|m| m.as_str()).collect::<Vec<_>>().join(", ");
+        res_headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_str(methods.as_str()).expect("Couldn't create the Access-Control-Allow-Methods header"),
+        );
+
+        if !requested_headers.is_empty() {
+            let headers = self.headers.iter().map(|h| h.as_str()).collect::<Vec<_>>().join(", ");
+            res_headers.insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                HeaderValue::from_str(headers.as_str())
+                    .expect("Couldn't create the Access-Control-Allow-Headers header"),
+            );
+        }
+    }
+
+    fn is_origin_allowed(&self, origin: &HeaderValue) -> bool {
+        self.any_origin
+            || self
+                .origins
+                .iter()
+                .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+    }
+}
@@ -27,4 +27,12 @@ impl RequestContext {
     pub(crate) fn get<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         self.inner.lock().unwrap().get::<T>().cloned()
     }
+
+    pub(crate) fn remove<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
+        self.inner.lock().unwrap().remove::<T>()
+    }
+
+    pub(crate) fn contains<T: Send + Sync + Clone + 'static>(&self) -> bool {
+        self.inner.lock().unwrap().contains::<T>()
+    }
 }
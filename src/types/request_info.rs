@@ -1,6 +1,8 @@
-use super::RequestContext;
+use super::{RequestContext, ResponseHints, RouteParams};
 use crate::data_map::SharedDataMap;
-use hyper::{Body, HeaderMap, Method, Request, Uri, Version};
+use hyper::header::{AsHeaderName, HeaderValue};
+use hyper::http::response::Builder;
+use hyper::{Body, HeaderMap, Method, Request, Response, Uri, Version};
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
 
@@ -13,6 +15,8 @@ pub struct RequestInfo {
     pub(crate) req_info_inner: Arc<RequestInfoInner>,
     pub(crate) shared_data_maps: Option<Vec<SharedDataMap>>,
     pub(crate) context: RequestContext,
+    pub(crate) is_error_response: bool,
+    pub(crate) route_params: Option<RouteParams>,
 }
 
 #[derive(Debug)]
@@ -36,6 +40,8 @@ impl RequestInfo {
             req_info_inner: Arc::new(inner),
             shared_data_maps: None,
             context: ctx,
+            is_error_response: false,
+            route_params: None,
         }
     }
 
@@ -44,6 +50,17 @@ impl RequestInfo {
         &self.req_info_inner.headers
     }
 
+    /// Returns the value of the specified request header. If there are multiple values, the first one is returned.
+    pub fn header<K: AsHeaderName>(&self, name: K) -> Option<&HeaderValue> {
+        self.req_info_inner.headers.get(name)
+    }
+
+    /// Returns the value of the specified request header as a string. It returns `None` if the header is missing or its
+    /// value contains non-visible ASCII characters.
+    pub fn header_str<K: AsHeaderName>(&self, name: K) -> Option<&str> {
+        self.header(name).and_then(|val| val.to_str().ok())
+    }
+
     /// Returns the request method type.
     pub fn method(&self) -> &Method {
         &self.req_info_inner.method
@@ -54,11 +71,58 @@ impl RequestInfo {
         &self.req_info_inner.uri
     }
 
+    /// Returns the path of the request uri.
+    pub fn path(&self) -> &str {
+        self.req_info_inner.uri.path()
+    }
+
+    /// Returns the query string of the request uri, it returns `None` if the uri has no query string.
+    pub fn query(&self) -> Option<&str> {
+        self.req_info_inner.uri.query()
+    }
+
     /// Returns the request's HTTP version.
     pub fn version(&self) -> Version {
         self.req_info_inner.version
     }
 
+    /// Returns the route parameters of the matched route. It returns `None` if the request didn't reach any route, e.g.
+    /// when a pre middleware failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, RequestInfo};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .middleware(Middleware::post_with_info(|res, req_info: RequestInfo| async move {
+    ///         if let Some(user_id) = req_info.params().and_then(|params| params.get("userId")) {
+    ///             println!("Accessed user: {}", user_id);
+    ///         }
+    ///
+    ///         Ok(res)
+    ///     }))
+    ///     .get("/users/:userId", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn params(&self) -> Option<&RouteParams> {
+        self.route_params.as_ref()
+    }
+
+    /// Returns `true` if the response was generated by the error handler, i.e. a route handler or a pre middleware failed.
+    ///
+    /// It can be used by the post middlewares to treat the error responses differently, e.g. to skip caching them.
+    pub fn is_error_response(&self) -> bool {
+        self.is_error_response
+    }
+
     /// Access data which was shared by the [`RouterBuilder`](./struct.RouterBuilder.html) method
     /// [`data`](./struct.RouterBuilder.html#method.data).
     ///
@@ -112,6 +176,45 @@ impl RequestInfo {
     pub fn context<T: Send + Sync + Clone + 'static>(&self) -> Option<T> {
         self.context.get::<T>()
     }
+
+    /// Returns the hints which the route handler left for the response, e.g. by the
+    /// [`RequestExt::hint_cache`](./ext/trait.RequestExt.html#tymethod.hint_cache) method. They are empty if no hint is set.
+    pub fn response_hints(&self) -> ResponseHints {
+        self.context.get::<ResponseHints>().unwrap_or_default()
+    }
+
+    /// Returns an empty response builder, it's a starting point for the error handlers and the post middlewares which
+    /// build a response from the request info, e.g. to set a cookie or a header derived from the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RequestInfo, RouteError};
+    /// use hyper::{header, Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// async fn error_handler(err: RouteError, req_info: RequestInfo) -> Response<Body> {
+    ///     let mut builder = req_info.response_builder().status(StatusCode::INTERNAL_SERVER_ERROR);
+    ///
+    ///     if let Some(lang) = req_info.header_str(header::ACCEPT_LANGUAGE) {
+    ///         builder = builder.header(header::SET_COOKIE, format!("lang={}", lang));
+    ///     }
+    ///
+    ///     builder.body(Body::from(err.to_string())).unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .err_handler_with_info(error_handler)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn response_builder(&self) -> Builder {
+        Response::builder()
+    }
 }
 
 impl Debug for RequestInfo {
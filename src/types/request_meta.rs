@@ -1,24 +1,89 @@
 use crate::types::route_params::RouteParams;
+use hyper::Uri;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub(crate) struct RequestMeta {
     route_params: Option<RouteParams>,
+    captures: Option<Vec<String>>,
+    glob_remainder: Option<String>,
+    scope_prefix: Option<String>,
     remote_addr: Option<SocketAddr>,
+    trust_proxy_headers: Option<bool>,
+    original_uri: Option<Uri>,
+    route_tags: Option<Arc<[String]>>,
 }
 
 impl RequestMeta {
-    pub fn with_route_params(route_params: RouteParams) -> RequestMeta {
+    pub fn with_route_params(
+        route_params: RouteParams,
+        captures: Vec<String>,
+        glob_remainder: Option<String>,
+        scope_prefix: Option<String>,
+        route_tags: Arc<[String]>,
+    ) -> RequestMeta {
         RequestMeta {
             route_params: Some(route_params),
+            captures: Some(captures),
+            glob_remainder,
+            scope_prefix,
             remote_addr: None,
+            trust_proxy_headers: None,
+            original_uri: None,
+            route_tags: Some(route_tags),
         }
     }
 
     pub fn with_remote_addr(remote_addr: SocketAddr) -> RequestMeta {
         RequestMeta {
             route_params: None,
+            captures: None,
+            glob_remainder: None,
+            scope_prefix: None,
             remote_addr: Some(remote_addr),
+            trust_proxy_headers: None,
+            original_uri: None,
+            route_tags: None,
+        }
+    }
+
+    pub fn with_trust_proxy_headers(trust_proxy_headers: bool) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            captures: None,
+            glob_remainder: None,
+            scope_prefix: None,
+            remote_addr: None,
+            trust_proxy_headers: Some(trust_proxy_headers),
+            original_uri: None,
+            route_tags: None,
+        }
+    }
+
+    pub fn with_original_uri(original_uri: Uri) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            captures: None,
+            glob_remainder: None,
+            scope_prefix: None,
+            remote_addr: None,
+            trust_proxy_headers: None,
+            original_uri: Some(original_uri),
+            route_tags: None,
+        }
+    }
+
+    pub fn with_route_tags(route_tags: Arc<[String]>) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            captures: None,
+            glob_remainder: None,
+            scope_prefix: None,
+            remote_addr: None,
+            trust_proxy_headers: None,
+            original_uri: None,
+            route_tags: Some(route_tags),
         }
     }
 
@@ -26,15 +91,63 @@ impl RequestMeta {
         self.route_params.as_ref()
     }
 
+    pub fn captures(&self) -> Option<&[String]> {
+        self.captures.as_deref()
+    }
+
+    pub fn glob_remainder(&self) -> Option<&str> {
+        self.glob_remainder.as_deref()
+    }
+
+    pub fn scope_prefix(&self) -> Option<&str> {
+        self.scope_prefix.as_deref()
+    }
+
     pub fn remote_addr(&self) -> Option<&SocketAddr> {
         self.remote_addr.as_ref()
     }
 
+    pub fn trust_proxy_headers(&self) -> bool {
+        self.trust_proxy_headers.unwrap_or(false)
+    }
+
+    pub fn original_uri(&self) -> Option<&Uri> {
+        self.original_uri.as_ref()
+    }
+
+    pub fn route_tags(&self) -> &[String] {
+        self.route_tags.as_deref().unwrap_or_default()
+    }
+
     pub fn extend(&mut self, other_req_meta: RequestMeta) {
         if let Some(other_ra) = other_req_meta.remote_addr {
             self.remote_addr = Some(other_ra)
         }
 
+        if let Some(other_tph) = other_req_meta.trust_proxy_headers {
+            self.trust_proxy_headers = Some(other_tph);
+        }
+
+        if let Some(other_ou) = other_req_meta.original_uri {
+            self.original_uri = Some(other_ou);
+        }
+
+        if let Some(other_rt) = other_req_meta.route_tags {
+            self.route_tags = Some(other_rt);
+        }
+
+        if let Some(other_caps) = other_req_meta.captures {
+            self.captures = Some(other_caps);
+        }
+
+        if let Some(other_gr) = other_req_meta.glob_remainder {
+            self.glob_remainder = Some(other_gr);
+        }
+
+        if let Some(other_sp) = other_req_meta.scope_prefix {
+            self.scope_prefix = Some(other_sp);
+        }
+
         if let Some(other_pm) = other_req_meta.route_params {
             if let Some(ref mut existing_pm) = self.route_params {
                 existing_pm.extend(other_pm);
@@ -0,0 +1,41 @@
+use crate::HasStatusCode;
+use hyper::StatusCode;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// The error passed to the error handler when a request isn't processed within the timeout set by the
+/// [`RouterBuilder::request_timeout`](./struct.RouterBuilder.html#method.request_timeout) method.
+///
+/// It suggests the status set by the [`RouterBuilder::request_timeout_status`](./struct.RouterBuilder.html#method.request_timeout_status)
+/// method, which is `504 Gateway Timeout` by default, and the default error handler responds with it.
+#[derive(Debug, Clone)]
+pub struct RequestTimeoutError {
+    timeout: Duration,
+    status: StatusCode,
+}
+
+impl RequestTimeoutError {
+    pub(crate) fn new(timeout: Duration, status: StatusCode) -> RequestTimeoutError {
+        RequestTimeoutError { timeout, status }
+    }
+
+    /// Returns the timeout which the request exceeded.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl HasStatusCode for RequestTimeoutError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl Display for RequestTimeoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "The request wasn't processed within {:?}", self.timeout)
+    }
+}
+
+impl StdError for RequestTimeoutError {}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+/// The hints which a route handler leaves for the post middlewares about how its response should be treated, e.g. how long
+/// it can be cached.
+///
+/// They are stored in the request context apart from the other context data, set by the `RequestExt::hint_*` methods
+/// and read by the [`RequestInfo::response_hints`](./struct.RequestInfo.html#method.response_hints) method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseHints {
+    cache_for: Option<Duration>,
+}
+
+impl ResponseHints {
+    /// Returns how long the response can be cached, it's set by the
+    /// [`RequestExt::hint_cache`](./ext/trait.RequestExt.html#tymethod.hint_cache) method.
+    pub fn cache_for(&self) -> Option<Duration> {
+        self.cache_for
+    }
+
+    pub(crate) fn set_cache_for(&mut self, duration: Duration) {
+        self.cache_for = Some(duration);
+    }
+}
@@ -0,0 +1,13 @@
+use super::RouteParams;
+
+/// Represents the route which matches a method and a path, it's returned by the
+/// [`Router::match_route`](./struct.Router.html#method.match_route) method.
+#[derive(Debug, Clone)]
+pub struct RouteMatch {
+    /// The index of the matched route in the registration order of the router.
+    pub index: usize,
+    /// The path pattern of the matched route, e.g. `/users/:id`.
+    pub pattern: String,
+    /// The route parameters extracted from the path.
+    pub params: RouteParams,
+}
@@ -1,4 +1,6 @@
+use super::ParamError;
 use std::collections::HashMap;
+use std::str::FromStr;
 
 /// Represents a map of the route parameters using the name of the parameter specified in the path as their respective keys.
 ///
@@ -6,26 +8,52 @@ use std::collections::HashMap;
 ///
 /// **Note:** This type shouldn't be created directly. It will be populated into the `req` object of the route handler and
 /// can be accessed as `req.params()`.
+///
+/// The values are percent decoded, the raw values as they appear in the request path are accessed by the
+/// [`get_raw`](#method.get_raw) method.
 #[derive(Debug, Clone, Default)]
-pub struct RouteParams(HashMap<String, String>);
+pub struct RouteParams {
+    decoded: HashMap<String, Vec<String>>,
+    raw: HashMap<String, Vec<String>>,
+}
 
 impl RouteParams {
     /// Creates an empty route parameters map.
     pub fn new() -> RouteParams {
-        RouteParams(HashMap::new())
+        RouteParams::default()
     }
 
     /// Creates an empty route parameters map with the specified capacity.
     pub fn with_capacity(capacity: usize) -> RouteParams {
-        RouteParams(HashMap::with_capacity(capacity))
+        RouteParams {
+            decoded: HashMap::with_capacity(capacity),
+            raw: HashMap::with_capacity(capacity),
+        }
     }
 
-    /// Sets a new parameter entry with the specified key and the value.
+    /// Sets a new parameter entry with the specified key and the value, replacing all the existing values of the key. The
+    /// value is used as both the decoded and the raw value.
     pub fn set<N: Into<String>, V: Into<String>>(&mut self, param_name: N, param_val: V) {
-        self.0.insert(param_name.into(), param_val.into());
+        let (name, val) = (param_name.into(), param_val.into());
+        self.raw.insert(name.clone(), vec![val.clone()]);
+        self.decoded.insert(name, vec![val]);
+    }
+
+    // Appends a decoded value and its raw value to the key, a key gets multiple values when a parameter name is repeated
+    // in the route path.
+    pub(crate) fn append_with_raw<N: Into<String>, V: Into<String>, R: Into<String>>(
+        &mut self,
+        param_name: N,
+        param_val: V,
+        raw_val: R,
+    ) {
+        let name = param_name.into();
+        self.raw.entry(name.clone()).or_default().push(raw_val.into());
+        self.decoded.entry(name).or_default().push(param_val.into());
     }
 
-    /// Returns the route parameter value mapped with the specified key.
+    /// Returns the route parameter value mapped with the specified key. If the parameter name is repeated in the route path,
+    /// it returns the first value.
     ///
     /// # Examples
     ///
@@ -52,7 +80,150 @@ impl RouteParams {
     /// # run();
     /// ```
     pub fn get<N: Into<String>>(&self, param_name: N) -> Option<&String> {
-        self.0.get(&param_name.into())
+        self.decoded.get(&param_name.into()).and_then(|vals| vals.first())
+    }
+
+    /// Returns the percent decoded route parameter value mapped with the specified key, it's the same as the
+    /// [`get`](#method.get) method and it's meant to make the intent explicit next to the [`get_raw`](#method.get_raw) method.
+    pub fn get_decoded<N: Into<String>>(&self, param_name: N) -> Option<&String> {
+        self.get(param_name)
+    }
+
+    /// Returns the route parameter value mapped with the specified key as it appears in the request path, i.e. without
+    /// percent decoding, e.g. to verify a signature over it. If the parameter name is repeated in the route path, it returns
+    /// the first value.
+    ///
+    /// The raw value is the part of the request path which the decoded value is decoded from, so an encoded slash splits
+    /// the raw values at the same place as the decoded ones. The raw value is the same as the decoded one if the decoded
+    /// path can't be aligned with the raw one, e.g. when the path ends with an encoded slash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/files/:name", |req| async move {
+    ///         // For the `/files/my%20notes` request path.
+    ///         let name = req.params().get_decoded("name").unwrap(); // "my notes"
+    ///         let raw_name = req.params().get_raw("name").unwrap(); // "my%20notes"
+    ///
+    ///         Ok(Response::new(Body::from(format!("{} ({})", name, raw_name))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_raw<N: Into<String>>(&self, param_name: N) -> Option<&String> {
+        self.raw.get(&param_name.into()).and_then(|vals| vals.first())
+    }
+
+    /// Parses the route parameter value mapped with the specified key into the specified type. The returned
+    /// [`ParamError`](./enum.ParamError.html) carries the parameter name and the underlying parse error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteParams};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    ///
+    /// # fn run() -> Router<Body, routerify::ParamError> {
+    /// let router = Router::builder()
+    ///     .get("/users/:id", |req| async move {
+    ///         let id: u64 = req.params().get_as("id")?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("User ID: {}", id))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_as<T, N>(&self, param_name: N) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+        N: Into<String>,
+    {
+        let name = param_name.into();
+
+        match self.get(name.as_str()) {
+            Some(value) => value.parse::<T>().map_err(|e| ParamError::Invalid {
+                name,
+                value: value.clone(),
+                source: e.into(),
+            }),
+            None => Err(ParamError::Missing { name }),
+        }
+    }
+
+    /// Returns the route parameter value mapped with the specified key, or the specified default value if it's absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Request, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// async fn docs_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     let lang = req.params().get_or("lang", "en");
+    ///
+    ///     Ok(Response::new(Body::from(format!("Docs in {}", lang))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .get("/docs", docs_handler)
+    ///      .get("/docs/:lang", docs_handler)
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_or<'a, N: Into<String>>(&'a self, param_name: N, default: &'a str) -> &'a str {
+        self.get(param_name).map(String::as_str).unwrap_or(default)
+    }
+
+    /// Returns all the values mapped with the specified key in the order they appear in the route path, e.g. the values of
+    /// the repeated `*` segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/compare/:version/:version", |req| async move {
+    ///         let versions = req.params().get_all("version");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Comparing {} with {}", versions[0], versions[1]))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_all<N: Into<String>>(&self, param_name: N) -> Vec<&String> {
+        self.decoded
+            .get(&param_name.into())
+            .map(|vals| vals.iter().collect())
+            .unwrap_or_default()
     }
 
     /// Checks if a route parameter exists.
@@ -83,33 +254,77 @@ impl RouteParams {
     /// # run();
     /// ```
     pub fn has<N: Into<String>>(&self, param_name: N) -> bool {
-        self.0.contains_key(&param_name.into())
+        self.decoded.contains_key(&param_name.into())
     }
 
     /// Returns the length of the route parameters.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.decoded.len()
     }
 
+    /// Returns `true` if there is no route parameter.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.decoded.is_empty()
     }
 
     /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter names.
     pub fn params_names(&self) -> impl Iterator<Item = &String> {
-        self.0.keys()
+        self.decoded.keys()
     }
 
     /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter entries
-    /// as `(parameter_name: &String, parameter_value:  &String)`.
+    /// as `(parameter_name: &String, parameter_value:  &String)`. Only the first value of a repeated parameter is yielded.
     pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
-        self.0.iter()
+        self.decoded
+            .iter()
+            .filter_map(|(key, vals)| vals.first().map(|val| (key, val)))
     }
 
     /// Extends the current parameters map with other one.
     pub fn extend(&mut self, other_route_params: RouteParams) {
-        other_route_params.0.into_iter().for_each(|(key, val)| {
-            self.set(key, val);
-        })
+        self.decoded.extend(other_route_params.decoded);
+        self.raw.extend(other_route_params.raw);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::ParseIntError;
+
+    #[test]
+    fn test_get_as() {
+        let mut params = RouteParams::new();
+        params.set("id", "42");
+        params.set("page", "first");
+
+        assert_eq!(params.get_as::<u64, _>("id").unwrap(), 42);
+
+        let err = params.get_as::<u64, _>("missing").unwrap_err();
+        assert!(matches!(err, ParamError::Missing { .. }));
+        assert_eq!(err.name(), "missing");
+
+        let err = params.get_as::<u64, _>("page").unwrap_err();
+        assert!(matches!(err, ParamError::Invalid { ref value, .. } if value == "first"));
+        assert_eq!(err.name(), "page");
+        assert!(std::error::Error::source(&err).unwrap().is::<ParseIntError>());
+        assert_eq!(
+            err.to_string(),
+            "Invalid route parameter `page` with value \"first\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_is_empty_and_get_or() {
+        let mut params = RouteParams::new();
+        assert!(params.is_empty());
+        assert_eq!(params.len(), 0);
+        assert_eq!(params.get_or("lang", "en"), "en");
+
+        params.set("lang", "fr");
+        assert!(!params.is_empty());
+        assert_eq!(params.len(), 1);
+        assert_eq!(params.get_or("lang", "en"), "fr");
+        assert_eq!(params.get_or("page", "1"), "1");
     }
 }
@@ -0,0 +1,89 @@
+/// A marker which makes the router skip all the post middlewares for a response.
+///
+/// Insert it into the response extensions in a route handler when the response must be sent as is, e.g. for a raw
+/// upgraded connection in a proxy.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, SkipPostMiddleware};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/raw", |_| async move {
+///         let mut resp = Response::new(Body::from("raw"));
+///         resp.extensions_mut().insert(SkipPostMiddleware);
+///         Ok(resp)
+///     })
+///     // This post middleware won't be executed for the "/raw" route.
+///     .middleware(routerify::Middleware::post(|res| async move { Ok(res) }))
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipPostMiddleware;
+
+/// A marker which makes the router skip the post middlewares of the specified scopes for a response, e.g. a file download
+/// handler can skip a JSON-wrapping post middleware of the root router while the others are still executed.
+///
+/// The scopes are identified by their depths: the middlewares of the root router have the depth `1`, the middlewares of a
+/// router mounted into the root router have the depth `2` and so on. Insert it into the response extensions in a route
+/// handler.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Middleware, Router, SkipPostMiddlewareScopes};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let files: Router<Body, Infallible> = Router::builder()
+///     .get("/download", |_| async move {
+///         let mut resp = Response::new(Body::from("raw bytes"));
+///         // Skip the post middlewares of the root router only.
+///         resp.extensions_mut().insert(SkipPostMiddlewareScopes::new([1]));
+///         Ok(resp)
+///     })
+///     .middleware(Middleware::post(|res| async move { /* It's still executed */ Ok(res) }))
+///     .build()
+///     .unwrap();
+///
+/// let router = Router::builder()
+///     .middleware(Middleware::post(|res| async move { /* Wrap the body as JSON */ Ok(res) }))
+///     .scope("/files", files)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SkipPostMiddlewareScopes {
+    depths: Vec<u32>,
+}
+
+impl SkipPostMiddlewareScopes {
+    /// Creates a marker which skips the post middlewares of the scopes with the specified depths.
+    pub fn new<I: IntoIterator<Item = u32>>(depths: I) -> Self {
+        SkipPostMiddlewareScopes {
+            depths: depths.into_iter().collect(),
+        }
+    }
+
+    /// Adds the scope with the specified depth to the skipped scopes.
+    pub fn skip(mut self, depth: u32) -> Self {
+        self.depths.push(depth);
+        self
+    }
+
+    /// Checks if the post middlewares of the scope with the specified depth are skipped.
+    pub fn is_skipped(&self, depth: u32) -> bool {
+        self.depths.contains(&depth)
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A typed app state which is passed to the handlers registered by the
+/// [`RouterBuilder::get_with_state`](./struct.RouterBuilder.html#method.get_with_state) and the
+/// [`RouterBuilder::add_with_state`](./struct.RouterBuilder.html#method.add_with_state) methods.
+///
+/// The state is set by the [`RouterBuilder::with_state`](./struct.RouterBuilder.html#method.with_state) method and it's
+/// shared between the requests, so it's cheap to clone. It dereferences to the inner state.
+pub struct State<S>(Arc<S>);
+
+impl<S> State<S> {
+    pub(crate) fn new(state: S) -> State<S> {
+        State(Arc::new(state))
+    }
+
+    /// Returns the shared inner state.
+    pub fn into_inner(self) -> Arc<S> {
+        self.0
+    }
+}
+
+impl<S> Clone for State<S> {
+    fn clone(&self) -> Self {
+        State(self.0.clone())
+    }
+}
+
+impl<S> Deref for State<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: Debug> Debug for State<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&self.0).finish()
+    }
+}
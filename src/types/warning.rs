@@ -0,0 +1,39 @@
+use std::fmt::{self, Display, Formatter};
+
+/// A warning which is reported while a router is prepared to serve requests, it's returned by the
+/// [`RequestServiceBuilder::new_with_warnings`](./struct.RequestServiceBuilder.html#method.new_with_warnings) method.
+///
+/// The defaults of the root router are only provided out of the box if the response body type is `hyper::Body`, so a router
+/// with another body type gets these warnings unless it sets them itself or sets a
+/// [`default_response_factory`](./struct.RouterBuilder.html#method.default_response_factory).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// No global `OPTIONS` route could be added.
+    NoOptionsRoute,
+    /// No default 404 route could be added.
+    No404Route,
+    /// No default error handler could be added.
+    NoErrHandler,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::NoOptionsRoute => write!(
+                f,
+                "No global `options method` route added. It is recommended to send response to any `options` request.\n\
+                Please add one by calling `.options(\"/*\", handler)` method of the root router builder."
+            ),
+            Warning::No404Route => write!(
+                f,
+                "No default 404 route added. It is recommended to send 404 response to any non-existent route.\n\
+                Please add one by calling `.any(handler)` method of the root router builder."
+            ),
+            Warning::NoErrHandler => write!(
+                f,
+                "No error handler added. It is recommended to add one to see what went wrong if any route or middleware fails.\n\
+                Please add one by calling `.err_handler(handler)` method of the root router builder."
+            ),
+        }
+    }
+}
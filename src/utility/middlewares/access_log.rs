@@ -0,0 +1,150 @@
+use crate::ext::RequestExt;
+use crate::types::RequestInfo;
+use crate::Middleware;
+use hyper::{body::HttpBody, Request, Response};
+use log::Level;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+const DEFAULT_FORMAT: &str = "{remote_addr} \"{method} {path}\" {status} {duration}";
+
+/// The configuration for the [`access_log`](./fn.access_log.html) middleware.
+///
+/// The format supports the `{method}`, `{path}`, `{status}`, `{duration}` and `{remote_addr}` placeholders. The default
+/// format is `{remote_addr} "{method} {path}" {status} {duration}`.
+#[derive(Debug, Clone)]
+pub struct AccessLogConfig {
+    format: String,
+    level: Level,
+    target: String,
+}
+
+impl AccessLogConfig {
+    /// Creates a new config which emits the access logs in the default format at the `Info` level.
+    pub fn new() -> AccessLogConfig {
+        AccessLogConfig::default()
+    }
+
+    /// Sets the format of the log lines.
+    pub fn format<F: Into<String>>(mut self, format: F) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the level of the log lines.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the target of the log lines, it's `routerify::access_log` by default.
+    pub fn target<T: Into<String>>(mut self, target: T) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    // Renders the line in a single pass over the format, so the placeholders within the substituted values, e.g. in the
+    // request path, are kept as they are.
+    fn render(&self, req_info: &RequestInfo, status: u16, duration: Duration, remote_addr: SocketAddr) -> String {
+        let mut line = String::with_capacity(self.format.len());
+        let mut rest = self.format.as_str();
+
+        while let Some(start) = rest.find('{') {
+            line.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let placeholder = rest.find('}').map(|end| &rest[..=end]);
+            let value = match placeholder {
+                Some("{method}") => req_info.method().to_string(),
+                Some("{path}") => req_info.uri().path().to_owned(),
+                Some("{status}") => status.to_string(),
+                Some("{duration}") => format!("{:?}", duration),
+                Some("{remote_addr}") => remote_addr.to_string(),
+                _ => {
+                    line.push('{');
+                    rest = &rest[1..];
+                    continue;
+                }
+            };
+
+            line.push_str(value.as_str());
+            rest = &rest[placeholder.map_or(0, str::len)..];
+        }
+
+        line.push_str(rest);
+        line
+    }
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> AccessLogConfig {
+        AccessLogConfig {
+            format: DEFAULT_FORMAT.to_owned(),
+            level: Level::Info,
+            target: "routerify::access_log".to_owned(),
+        }
+    }
+}
+
+// The request state which is stored in the request context by the pre middleware.
+#[derive(Debug, Clone, Copy)]
+struct AccessLogStart {
+    started: Instant,
+    remote_addr: SocketAddr,
+}
+
+/// Creates a pair of middlewares which emit an access log line for every request via the [`log`](https://docs.rs/log) facade.
+///
+/// The pre middleware records the start time and the remote address of the request and the post middleware emits the
+/// line with the response status. Register them with the
+/// [`RouterBuilder::middlewares`](../../struct.RouterBuilder.html#method.middlewares) method.
+///
+/// It's available only if the `log` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::utility::middlewares::{access_log, AccessLogConfig};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middlewares(access_log(AccessLogConfig::new().format("{method} {path} -> {status}")))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn access_log<B, E>(config: AccessLogConfig) -> Vec<Middleware<B, E>>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let pre = Middleware::pre(|req: Request<hyper::Body>| async move {
+        req.set_context(AccessLogStart {
+            started: Instant::now(),
+            remote_addr: req.remote_addr(),
+        });
+        Ok(req)
+    });
+
+    let post = Middleware::post_with_info(move |res: Response<B>, req_info: RequestInfo| {
+        if let Some(start) = req_info.context::<AccessLogStart>() {
+            let line = config.render(
+                &req_info,
+                res.status().as_u16(),
+                start.started.elapsed(),
+                start.remote_addr,
+            );
+            log::log!(target: config.target.as_str(), config.level, "{}", line);
+        }
+
+        async move { Ok(res) }
+    });
+
+    vec![pre, post]
+}
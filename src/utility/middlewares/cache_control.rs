@@ -0,0 +1,51 @@
+use crate::types::RequestInfo;
+use crate::Middleware;
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, Response};
+
+/// Creates a post middleware which sets the `Cache-Control` header from the cache hint of the route handler, which is set
+/// by the [`RequestExt::hint_cache`](../../ext/trait.RequestExt.html#tymethod.hint_cache) method.
+///
+/// The header is set to `max-age=<seconds>` only for the successful responses which don't have a `Cache-Control` header
+/// already.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use routerify::utility::middlewares::cache_control;
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(cache_control())
+///     .get("/catalog", |req| async move {
+///         req.hint_cache(Duration::from_secs(60));
+///         Ok(Response::new(Body::from("Catalog")))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn cache_control<B, E>() -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    Middleware::post_with_info(|mut res: Response<B>, req_info: RequestInfo| {
+        if let Some(cache_for) = req_info.response_hints().cache_for() {
+            if res.status().is_success() && !res.headers().contains_key(header::CACHE_CONTROL) {
+                let value = HeaderValue::from_str(format!("max-age={}", cache_for.as_secs()).as_str())
+                    .expect("Couldn't create the Cache-Control header");
+                res.headers_mut().insert(header::CACHE_CONTROL, value);
+            }
+        }
+
+        async move { Ok(res) }
+    })
+}
@@ -0,0 +1,19 @@
+//! Ready-made middlewares, e.g. access logging, caching, sessions and `Vary` header management.
+//!
+//! Some of the middlewares consist of a pre and a post middleware, so they are returned as a list which can be registered
+//! by the [`RouterBuilder::middlewares`](../../struct.RouterBuilder.html#method.middlewares) method.
+
+#[cfg(feature = "log")]
+pub use self::access_log::{access_log, AccessLogConfig};
+pub use self::cache_control::cache_control;
+#[cfg(feature = "session")]
+pub use self::session::{session, MemoryStore, Session, SessionConfig, SessionData, SessionStore};
+pub(crate) use self::vary::append_vary;
+pub use self::vary::vary;
+
+#[cfg(feature = "log")]
+mod access_log;
+mod cache_control;
+#[cfg(feature = "session")]
+mod session;
+mod vary;
@@ -0,0 +1,354 @@
+use crate::ext::RequestExt;
+use crate::types::RequestInfo;
+use crate::Middleware;
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, HeaderMap, Request, Response};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The data of a session, it maps the keys to the values.
+pub type SessionData = HashMap<String, String>;
+
+/// A storage of the session data which is used by the [`session`](./fn.session.html) middleware.
+pub trait SessionStore: Send + Sync + 'static {
+    /// Loads the data of the session with the specified id, it returns `None` if the session doesn't exist.
+    fn load(&self, id: &str) -> Option<SessionData>;
+
+    /// Stores the data of the session with the specified id.
+    fn store(&self, id: &str, data: SessionData);
+
+    /// Removes the session with the specified id.
+    fn remove(&self, id: &str);
+}
+
+/// A session store which keeps the sessions in memory. It's bounded, so the oldest session is evicted when a new session
+/// is stored while the store is full.
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    inner: Mutex<MemoryStoreInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStoreInner {
+    sessions: HashMap<String, SessionData>,
+    // The session ids in the order they were created, so the oldest one can be evicted.
+    order: VecDeque<String>,
+}
+
+impl MemoryStore {
+    /// Creates a store which keeps at most the specified number of sessions.
+    pub fn new(capacity: usize) -> MemoryStore {
+        MemoryStore {
+            capacity,
+            inner: Mutex::new(MemoryStoreInner::default()),
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> MemoryStore {
+        MemoryStore::new(10_000)
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load(&self, id: &str) -> Option<SessionData> {
+        self.inner.lock().unwrap().sessions.get(id).cloned()
+    }
+
+    fn store(&self, id: &str, data: SessionData) {
+        let mut inner = self.inner.lock().unwrap();
+
+        if !inner.sessions.contains_key(id) {
+            while inner.sessions.len() >= self.capacity.max(1) {
+                match inner.order.pop_front() {
+                    Some(oldest_id) => inner.sessions.remove(&oldest_id),
+                    None => break,
+                };
+            }
+            inner.order.push_back(id.to_owned());
+        }
+
+        inner.sessions.insert(id.to_owned(), data);
+    }
+
+    fn remove(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.sessions.remove(id).is_some() {
+            inner.order.retain(|other_id| other_id != id);
+        }
+    }
+}
+
+/// The configuration for the [`session`](./fn.session.html) middleware.
+///
+/// By default, the session id is kept in the `sid` cookie at the `/` path without an expiry and the sessions are kept by
+/// a [`MemoryStore`](./struct.MemoryStore.html) with the default capacity.
+#[derive(Clone)]
+pub struct SessionConfig {
+    cookie_name: String,
+    cookie_path: String,
+    max_age: Option<Duration>,
+    secure: bool,
+    store: Arc<dyn SessionStore>,
+}
+
+impl SessionConfig {
+    /// Creates a new config with the default settings.
+    pub fn new() -> SessionConfig {
+        SessionConfig::default()
+    }
+
+    /// Sets the name of the session cookie.
+    pub fn cookie_name<N: Into<String>>(mut self, name: N) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    /// Sets the path of the session cookie.
+    pub fn cookie_path<P: Into<String>>(mut self, path: P) -> Self {
+        self.cookie_path = path.into();
+        self
+    }
+
+    /// Sets the `Max-Age` of the session cookie, it's refreshed on every response of the session.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets whether the session cookie is only sent over HTTPS.
+    pub fn secure(mut self, enable: bool) -> Self {
+        self.secure = enable;
+        self
+    }
+
+    /// Sets the store of the session data.
+    pub fn store<S: SessionStore>(mut self, store: S) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    fn session_id(&self, headers: &HeaderMap) -> Option<String> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == self.cookie_name)
+            .map(|(_, id)| id.to_owned())
+    }
+
+    fn cookie(&self, id: &str, max_age: Option<Duration>) -> String {
+        let mut cookie = format!(
+            "{}={}; Path={}; HttpOnly; SameSite=Lax",
+            self.cookie_name, id, self.cookie_path
+        );
+        if let Some(max_age) = max_age {
+            cookie.push_str(format!("; Max-Age={}", max_age.as_secs()).as_str());
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> SessionConfig {
+        SessionConfig {
+            cookie_name: "sid".to_owned(),
+            cookie_path: "/".to_owned(),
+            max_age: None,
+            secure: false,
+            store: Arc::new(MemoryStore::default()),
+        }
+    }
+}
+
+impl Debug for SessionConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("cookie_name", &self.cookie_name)
+            .field("cookie_path", &self.cookie_path)
+            .field("max_age", &self.max_age)
+            .field("secure", &self.secure)
+            .finish()
+    }
+}
+
+/// The session of a request, it's put into the request context by the [`session`](./fn.session.html) middleware.
+///
+/// It's cheap to clone and the clones share the same data, so the changes made by a route handler are written back by the
+/// post middleware.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    inner: Arc<Mutex<SessionInner>>,
+}
+
+#[derive(Debug, Default)]
+struct SessionInner {
+    id: Option<String>,
+    data: SessionData,
+    changed: bool,
+    regenerated: bool,
+    destroyed: bool,
+}
+
+impl Session {
+    fn new(id: Option<String>, data: SessionData) -> Session {
+        Session {
+            inner: Arc::new(Mutex::new(SessionInner {
+                id,
+                data,
+                changed: false,
+                regenerated: false,
+                destroyed: false,
+            })),
+        }
+    }
+
+    /// Returns the session id, it's `None` for a new session until its data is stored.
+    pub fn id(&self) -> Option<String> {
+        self.inner.lock().unwrap().id.clone()
+    }
+
+    /// Returns the value of the specified key.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.inner.lock().unwrap().data.get(key).cloned()
+    }
+
+    /// Sets the value of the specified key.
+    pub fn insert<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.data.insert(key.into(), value.into());
+        inner.changed = true;
+    }
+
+    /// Removes the specified key and returns its value.
+    pub fn remove(&self, key: &str) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let value = inner.data.remove(key);
+        inner.changed |= value.is_some();
+        value
+    }
+
+    /// Moves the session data to a new session id and removes the old one from the store, e.g. after a login to prevent
+    /// session fixation.
+    pub fn regenerate(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.regenerated = true;
+        inner.changed = true;
+    }
+
+    /// Destroys the session, so its data is removed from the store and the session cookie is expired.
+    pub fn destroy(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.data.clear();
+        inner.destroyed = true;
+    }
+}
+
+// Generates a session id from 128 bits of the operating system's random number generator.
+fn gen_session_id() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("Couldn't generate a random session id");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Creates a pair of middlewares which maintain a cookie based session for every request.
+///
+/// The pre middleware reads the session id from the session cookie, loads the session data from the
+/// [`SessionStore`](./trait.SessionStore.html) and puts a [`Session`](./struct.Session.html) into the request context. The
+/// post middleware writes the changed data back to the store and sets or refreshes the session cookie. A new session is
+/// only stored once its data is changed. Register them with the
+/// [`RouterBuilder::middlewares`](../../struct.RouterBuilder.html#method.middlewares) method.
+///
+/// It's available only if the `session` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use routerify::utility::middlewares::{session, Session, SessionConfig};
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middlewares(session(SessionConfig::new().max_age(Duration::from_secs(3600))))
+///     .get("/", |req| async move {
+///         let session = req.context::<Session>().unwrap();
+///         let visits = session.get("visits").and_then(|v| v.parse::<u32>().ok()).unwrap_or(0) + 1;
+///         session.insert("visits", visits.to_string());
+///
+///         Ok(Response::new(Body::from(format!("Visits: {}", visits))))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn session<B, E>(config: SessionConfig) -> Vec<Middleware<B, E>>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let pre_config = config.clone();
+    let pre = Middleware::pre(move |req: Request<hyper::Body>| {
+        let session = match pre_config.session_id(req.headers()) {
+            Some(id) => match pre_config.store.load(id.as_str()) {
+                Some(data) => Session::new(Some(id), data),
+                // The session is unknown, e.g. it's expired or evicted, so start a new one.
+                None => Session::default(),
+            },
+            None => Session::default(),
+        };
+        req.set_context(session);
+
+        async move { Ok(req) }
+    });
+
+    let post = Middleware::post_with_info(move |mut res: Response<B>, req_info: RequestInfo| {
+        let session = req_info.context::<Session>();
+
+        if let Some(session) = session {
+            let mut inner = session.inner.lock().unwrap();
+
+            let cookie = if inner.destroyed {
+                if let Some(id) = inner.id.take() {
+                    config.store.remove(id.as_str());
+                }
+                Some(config.cookie("", Some(Duration::from_secs(0))))
+            } else {
+                if inner.regenerated {
+                    if let Some(id) = inner.id.take() {
+                        config.store.remove(id.as_str());
+                    }
+                    inner.regenerated = false;
+                }
+                if inner.changed {
+                    let id = inner.id.get_or_insert_with(gen_session_id).clone();
+                    config.store.store(id.as_str(), inner.data.clone());
+                    inner.changed = false;
+                }
+                inner.id.as_ref().map(|id| config.cookie(id, config.max_age))
+            };
+
+            if let Some(cookie) = cookie.and_then(|cookie| HeaderValue::from_str(cookie.as_str()).ok()) {
+                res.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+        }
+
+        async move { Ok(res) }
+    });
+
+    vec![pre, post]
+}
@@ -0,0 +1,72 @@
+use crate::Middleware;
+use hyper::header::{self, HeaderName, HeaderValue};
+use hyper::{body::HttpBody, HeaderMap, Response};
+
+/// Creates a post middleware which appends the specified header names to the `Vary` header of every response, e.g. when
+/// the responses differ by the `Accept` or the `Accept-Encoding` header.
+///
+/// The names already listed in the `Vary` header are not repeated and a `Vary: *` header is left as it is.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::utility::middlewares::vary;
+/// use hyper::{header, Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(vary(&[header::ACCEPT, header::ACCEPT_ENCODING]))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn vary<B, E>(header_names: &[HeaderName]) -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let header_names = header_names.to_vec();
+
+    Middleware::post(move |mut res: Response<B>| {
+        append_vary(res.headers_mut(), header_names.as_slice());
+        async move { Ok(res) }
+    })
+}
+
+// Merges the header names into the `Vary` header, it's shared by the middlewares whose responses depend on the request
+// headers.
+pub(crate) fn append_vary(headers: &mut HeaderMap, header_names: &[HeaderName]) {
+    let mut values = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    if values.iter().any(|name| name == "*") {
+        return;
+    }
+
+    let mut changed = false;
+    for header_name in header_names {
+        if !values
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(header_name.as_str()))
+        {
+            values.push(header_name.as_str().to_owned());
+            changed = true;
+        }
+    }
+
+    if changed {
+        let value = HeaderValue::from_str(values.join(", ").as_str()).expect("Couldn't create the Vary header");
+        headers.insert(header::VARY, value);
+    }
+}
@@ -0,0 +1,4 @@
+//! Ready-made utilities which are commonly needed by the apps built on top of the router.
+
+pub mod middlewares;
+pub mod response;
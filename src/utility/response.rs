@@ -0,0 +1,363 @@
+//! Helpers to convert the handler results into responses.
+
+use hyper::header::{self, HeaderValue, IntoHeaderName};
+use hyper::{Body, Response, StatusCode};
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+/// Converts a value into a `Response<Body>`, so the route handlers can return the domain types.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::utility::response::IntoResponse;
+/// use hyper::{Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/", |_| async move { Ok((StatusCode::ACCEPTED, "Queued").into_response()) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub trait IntoResponse {
+    /// Converts the value into a response.
+    fn into_response(self) -> Response<Body>;
+}
+
+impl IntoResponse for Response<Body> {
+    fn into_response(self) -> Response<Body> {
+        self
+    }
+}
+
+impl IntoResponse for StatusCode {
+    fn into_response(self) -> Response<Body> {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = self;
+        res
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response<Body> {
+        (StatusCode::OK, self).into_response()
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self) -> Response<Body> {
+        (StatusCode::OK, self).into_response()
+    }
+}
+
+impl<T: Into<Body>> IntoResponse for (StatusCode, T) {
+    fn into_response(self) -> Response<Body> {
+        Response::builder()
+            .status(self.0)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.1.into())
+            .expect("Couldn't create the response")
+    }
+}
+
+/// Creates a response with the specified status and its canonical reason as a plain text body, e.g. to reject a request
+/// with `401 Unauthorized`, `403 Forbidden` or `429 Too Many Requests` from a handler or a post middleware. It's
+/// re-exported at the crate root.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{reject, Router};
+/// use hyper::{header, Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/admin", |req| async move {
+///         if !req.headers().contains_key(header::AUTHORIZATION) {
+///             return Ok(reject(StatusCode::UNAUTHORIZED));
+///         }
+///         Ok(hyper::Response::new(Body::from("Admin page")))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn reject(status: StatusCode) -> Response<Body> {
+    reject_with(status, status.canonical_reason().unwrap_or_default())
+}
+
+/// Like the [`reject`](./fn.reject.html) function, but with the specified plain text body. It's re-exported at the
+/// crate root.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{reject_with, Router};
+/// use hyper::{Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/search", |_| async move { Ok(reject_with(StatusCode::TOO_MANY_REQUESTS, "Slow down")) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn reject_with<T: Into<Body>>(status: StatusCode, body: T) -> Response<Body> {
+    (status, body).into_response()
+}
+
+/// Creates a `304 Not Modified` response without a body, e.g. to answer a conditional request whose `If-None-Match`
+/// header matches the current `ETag` of the resource. It's re-exported at the crate root.
+///
+/// Set the `ETag` and the `Cache-Control` headers on it like on the full response, they're kept. The router removes the
+/// `Content-Type` header from the response after the post middlewares, so a post middleware setting it for every response
+/// doesn't describe the missing body. Other `304` responses are left as they are.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{not_modified, Router};
+/// use hyper::{header, Body, Response};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/report", |req| async move {
+///         let etag = "\"v1\"";
+///         let mut res = if req.headers().get(header::IF_NONE_MATCH).is_some_and(|val| val == etag) {
+///             not_modified()
+///         } else {
+///             Response::new(Body::from("Monthly report"))
+///         };
+///         res.headers_mut().insert(header::ETAG, header::HeaderValue::from_static(etag));
+///         Ok(res)
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn not_modified() -> Response<Body> {
+    let mut res = StatusCode::NOT_MODIFIED.into_response();
+    res.extensions_mut().insert(NotModified);
+    res
+}
+
+// Marks the responses created by the `not_modified` function, the router drops their `Content-Type` header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NotModified;
+
+/// Buffers the body of the response, sets the specified header to the signature computed by the `sign` function over
+/// the body bytes and re-attaches the same bytes as the body, so a post middleware can sign a response without consuming
+/// its body twice.
+///
+/// The signing algorithm and the key are up to the caller, e.g. an HMAC from the `hmac` crate over the body and the
+/// request path read from the [request info](../../struct.RequestInfo.html).
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Middleware, Router};
+/// use routerify::utility::response::with_signature;
+/// use hyper::header::HeaderValue;
+/// use hyper::{Body, Response};
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// // Stands in for a real MAC, e.g. HMAC-SHA256.
+/// fn sign(key: &[u8], path: &str, body: &[u8]) -> HeaderValue {
+///     let mut hasher = DefaultHasher::new();
+///     hasher.write(key);
+///     hasher.write(path.as_bytes());
+///     hasher.write(body);
+///     HeaderValue::from(hasher.finish())
+/// }
+///
+/// # fn run() -> Router<Body, hyper::Error> {
+/// let router = Router::builder()
+///     .get("/report", |_| async move { Ok(Response::new(Body::from("Report"))) })
+///     .middleware(Middleware::post_with_info(|res, req_info| async move {
+///         with_signature(res, "x-signature", |body| sign(b"secret", req_info.uri().path(), body)).await
+///     }))
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub async fn with_signature<K, F>(res: Response<Body>, header: K, sign: F) -> Result<Response<Body>, hyper::Error>
+where
+    K: IntoHeaderName,
+    F: FnOnce(&[u8]) -> HeaderValue,
+{
+    let (mut parts, body) = res.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+
+    parts.headers.insert(header, sign(&body));
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// The common results of an API handler, each of them is converted into a response with the matching status.
+///
+/// The data is serialized as a JSON body and the error variants have a `{"error": "<message>"}` JSON body, where the
+/// message is the canonical reason of the status if not specified. It's available only if the `json` feature is
+/// enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::utility::response::{ApiResult, IntoResponse};
+/// use hyper::Body;
+/// use serde::Serialize;
+/// # use std::convert::Infallible;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .post("/users", |_| async move {
+///         Ok(ApiResult::Created(User { name: "alice".to_owned() }).into_response())
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub enum ApiResult<T> {
+    /// `200 OK` with the data.
+    Ok(T),
+    /// `201 Created` with the created data.
+    Created(T),
+    /// `202 Accepted` with the data.
+    Accepted(T),
+    /// `204 No Content` with an empty body.
+    NoContent,
+    /// `400 Bad Request` with the error message.
+    BadRequest(Option<String>),
+    /// `401 Unauthorized`.
+    Unauthorized,
+    /// `403 Forbidden`.
+    Forbidden,
+    /// `404 Not Found`.
+    NotFound,
+    /// `409 Conflict` with the error message.
+    Conflict(Option<String>),
+    /// `500 Internal Server Error` with the error message.
+    InternalError(Option<String>),
+}
+
+#[cfg(feature = "json")]
+impl<T> ApiResult<T> {
+    /// Returns the status of the response for this result.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiResult::Ok(_) => StatusCode::OK,
+            ApiResult::Created(_) => StatusCode::CREATED,
+            ApiResult::Accepted(_) => StatusCode::ACCEPTED,
+            ApiResult::NoContent => StatusCode::NO_CONTENT,
+            ApiResult::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiResult::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiResult::Forbidden => StatusCode::FORBIDDEN,
+            ApiResult::NotFound => StatusCode::NOT_FOUND,
+            ApiResult::Conflict(_) => StatusCode::CONFLICT,
+            ApiResult::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: Serialize> IntoResponse for ApiResult<T> {
+    fn into_response(self) -> Response<Body> {
+        let status = self.status();
+
+        let body = match self {
+            ApiResult::Ok(data) | ApiResult::Created(data) | ApiResult::Accepted(data) => serde_json::to_vec(&data),
+            ApiResult::NoContent => return status.into_response(),
+            ApiResult::BadRequest(msg) | ApiResult::Conflict(msg) | ApiResult::InternalError(msg) => {
+                serde_json::to_vec(&serde_json::json!({
+                    "error": msg.unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_owned())
+                }))
+            }
+            ApiResult::Unauthorized | ApiResult::Forbidden | ApiResult::NotFound => {
+                serde_json::to_vec(&serde_json::json!({ "error": status.canonical_reason().unwrap_or_default() }))
+            }
+        };
+
+        match body {
+            Ok(body) => Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .expect("Couldn't create the response"),
+            Err(err) => ApiResult::<()>::InternalError(Some(err.to_string())).into_response(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    async fn into_parts<T: Serialize>(result: ApiResult<T>) -> (StatusCode, String) {
+        let res = result.into_response();
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_api_result_into_response() {
+        let cases = vec![
+            (ApiResult::Ok(1), StatusCode::OK, "1"),
+            (ApiResult::Created(2), StatusCode::CREATED, "2"),
+            (ApiResult::Accepted(3), StatusCode::ACCEPTED, "3"),
+            (ApiResult::NoContent, StatusCode::NO_CONTENT, ""),
+            (
+                ApiResult::BadRequest(Some("Invalid name".to_owned())),
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"Invalid name"}"#,
+            ),
+            (
+                ApiResult::Unauthorized,
+                StatusCode::UNAUTHORIZED,
+                r#"{"error":"Unauthorized"}"#,
+            ),
+            (ApiResult::Forbidden, StatusCode::FORBIDDEN, r#"{"error":"Forbidden"}"#),
+            (ApiResult::NotFound, StatusCode::NOT_FOUND, r#"{"error":"Not Found"}"#),
+            (
+                ApiResult::Conflict(None),
+                StatusCode::CONFLICT,
+                r#"{"error":"Conflict"}"#,
+            ),
+            (
+                ApiResult::InternalError(None),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                r#"{"error":"Internal Server Error"}"#,
+            ),
+        ];
+
+        for (result, status, body) in cases {
+            assert_eq!(into_parts(result).await, (status, body.to_owned()));
+        }
+    }
+}
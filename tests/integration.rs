@@ -1,9 +1,17 @@
+// The context struct of `can_handle_pre_middleware_errors` is only checked for presence, so its field is never read.
+#![allow(dead_code)]
+
 use self::support::{into_text, serve};
 use hyper::{Body, Client, Request, Response, StatusCode};
 use routerify::prelude::RequestExt;
-use routerify::{Middleware, RequestInfo, RouteError, Router};
+use routerify::utility::middlewares::{cache_control, vary};
+use routerify::{
+    reject, reject_with, BoxedHandlerFuture, HasStatusCode, Matcher, Middleware, PreflightConfig, RequestInfo,
+    RequestServiceBuilder, Route, RouteError, RouteMatch, Router, SkipPostMiddleware, SkipPostMiddlewareScopes, State,
+};
 use std::io;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 mod support;
 
@@ -496,3 +504,3504 @@ async fn can_handle_pre_middleware_errors() {
         .unwrap();
     serve.shutdown();
 }
+
+#[tokio::test]
+async fn can_access_raw_captures() {
+    const RESPONSE_TEXT: &str = "Hello world";
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/compare/:version/:version/:name.:ext", |req| async move {
+            // The repeated param has a capture group for each segment, the param only returns the first one.
+            assert_eq!(req.param("version").unwrap(), "v1");
+            let captures = req.captures().unwrap();
+            assert_eq!(captures, ["v1", "v2", "data.tar", "gz"]);
+            let (parts, _) = req.into_parts();
+            let captures = parts.captures().unwrap();
+            assert_eq!(captures, ["v1", "v2", "data.tar", "gz"]);
+            Ok(Response::new(RESPONSE_TEXT.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/compare/v1/v2/data.tar.gz")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, RESPONSE_TEXT.to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_detect_error_response_in_post_middleware() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/ok", |_| async { Ok(Response::new("ok".into())) })
+        .get("/fail", |_| async { Err(routerify::Error::new("Error!")) })
+        .err_handler(|err: RouteError| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(err.to_string()))
+                .unwrap()
+        })
+        .middleware(Middleware::post_with_info(
+            |mut res: Response<Body>, req_info: RequestInfo| async move {
+                let val = if req_info.is_error_response() { "true" } else { "false" };
+                res.headers_mut().insert("x-error-response", val.parse().unwrap());
+                Ok(res)
+            },
+        ))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/ok").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["x-error-response"], "false");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/fail").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(resp.headers()["x-error-response"], "true");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_merge_duplicate_slashes() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .merge_slashes(true)
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Body::from(req.param("id").unwrap().to_string())))
+        })
+        .build()
+        .unwrap();
+    let srv = serve(router).await;
+    let resp = Client::new()
+        .request(srv.new_request("GET", "/users//42?q=a//b").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "42");
+    srv.shutdown();
+
+    // The duplicate slashes are kept as is when the option is disabled.
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Body::from(req.param("id").unwrap().to_string())))
+        })
+        .build()
+        .unwrap();
+    let srv = serve(router).await;
+    let resp = Client::new()
+        .request(srv.new_request("GET", "/users//42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    srv.shutdown();
+}
+
+#[tokio::test]
+async fn can_delegate_unmatched_requests_to_fallback_router() {
+    let static_router: Router<Body, routerify::Error> = Router::builder()
+        .get("/static/:file", |req| async move {
+            Ok(Response::new(Body::from(format!(
+                "static {}",
+                req.param("file").unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/api/:name", |req| async move {
+            Ok(Response::new(Body::from(format!("api {}", req.param("name").unwrap()))))
+        })
+        .fallback_to(static_router)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/x").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "api x");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/static/y").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "static y");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/unknown").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_run_root_middlewares_around_fallback_router() {
+    let static_router: Router<Body, routerify::Error> = Router::builder()
+        .get("/static/:file", |req| async move {
+            Ok(Response::new(Body::from(format!(
+                "static {}",
+                req.headers()["x-request-id"].to_str().unwrap()
+            ))))
+        })
+        .build()
+        .unwrap();
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .middleware(Middleware::pre(|mut req| async move {
+            req.headers_mut().insert("x-request-id", "42".parse().unwrap());
+            Ok(req)
+        }))
+        .middleware(Middleware::post(|mut res| async move {
+            res.headers_mut().insert("x-powered-by", "routerify".parse().unwrap());
+            Ok(res)
+        }))
+        .get("/api/users", |_| async move { Ok(Response::new(Body::from("api"))) })
+        .fallback_to(static_router)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/static/app.js").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-powered-by"], "routerify");
+    assert_eq!(into_text(resp.into_body()).await, "static 42");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/unknown").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["x-powered-by"], "routerify");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn execute_prefix_middleware_for_any_path_under_prefix() {
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter2 = counter.clone();
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .middleware(
+            Middleware::pre_for_prefix("/api", move |req| {
+                let counter = counter2.clone();
+                async move {
+                    counter.fetch_add(1, SeqCst);
+                    Ok(req)
+                }
+            })
+            .unwrap(),
+        )
+        .get("/api/anything/else", |_| async { Ok(Response::new("".into())) })
+        .get("/apis", |_| async { Ok(Response::new("".into())) })
+        .get("/other", |_| async { Ok(Response::new("".into())) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    for (path, expected_count) in [("/api/anything/else", 1), ("/other", 1), ("/apis", 1), ("/api", 2)] {
+        let _ = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(counter.load(SeqCst), expected_count, "path: {}", path);
+    }
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_serve_router_via_service_fn() {
+    use hyper::service::make_service_fn;
+    use hyper::Server;
+
+    const RESPONSE_TEXT: &str = "Hello world";
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/", |req| async move {
+            assert!(req.remote_addr().ip().is_loopback());
+            Ok(Response::new(RESPONSE_TEXT.into()))
+        })
+        .build()
+        .unwrap();
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(router.into_service_fn().unwrap()));
+    let addr = server.local_addr();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(server.with_graceful_shutdown(async {
+        rx.await.unwrap();
+    }));
+
+    let resp = Client::new()
+        .request(
+            Request::builder()
+                .method("GET")
+                .uri(format!("http://{}/", addr))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, RESPONSE_TEXT);
+    tx.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn respond_bad_request_for_malformed_path() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/:name", |_| async { Ok(Response::new("".into())) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/invalid%FF%FE").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_add_default_headers() {
+    use hyper::header::{self, HeaderMap, HeaderValue};
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/", |_| async {
+            Ok(Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .body(Body::empty())
+                .unwrap())
+        })
+        .default_headers(headers)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(resp.headers()[header::CACHE_CONTROL], "max-age=60");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn prefer_explicit_route_registered_after_any() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .any(|_| async {
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from("not found"))
+                .unwrap())
+        })
+        .get("/late", |_| async { Ok(Response::new(Body::from("late"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/late").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "late");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/unknown").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(into_text(resp.into_body()).await, "not found");
+
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_detect_secure_scheme_behind_proxy() {
+    let handler = |req: Request<Body>| async move {
+        let body = format!("{} {}", req.scheme().unwrap_or("none"), req.is_secure());
+        Ok(Response::new(Body::from(body)))
+    };
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .trust_proxy_headers(true)
+        .get("/", handler)
+        .build()
+        .unwrap();
+    let srv = serve(router).await;
+    let resp = Client::new()
+        .request(
+            srv.new_request("GET", "/")
+                .header("x-forwarded-proto", "https")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "https true");
+    srv.shutdown();
+
+    // The proxy headers are ignored unless they are trusted.
+    let router: Router<Body, routerify::Error> = Router::builder().get("/", handler).build().unwrap();
+    let srv = serve(router).await;
+    let resp = Client::new()
+        .request(
+            srv.new_request("GET", "/")
+                .header("x-forwarded-proto", "https")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "none false");
+    srv.shutdown();
+}
+
+#[tokio::test]
+async fn can_stream_request_body_through_reader() {
+    use routerify::ext::RequestBodyExt;
+    use tokio::io::AsyncReadExt;
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .post("/upload", |req| async move {
+            let mut reader = req.body_reader();
+            let mut buf = [0; 3];
+            let mut data = Vec::new();
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                data.extend_from_slice(&buf[..n]);
+            }
+            Ok(Response::new(Body::from(format!(
+                "{} {}",
+                data.len(),
+                String::from_utf8_lossy(&data)
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        for chunk in ["hello ", "streaming ", "world"] {
+            sender.send_data(chunk.into()).await.unwrap();
+        }
+    });
+
+    let resp = Client::new()
+        .request(serve.new_request("POST", "/upload").body(body).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "21 hello streaming world");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_access_route_params_in_post_middleware() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/users/:id", |_| async { Ok(Response::new(Body::empty())) })
+        .middleware(Middleware::post_with_info(
+            |mut res: Response<Body>, req_info: RequestInfo| async move {
+                let id = req_info.params().and_then(|params| params.get("id")).unwrap();
+                res.headers_mut().insert("x-user-id", id.parse().unwrap());
+                Ok(res)
+            },
+        ))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users/42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-user-id"], "42");
+    serve.shutdown();
+}
+
+#[test]
+fn reject_duplicate_data_registration() {
+    let result: routerify::Result<Router<Body, routerify::Error>> =
+        Router::builder().try_data(1u32).try_data(2u32).build();
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("u32"));
+
+    let result: routerify::Result<Router<Body, routerify::Error>> =
+        Router::builder().try_data(1u32).try_data(2u64).build();
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn can_access_glob_remainder() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/proxy/*", |req| async move {
+            let remainder = req.glob_remainder().unwrap().to_owned();
+            Ok(Response::new(remainder.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/proxy/a/b/c").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "a/b/c".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_skip_post_middleware() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/raw", |_| async move {
+            let mut resp = Response::new("raw".into());
+            resp.extensions_mut().insert(SkipPostMiddleware);
+            Ok(resp)
+        })
+        .middleware(Middleware::post(|_| async move {
+            Ok(Response::new("post middleware".into()))
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/raw").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "raw".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_time_out_stalled_request_body() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .body_read_timeout(Duration::from_millis(100))
+        .post("/upload", |req| async move {
+            let text = match hyper::body::to_bytes(req.into_body()).await {
+                Ok(_) => "completed",
+                Err(_) => "timed out",
+            };
+            Ok(Response::new(text.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let (mut sender, body) = Body::channel();
+    sender.send_data("partial".into()).await.unwrap();
+    let resp = Client::new()
+        .request(serve.new_request("POST", "/upload").body(body).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "timed out".to_owned());
+    drop(sender);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_request_body_after_delay_longer_than_body_read_timeout() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .body_read_timeout(Duration::from_millis(100))
+        .post("/upload", |req| async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let text = match hyper::body::to_bytes(req.into_body()).await {
+                Ok(body) => String::from_utf8(body.to_vec()).unwrap(),
+                Err(_) => "timed out".to_owned(),
+            };
+            Ok(Response::new(text.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        sender.send_data("payload".into()).await.unwrap();
+    });
+    let resp = Client::new()
+        .request(serve.new_request("POST", "/upload").body(body).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "payload".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_access_all_values_of_repeated_param() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/compare/:version/:version", |req| async move {
+            assert_eq!(req.param("version").unwrap(), "v1");
+            let versions = req.params().get_all("version").into_iter().cloned().collect::<Vec<_>>();
+            Ok(Response::new(versions.join(",").into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/compare/v1/v2").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "v1,v2".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_mount_cloned_router_at_multiple_paths() {
+    let api: Router<Body, routerify::Error> = Router::builder()
+        .data(String::from("api"))
+        .get("/users", |req| async move {
+            let name = req.data::<String>().unwrap().clone();
+            Ok(Response::new(name.into()))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .scope_cloned("/v1", &api)
+        .scope_cloned("/v2", &api)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    for version in ["v1", "v2"] {
+        let resp = Client::new()
+            .request(
+                serve
+                    .new_request("GET", &format!("/{}/users", version))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let resp = into_text(resp.into_body()).await;
+        assert_eq!(resp, "api".to_owned());
+    }
+    serve.shutdown();
+}
+
+#[cfg(feature = "log")]
+#[tokio::test]
+async fn can_emit_access_log() {
+    use routerify::utility::middlewares::{access_log, AccessLogConfig};
+
+    struct CapturingLogger(Mutex<Vec<String>>);
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "routerify::access_log"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .middlewares(access_log(AccessLogConfig::new().format("{method} {path} {status}")))
+        .get("/users", |_| async move { Ok(Response::new("users".into())) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(*LOGGER.0.lock().unwrap(), vec!["GET /users 200".to_owned()]);
+
+    // The placeholders in the request path must not be expanded.
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/{status}").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        *LOGGER.0.lock().unwrap(),
+        vec!["GET /users 200".to_owned(), "GET /{status} 404".to_owned()]
+    );
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_to_head_request_on_non_existent_route() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new("home".into())) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("HEAD", "/non-existent").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["content-type"], "text/plain");
+    assert_eq!(resp.headers()["content-length"], "9");
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_on_raw_path_and_decode_params() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .decode_before_match(false)
+        .get("/:name/c", |req| async move {
+            let name = req.param("name").unwrap().to_owned();
+            Ok(Response::new(name.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/a%2Fb/c").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "a/b".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_decode_last_param_individually_on_raw_path() {
+    fn files_router(decode_before_match: bool) -> Router<Body, routerify::Error> {
+        Router::builder()
+            .decode_before_match(decode_before_match)
+            .get("/files/:name", |req| async move {
+                let name = req.param("name").unwrap().to_owned();
+                Ok(Response::new(name.into()))
+            })
+            .build()
+            .unwrap()
+    }
+
+    let raw_serve = serve(files_router(false)).await;
+    let resp = Client::new()
+        .request(
+            raw_serve
+                .new_request("GET", "/files/a%2Fb")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "a/b".to_owned());
+    raw_serve.shutdown();
+
+    // The whole path is decoded by default, so the encoded slash splits the segment.
+    let decoding_serve = serve(files_router(true)).await;
+    let resp = Client::new()
+        .request(
+            decoding_serve
+                .new_request("GET", "/files/a%2Fb")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    decoding_serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_access_typed_state() {
+    struct AppState(&'static str);
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .with_state(AppState("state"))
+        .get_with_state("/", |state: State<AppState>, _| async move {
+            Ok(Response::new(state.0.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "state".to_owned());
+    serve.shutdown();
+}
+
+#[test]
+fn should_fail_to_build_without_required_state() {
+    let result: routerify::Result<Router<Body, routerify::Error>> = Router::builder()
+        .get_with_state(
+            "/",
+            |_: State<String>, _| async move { Ok(Response::new(Body::empty())) },
+        )
+        .build();
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn can_set_typed_state_after_routes() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .with_state("old".to_owned())
+        .get_with_state("/", |state: State<String>, _| async move {
+            Ok(Response::new(state.as_str().to_owned().into()))
+        })
+        .with_state("new".to_owned())
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "new".to_owned());
+    serve.shutdown();
+}
+
+#[test]
+fn should_reject_overlapping_routes_in_strict_mode() {
+    fn builder(strict: bool) -> routerify::RouterBuilder<Body, routerify::Error> {
+        Router::builder()
+            .strict(strict)
+            .get("/a/:x", |_| async move { Ok(Response::new(Body::empty())) })
+            .get("/a/b", |_| async move { Ok(Response::new(Body::empty())) })
+            .post("/a/b", |_| async move { Ok(Response::new(Body::empty())) })
+    }
+    assert!(builder(true).build().is_err());
+    assert!(builder(false).build().is_ok());
+
+    // The trailing slash is significant in the strict slash mode, so these routes never match the same request.
+    let strict_slash_builder = |strict_slash: bool| -> routerify::RouterBuilder<Body, routerify::Error> {
+        Router::builder()
+            .strict(true)
+            .strict_slash(strict_slash)
+            .get("/users", |_| async move { Ok(Response::new(Body::empty())) })
+            .get("/users/", |_| async move { Ok(Response::new(Body::empty())) })
+            .get("/posts/:id", |_| async move { Ok(Response::new(Body::empty())) })
+            .get("/posts/", |_| async move { Ok(Response::new(Body::empty())) })
+    };
+    assert!(strict_slash_builder(true).build().is_ok());
+    assert!(strict_slash_builder(false).build().is_err());
+
+    // The parameters with a different literal suffix never match the same segment.
+    let suffix_builder = |suffix: &str| -> routerify::RouterBuilder<Body, routerify::Error> {
+        Router::builder()
+            .strict(true)
+            .get("/files/:name.json", |_| async move { Ok(Response::new(Body::empty())) })
+            .get(format!("/files/:name{}", suffix), |_| async move {
+                Ok(Response::new(Body::empty()))
+            })
+    };
+    assert!(suffix_builder(".xml").build().is_ok());
+    assert!(suffix_builder(".min.json").build().is_err());
+    assert!(Router::<Body, routerify::Error>::builder()
+        .strict(true)
+        .get("/files/:name.json", |_| async move { Ok(Response::new(Body::empty())) })
+        .get(
+            "/files/report.json",
+            |_| async move { Ok(Response::new(Body::empty())) }
+        )
+        .build()
+        .is_err());
+
+    // A wildcard route which isn't a catch-all shadows the later routes.
+    assert!(Router::<Body, routerify::Error>::builder()
+        .strict(true)
+        .get("/*", |_| async move { Ok(Response::new(Body::empty())) })
+        .get("/users", |_| async move { Ok(Response::new(Body::empty())) })
+        .build()
+        .is_err());
+
+    // The catch-all routes, including the ones of the scoped routers, are expected to overlap the other routes.
+    let api: Router<Body, routerify::Error> = Router::builder()
+        .get("/users", |_| async move { Ok(Response::new(Body::empty())) })
+        .any(|_| async move { Ok(Response::new(Body::empty())) })
+        .build()
+        .unwrap();
+    assert!(Router::builder()
+        .strict(true)
+        .any(|_| async move { Ok(Response::new(Body::empty())) })
+        .get("/users", |_| async move { Ok(Response::new(Body::empty())) })
+        .scope("/api", api)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn should_count_match_candidates() {
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .middleware(Middleware::pre(|req| async move { Ok(req) }))
+        .get("/users/:id", |_| async move { Ok(Response::new(Body::empty())) })
+        .get("/users/*", |_| async move { Ok(Response::new(Body::empty())) })
+        .get("/posts", |_| async move { Ok(Response::new(Body::empty())) })
+        .build()
+        .unwrap();
+    assert_eq!(router.match_stats("/users/1").candidates, 3);
+    assert_eq!(router.match_stats("/posts").candidates, 2);
+}
+
+#[tokio::test]
+async fn can_scope_router_with_mapped_error_type() {
+    #[derive(Debug)]
+    struct MyError(String);
+    impl std::error::Error for MyError {}
+    impl std::fmt::Display for MyError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "MyError: {}", self.0)
+        }
+    }
+
+    let files: Router<Body, MyError> = Router::builder()
+        .get("/:name", |_| async move {
+            Err::<Response<Body>, _>(io::Error::other("not readable"))
+        })
+        .map_err(|e: io::Error| MyError(e.to_string()))
+        .build()
+        .unwrap();
+    let router: Router<Body, MyError> = Router::builder()
+        .scope("/files", files)
+        .err_handler(|err: RouteError| async move {
+            let my_err = err.downcast::<MyError>().unwrap();
+            Response::new(my_err.to_string().into())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/files/a.txt").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "MyError: not readable".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_move_shared_data_into_spawned_task() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<&'static str>();
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .data_shared(tx)
+        .get("/", |req| async move {
+            let tx = req
+                .data_arc::<tokio::sync::mpsc::UnboundedSender<&'static str>>()
+                .unwrap();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                tx.send("from task").unwrap();
+            });
+            Ok(Response::new("ok".into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "ok".to_owned());
+    assert_eq!(rx.recv().await, Some("from task"));
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_add_route_created_by_route_builder() {
+    let route = routerify::Route::builder("/upload")
+        .methods(vec![hyper::Method::POST])
+        .max_size(8)
+        .handler(|req| async move {
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+            Ok(Response::new(Body::from(body)))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, hyper::Error> = Router::builder().route(route).build().unwrap();
+    let serve = serve(router).await;
+    let upload = |body: &'static str| {
+        Client::new().request(serve.new_request("POST", "/upload").body(Body::from(body)).unwrap())
+    };
+    let resp = upload("small").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "small".to_owned());
+    let resp = upload("a large body").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_inherit_max_size_in_scoped_router() {
+    let api: Router<Body, routerify::Error> = Router::builder()
+        .post("/upload", |_| async move { Ok(Response::new("uploaded".into())) })
+        .build()
+        .unwrap();
+    let router: Router<Body, routerify::Error> = Router::builder().max_size(1024).scope("/api", api).build().unwrap();
+    let serve = serve(router).await;
+    let upload = |len: usize| {
+        Client::new().request(
+            serve
+                .new_request("POST", "/api/upload")
+                .body(Body::from(vec![b'a'; len]))
+                .unwrap(),
+        )
+    };
+    assert_eq!(upload(1024).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(upload(1025).await.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+    serve.shutdown();
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn can_test_router_with_test_client() {
+    use routerify::test::TestClient;
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .get("/users/:id", |req| async move {
+            let id = req.param("id").unwrap().to_owned();
+            Ok(Response::builder().header("x-user-id", id).body("user".into()).unwrap())
+        })
+        .post("/echo", |req| async move {
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            Ok(Response::new(Body::from(body)))
+        })
+        .get("/fail", |_| async move { Err(routerify::Error::new("failed")) })
+        .build()
+        .unwrap();
+    let client = TestClient::new(router).unwrap();
+
+    let resp = client.get("/users/1").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["x-user-id"], "1");
+    assert_eq!(into_text(resp.into_body()).await, "user".to_owned());
+
+    let resp = client.post("/echo", "hello").await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "hello".to_owned());
+
+    let resp = client
+        .request(
+            Request::builder()
+                .method("GET")
+                .uri("/fail")
+                .header("accept", "text/plain")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Internal Server Error: routerify::Error: failed".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn can_reject_oversized_chunked_body() {
+    let router: Router<Body, hyper::Error> = Router::builder()
+        .max_size(8)
+        .post("/upload", |req| async move {
+            let body = hyper::body::to_bytes(req.into_body()).await?;
+            Ok(Response::new(Body::from(body)))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let chunks = vec![Ok::<_, io::Error>("aaaa"), Ok("bbbb"), Ok("cccc"), Ok("dddd")];
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "/upload")
+                .body(Body::wrap_stream(futures::stream::iter(chunks)))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_take_data_from_context() {
+    #[derive(Clone)]
+    struct Token(&'static str);
+
+    let router: Router<Body, routerify::Error> = Router::builder()
+        .middleware(Middleware::pre(|req: Request<Body>| async move {
+            assert!(!req.has_context::<Token>());
+            req.set_context(Token("one-shot"));
+            assert!(req.has_context::<Token>());
+            Ok(req)
+        }))
+        .get("/", |req| async move {
+            let token = req.take_context::<Token>().unwrap();
+            assert!(!req.has_context::<Token>());
+            assert!(req.context::<Token>().is_none());
+            Ok(Response::new(token.0.into()))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "one-shot".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_mount_built_router() {
+    let plugin: Router<Body, io::Error> = Router::builder()
+        .get("/status/:id", |req| async move {
+            let id = req.param("id").unwrap().to_owned();
+            Ok(Response::new(Body::from(format!("Plugin status {}", id))))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+        .build()
+        .unwrap()
+        .mount("/plugins/stats/", plugin)
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/plugins/stats/status/1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "Plugin status 1".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_trailing_slash_strictly() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .strict_slash(true)
+        .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "User list".to_owned());
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_match_trailing_slash_strictly_regardless_of_call_order() {
+    fn build(strict_slash_first: bool) -> Router<Body, io::Error> {
+        let mut builder = Router::builder();
+        if strict_slash_first {
+            builder = builder.strict_slash(true);
+        }
+        builder = builder
+            .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+            .route(
+                Route::builder("/books")
+                    .handler(|_| async move { Ok(Response::new(Body::from("Book list"))) })
+                    .build()
+                    .unwrap(),
+            );
+        if !strict_slash_first {
+            builder = builder.strict_slash(true);
+        }
+        builder.build().unwrap()
+    }
+
+    for strict_slash_first in [true, false] {
+        let serve = serve(build(strict_slash_first)).await;
+        for (path, status) in [
+            ("/users", StatusCode::OK),
+            ("/users/", StatusCode::NOT_FOUND),
+            ("/books", StatusCode::OK),
+            ("/books/", StatusCode::NOT_FOUND),
+        ] {
+            let resp = Client::new()
+                .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), status, "{} {}", strict_slash_first, path);
+        }
+        serve.shutdown();
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .route(
+            Route::builder("/books")
+                .handler(|_| async move { Ok(Response::new(Body::from("Book list"))) })
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    for path in ["/books", "/books/"] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK, "{}", path);
+    }
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_request_with_status() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/search", |_| async move {
+            Ok(reject_with(StatusCode::TOO_MANY_REQUESTS, "Slow down"))
+        })
+        .get("/admin", |_| async move { Ok(Response::new(Body::from("Admin page"))) })
+        .middleware(Middleware::post_with_info(|res, req_info| async move {
+            if req_info.uri().path() == "/admin" && !req_info.headers().contains_key("authorization") {
+                return Ok(reject(StatusCode::UNAUTHORIZED));
+            }
+            Ok(res)
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/search").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(into_text(resp.into_body()).await, "Slow down".to_owned());
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/admin").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(into_text(resp.into_body()).await, "Unauthorized".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_set_keep_alive_header_for_http1() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .keep_alive(true)
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+        .build()
+        .unwrap();
+    let builder = RequestServiceBuilder::new(router).unwrap();
+
+    for (version, expected) in [
+        (hyper::Version::HTTP_11, Some("keep-alive")),
+        (hyper::Version::HTTP_2, None),
+    ] {
+        let mut service = builder.build(([127, 0, 0, 1], 8080).into());
+        let req = Request::builder()
+            .method("GET")
+            .uri("/")
+            .version(version)
+            .body(Body::empty())
+            .unwrap();
+        let resp = hyper::service::Service::call(&mut service, req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("connection").map(|v| v.to_str().unwrap()),
+            expected,
+            "{:?}",
+            version
+        );
+    }
+}
+
+#[tokio::test]
+async fn can_route_to_boxed_future_handler() {
+    fn plugin_handler(req: Request<Body>) -> BoxedHandlerFuture<Body, io::Error> {
+        Box::pin(async move {
+            let name = req.param("name").unwrap().to_owned();
+            Ok(Response::new(Body::from(format!("Plugin {}", name))))
+        })
+    }
+
+    type PluginHandler = Box<dyn Fn(Request<Body>) -> BoxedHandlerFuture<Body, io::Error> + Send + Sync>;
+
+    let handlers: Vec<PluginHandler> = vec![Box::new(plugin_handler)];
+    let mut builder = Router::builder();
+    for handler in handlers {
+        builder = builder.get_boxed("/plugins/:name", handler);
+    }
+    let router: Router<Body, io::Error> = builder.build().unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/plugins/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "Plugin stats".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_report_path_of_invalid_route_regex() {
+    // Too many params make the generated regex exceed the regex size limit.
+    let path = "/:a".repeat(20000);
+    let err = Router::<Body, io::Error>::builder()
+        .get(path.as_str(), |_| async move { Ok(Response::new(Body::empty())) })
+        .build()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(&format!("route path `{}`", path)), "{}", &err[..200]);
+    assert!(err.contains("generated regex: `(?s)^/([^/]+)"));
+}
+
+#[tokio::test]
+async fn can_redirect_request() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .redirect("/home", "/", StatusCode::PERMANENT_REDIRECT)
+        .redirect("/u/:id", "/users/:id", StatusCode::MOVED_PERMANENTLY)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    for (path, status, location) in [
+        ("/home", StatusCode::PERMANENT_REDIRECT, "/"),
+        ("/u/john%20doe", StatusCode::MOVED_PERMANENTLY, "/users/john%20doe"),
+    ] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), status);
+        assert_eq!(resp.headers()["location"], location);
+    }
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_add_default_charset_to_text_content_types() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .default_charset("utf-8")
+        .get("/:content_type/:subtype", |req| async move {
+            let content_type = format!(
+                "{}/{}",
+                req.param("content_type").unwrap(),
+                req.param("subtype").unwrap()
+            );
+            Ok(Response::builder()
+                .header("content-type", content_type)
+                .body(Body::empty())
+                .unwrap())
+        })
+        .get("/latin", |_| async move {
+            Ok(Response::builder()
+                .header("content-type", "text/plain; charset=iso-8859-1")
+                .body(Body::empty())
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    for (path, expected) in [
+        ("/text/html", "text/html; charset=utf-8"),
+        ("/application/json", "application/json; charset=utf-8"),
+        ("/application/octet-stream", "application/octet-stream"),
+        ("/image/png", "image/png"),
+        ("/latin", "text/plain; charset=iso-8859-1"),
+    ] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.headers()["content-type"], expected);
+    }
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_skip_post_middlewares_of_specific_scope() {
+    let files: Router<Body, io::Error> = Router::builder()
+        .get("/download", |_| async move {
+            let mut resp = Response::new(Body::from("raw"));
+            resp.extensions_mut().insert(SkipPostMiddlewareScopes::new([1]));
+            Ok(resp)
+        })
+        .middleware(Middleware::post(|mut res| async move {
+            res.headers_mut().insert("x-files", "1".parse().unwrap());
+            Ok(res)
+        }))
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(Middleware::post(|res| async move {
+            let body = into_text(res.into_body()).await;
+            Ok(Response::new(Body::from(format!("{{\"data\":\"{}\"}}", body))))
+        }))
+        .scope("/files", files)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/files/download").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-files"], "1");
+    assert_eq!(into_text(resp.into_body()).await, "raw".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_path_and_query_from_request_info() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/search", |_| async move { Ok(Response::new(Body::empty())) })
+        .middleware(Middleware::post_with_info(|_, req_info| async move {
+            let text = format!("{} {:?}", req_info.path(), req_info.query());
+            Ok(Response::new(Body::from(text)))
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/search?q=rust&page=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "/search Some(\"q=rust&page=2\")".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_get_scope_prefix_of_route() {
+    fn greeting() -> Router<Body, io::Error> {
+        Router::builder()
+            .get("/", |req| async move {
+                let prefix = format!("{:?}", req.scope_prefix());
+                Ok(Response::new(Body::from(prefix)))
+            })
+            .build()
+            .unwrap()
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |req| async move {
+            Ok(Response::new(Body::from(format!("{:?}", req.scope_prefix()))))
+        })
+        .scope("/hello", greeting())
+        .scope("/hello/:name", greeting())
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    for (path, expected) in [
+        ("/", "None"),
+        ("/hello", "Some(\"/hello\")"),
+        ("/hello/alice", "Some(\"/hello/:name\")"),
+    ] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(into_text(resp.into_body()).await, expected.to_owned());
+    }
+    serve.shutdown();
+}
+
+#[cfg(feature = "multipart")]
+#[tokio::test]
+async fn can_parse_multipart_body() {
+    use routerify::body::MultipartError;
+    use routerify::ext::RequestBodyExt;
+
+    let router: Router<Body, MultipartError> = Router::builder()
+        .post("/upload", |req| async move {
+            let mut multipart = req.multipart()?;
+            let mut parts = Vec::new();
+
+            while let Some(field) = multipart.next_field().await? {
+                let name = field.name().unwrap_or_default().to_owned();
+                let file_name = field.file_name().map(ToOwned::to_owned);
+                let text = field.text().await?;
+                parts.push(format!("{} {:?} {}", name, file_name, text));
+            }
+
+            Ok(Response::new(Body::from(parts.join("\n"))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let body = "--X-BOUNDARY\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+        My notes\r\n\
+        --X-BOUNDARY\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\n\
+        Content-Type: text/plain\r\n\r\n\
+        Hello world\r\n\
+        --X-BOUNDARY--\r\n";
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "/upload")
+                .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(
+        resp,
+        "title None My notes\nfile Some(\"notes.txt\") Hello world".to_owned()
+    );
+    serve.shutdown();
+}
+
+#[cfg(feature = "multipart")]
+#[tokio::test]
+async fn can_reject_multipart_body_over_max_size() {
+    use routerify::body::MultipartError;
+    use routerify::ext::RequestBodyExt;
+
+    let router: Router<Body, MultipartError> = Router::builder()
+        .max_size(128)
+        .post("/upload", |req| async move {
+            let mut multipart = req.multipart()?;
+            let mut count = 0;
+
+            while let Some(field) = multipart.next_field().await? {
+                field.bytes().await?;
+                count += 1;
+            }
+
+            Ok(Response::new(Body::from(count.to_string())))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let upload = |text: &str| {
+        let body = format!(
+            "--X-BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\n\r\n\
+            {}\r\n\
+            --X-BOUNDARY--\r\n",
+            text
+        );
+        Client::new().request(
+            serve
+                .new_request("POST", "/upload")
+                .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+    };
+    let resp = upload("Hello world").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "1".to_owned());
+    let resp = upload("Hello world".repeat(10).as_str()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_observe_requests_and_responses_with_hooks() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let on_request_events = events.clone();
+    let on_response_events = events.clone();
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+        .on_request(move |req_info: &RequestInfo| {
+            let event = format!("--> {} {}", req_info.method(), req_info.path());
+            on_request_events.lock().unwrap().push(event);
+        })
+        .on_response(move |req_info: &RequestInfo, res: &Response<Body>| {
+            let event = format!("<-- {} {}", req_info.path(), res.status().as_u16());
+            on_response_events.lock().unwrap().push(event);
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    for path in ["/users", "/missing"] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        into_text(resp.into_body()).await;
+    }
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "--> GET /users".to_owned(),
+            "<-- /users 200".to_owned(),
+            "--> GET /missing".to_owned(),
+            "<-- /missing 404".to_owned(),
+        ]
+    );
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_with_error_status_hint_by_default_err_handler() {
+    #[derive(Debug)]
+    struct ValidationError(String);
+    impl std::error::Error for ValidationError {}
+    impl std::fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "Invalid input: {}", self.0)
+        }
+    }
+    impl HasStatusCode for ValidationError {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::BAD_REQUEST
+        }
+    }
+
+    let router: Router<Body, ValidationError> = Router::builder()
+        .post("/users", |_| async move {
+            Err::<Response<Body>, _>(ValidationError("name".to_owned()))
+        })
+        .error_status::<ValidationError>()
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("POST", "/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = into_text(resp.into_body()).await;
+    assert_eq!(resp, "Bad Request: Invalid input: name".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_set_cookie_from_request_info_in_err_handler() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get(
+            "/",
+            |_| async move { Err::<Response<Body>, _>(io::Error::other("failed")) },
+        )
+        .err_handler_with_info(|err: RouteError, req_info: RequestInfo| async move {
+            let session = req_info.header_str("x-session").unwrap_or("anonymous").to_owned();
+            req_info
+                .response_builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("set-cookie", format!("last_error_session={}", session))
+                .body(Body::from(err.to_string()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/")
+                .header("x-session", "abc")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(resp.headers()["set-cookie"], "last_error_session=abc");
+    assert_eq!(into_text(resp.into_body()).await, "failed".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_warmup_router_before_serving() {
+    let mut router: Router<Body, io::Error> = Router::builder()
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Body::from(req.param("id").unwrap().to_owned())))
+        })
+        .build()
+        .unwrap();
+    assert!(router.warmup().is_ok());
+    // Warming up is idempotent.
+    assert!(router.warmup().is_ok());
+
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users/10").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "10".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_handle_error_of_post_middleware_with_info_by_err_handler_with_info() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+        .middleware(Middleware::post_with_info(|_res, req_info: RequestInfo| async move {
+            Err(io::Error::other(format!("failed after {}", req_info.path())))
+        }))
+        .err_handler_with_info(|err: RouteError, req_info: RequestInfo| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(format!(
+                    "{} {}: {}",
+                    req_info.method(),
+                    req_info.path(),
+                    err
+                )))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(into_text(resp.into_body()).await, "GET /: failed after /".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_order_middlewares_by_priority() {
+    fn append_pre(name: &'static str) -> Middleware<Body, io::Error> {
+        Middleware::pre(move |mut req| async move {
+            let order = format!("{}{}", req.header_str("x-order").unwrap_or_default(), name);
+            req.headers_mut().insert("x-order", order.parse().unwrap());
+            Ok(req)
+        })
+    }
+
+    fn append_post(name: &'static str) -> Middleware<Body, io::Error> {
+        Middleware::post(move |mut res| async move {
+            let order = format!("{}{}", res.headers()["x-order"].to_str().unwrap(), name);
+            res.headers_mut().insert("x-order", order.parse().unwrap());
+            Ok(res)
+        })
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware_with_priority(10, append_pre("c"))
+        .middleware(append_pre("b"))
+        .middleware_with_priority(-10, append_pre("a"))
+        .middleware_with_priority(10, append_pre("d"))
+        .middleware_with_priority(5, append_post("3"))
+        .middleware_with_priority(-5, append_post("1"))
+        .middleware(append_post("2"))
+        .get("/", |req| async move {
+            let order = req.header_str("x-order").unwrap().to_owned();
+            Ok(Response::builder()
+                .header("x-order", "")
+                .body(Body::from(order))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-order"], "123");
+    assert_eq!(into_text(resp.into_body()).await, "abcd".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_order_middlewares_by_priority_within_scope_level() {
+    fn append_pre(name: &'static str) -> Middleware<Body, io::Error> {
+        Middleware::pre(move |mut req| async move {
+            let order = format!("{}{}", req.header_str("x-order").unwrap_or_default(), name);
+            req.headers_mut().insert("x-order", order.parse().unwrap());
+            Ok(req)
+        })
+    }
+
+    let echo_order = |req: Request<Body>| async move {
+        let order = req.header_str("x-order").unwrap().to_owned();
+        Ok(Response::new(Body::from(order)))
+    };
+
+    let api: Router<Body, io::Error> = Router::builder()
+        .middleware(append_pre("t"))
+        .middleware_with_priority(-20, append_pre("s"))
+        .get("/users", echo_order)
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(append_pre("b"))
+        .scope("/api", api)
+        .middleware_with_priority(-10, append_pre("a"))
+        .get("/", echo_order)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "ab".to_owned());
+
+    // The scoped middlewares keep their position, so the priority of `s` doesn't move it before this router's ones.
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "astb".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_set_cache_control_from_cache_hint() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(cache_control())
+        .get("/catalog", |req| async move {
+            req.hint_cache(Duration::from_secs(90));
+            Ok(Response::new(Body::from("Catalog")))
+        })
+        .get("/cart", |_| async move { Ok(Response::new(Body::from("Cart"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/catalog").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["cache-control"], "max-age=90");
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/cart").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(resp.headers().get("cache-control").is_none());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_delegate_requests_to_scoped_service() {
+    let ext = hyper::service::service_fn(|req: Request<Body>| async move {
+        Ok::<_, io::Error>(Response::new(Body::from(format!("ext {}", req.uri().path()))))
+    });
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+        .scope_service("/ext", ext)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("POST", "/ext/anything").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "ext /ext/anything".to_owned());
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Home".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_delegate_unmatched_requests_to_fallback_service() {
+    let spa = hyper::service::service_fn(|_req: Request<Body>| async move {
+        Ok::<_, io::Error>(Response::new(Body::from("<!doctype html>")))
+    });
+    let router: Router<Body, io::Error> = Router::builder()
+        .get(
+            "/api/users",
+            |_| async move { Ok(Response::new(Body::from("User list"))) },
+        )
+        .fallback_service(spa)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/settings/profile")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "<!doctype html>".to_owned());
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "User list".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_add_routes_conditionally() {
+    fn debug_router() -> Router<Body, io::Error> {
+        Router::builder()
+            .get("/info", |_| async move { Ok(Response::new(Body::from("Debug info"))) })
+            .build()
+            .unwrap()
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get_if(
+            true,
+            "/enabled",
+            |_| async move { Ok(Response::new(Body::from("Enabled"))) },
+        )
+        .get_if(false, "/disabled", |_| async move {
+            Ok(Response::new(Body::from("Disabled")))
+        })
+        .scope_if(true, "/debug", debug_router())
+        .scope_if(false, "/hidden", debug_router())
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    for (path, status) in [
+        ("/enabled", StatusCode::OK),
+        ("/disabled", StatusCode::NOT_FOUND),
+        ("/debug/info", StatusCode::OK),
+        ("/hidden/info", StatusCode::NOT_FOUND),
+    ] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), status, "{}", path);
+    }
+    serve.shutdown();
+}
+
+#[test]
+fn can_match_route_without_executing_handlers() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+        .get("/users/:userId/books/:bookName", |_| async move {
+            Ok(Response::new(Body::from("Book")))
+        })
+        .build()
+        .unwrap();
+
+    let route_match = router
+        .match_route(&hyper::Method::GET, "/users/alice/books/rust%20book")
+        .unwrap();
+    assert_eq!(route_match.index, 1);
+    assert_eq!(route_match.pattern, "/users/:userId/books/:bookName");
+    assert_eq!(route_match.params.get("userId").unwrap(), "alice");
+    assert_eq!(route_match.params.get("bookName").unwrap(), "rust book");
+
+    assert!(router
+        .match_route(&hyper::Method::POST, "/users/alice/books/rust")
+        .is_none());
+    assert!(router.match_route(&hyper::Method::GET, "/unknown").is_none());
+}
+
+#[test]
+fn can_match_route_before_earlier_catch_all_route() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .any(|_| async move { Ok(Response::new(Body::from("Any"))) })
+        .get("/late", |_| async move { Ok(Response::new(Body::from("Late"))) })
+        .build()
+        .unwrap();
+
+    let route_match = router.match_route(&hyper::Method::GET, "/late").unwrap();
+    assert_eq!(route_match.index, 1);
+    assert_eq!(route_match.pattern, "/late");
+
+    let route_match = router.match_route(&hyper::Method::GET, "/other").unwrap();
+    assert_eq!(route_match.index, 0);
+    assert_eq!(route_match.pattern, "/*");
+}
+
+#[tokio::test]
+async fn can_prefix_whole_router_with_base_path() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .base_path("/api/v1")
+        .middleware(Middleware::post(|mut res| async move {
+            res.headers_mut().insert("x-api", "v1".parse().unwrap());
+            Ok(res)
+        }))
+        .get("/", |_| async move { Ok(Response::new(Body::from("Index"))) })
+        .get("/users/:id", |req| async move {
+            Ok(Response::new(Body::from(req.param("id").unwrap().to_owned())))
+        })
+        .get("/prefix", |req| async move {
+            Ok(Response::new(Body::from(req.scope_prefix().unwrap().to_owned())))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/v1/prefix").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "/api/v1".to_owned());
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/v1/users/7").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-api"], "v1");
+    assert_eq!(into_text(resp.into_body()).await, "7".to_owned());
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/api/v1").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Index".to_owned());
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users/7").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    serve.shutdown();
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn can_respond_with_json_errors_by_default() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .json_errors(true)
+        .get("/fail", |_| async move {
+            Err::<Response<Body>, _>(io::Error::other("db down"))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/missing").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        r#"{"error":"Not Found","status":404}"#.to_owned()
+    );
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/fail").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        r#"{"error":"Internal Server Error","message":"db down","status":500}"#.to_owned()
+    );
+    serve.shutdown();
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn can_extract_handler_arguments() {
+    use routerify::extract::{Path, Query};
+
+    #[derive(serde::Deserialize)]
+    struct Pagination {
+        page: u32,
+        per_page: Option<u32>,
+    }
+
+    async fn books_handler(
+        Path((user_id, shelf)): Path<(u32, String)>,
+        Query(pagination): Query<Pagination>,
+    ) -> Result<Response<Body>, io::Error> {
+        Ok(Response::new(Body::from(format!(
+            "{} {} {} {}",
+            user_id,
+            shelf,
+            pagination.page,
+            pagination.per_page.unwrap_or(10)
+        ))))
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get_ext("/users/:userId/shelves/:shelf", books_handler)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/users/7/shelves/fiction?page=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "7 fiction 2 10".to_owned());
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/users/alice/shelves/fiction?page=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/users/7/shelves/fiction?page=first")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_get_original_uri_before_normalization() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .merge_slashes(true)
+        .middleware(Middleware::pre(|mut req: Request<Body>| async move {
+            *req.uri_mut() = "/hooks/github".parse().unwrap();
+            Ok(req)
+        }))
+        .post("/hooks/github", |req| async move {
+            Ok(Response::new(Body::from(format!(
+                "{} {}",
+                req.uri(),
+                req.original_uri()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "//hooks/%67ithub?sig=abc")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "/hooks/github //hooks/%67ithub?sig=abc".to_owned()
+    );
+    serve.shutdown();
+}
+
+#[cfg(feature = "session")]
+#[tokio::test]
+async fn can_create_and_reuse_session() {
+    use routerify::utility::middlewares::{session, Session, SessionConfig};
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .middlewares(session(SessionConfig::new().max_age(Duration::from_secs(60))))
+        .get("/visit", |req| async move {
+            let session = req.context::<Session>().unwrap();
+            let visits = session.get("visits").and_then(|v| v.parse::<u32>().ok()).unwrap_or(0) + 1;
+            session.insert("visits", visits.to_string());
+            Ok(Response::new(Body::from(visits.to_string())))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/visit").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let cookie = resp.headers()["set-cookie"].to_str().unwrap().to_owned();
+    assert!(cookie.starts_with("sid="));
+    assert!(cookie.contains("Max-Age=60"));
+    assert_eq!(into_text(resp.into_body()).await, "1".to_owned());
+
+    let session_cookie = cookie.split(';').next().unwrap().to_owned();
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/visit")
+                .header("cookie", format!("theme=dark; {}", session_cookie))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(resp.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .starts_with(&session_cookie));
+    assert_eq!(into_text(resp.into_body()).await, "2".to_owned());
+    serve.shutdown();
+}
+
+#[cfg(feature = "session")]
+#[tokio::test]
+async fn can_regenerate_session_id() {
+    use routerify::utility::middlewares::{session, Session, SessionConfig};
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .middlewares(session(SessionConfig::new()))
+        .get("/login", |req| async move {
+            let session = req.context::<Session>().unwrap();
+            session.insert("user", "alice");
+            session.regenerate();
+            Ok(Response::new(Body::empty()))
+        })
+        .get("/whoami", |req| async move {
+            let session = req.context::<Session>().unwrap();
+            Ok(Response::new(Body::from(session.get("user").unwrap_or_default())))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let request = |path: &str, cookie: String| {
+        Client::new().request(
+            serve
+                .new_request("GET", path)
+                .header("cookie", cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    let session_cookie = |resp: &Response<Body>| {
+        resp.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned()
+    };
+
+    let resp = request("/login", String::new()).await.unwrap();
+    let first_id = session_cookie(&resp);
+    assert_eq!(first_id.len(), "sid=".len() + 32);
+
+    let resp = request("/login", first_id.clone()).await.unwrap();
+    let second_id = session_cookie(&resp);
+    assert_ne!(first_id, second_id);
+
+    let resp = request("/whoami", second_id).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "alice".to_owned());
+    let resp = request("/whoami", first_id).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_append_to_vary_header() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(vary(&[hyper::header::ACCEPT_ENCODING, hyper::header::ACCEPT]))
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+        .get("/negotiated", |_| async move {
+            Ok(Response::builder()
+                .header("vary", "accept")
+                .body(Body::from("Negotiated"))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["vary"], "accept-encoding, accept");
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/negotiated").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["vary"], "accept, accept-encoding");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_shared_data_in_err_handler() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .data(Arc::new(String::from("help@example.com")))
+        .get("/", |_| async move { Err(io::Error::other("Oops")) })
+        .err_handler_with_data(|err: RouteError, support_email: Option<Arc<String>>| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(format!("{}, contact: {}", err, support_email.unwrap())))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "Oops, contact: help@example.com".to_owned()
+    );
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_allow_preflight_without_options_handler() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .put(
+            "/api/users/:id",
+            |_| async move { Ok(Response::new(Body::from("Updated"))) },
+        )
+        .allow_preflight(
+            "/api/users/:id",
+            PreflightConfig::new()
+                .allow_origin("https://example.com")
+                .allow_methods(&[hyper::Method::PUT, hyper::Method::DELETE])
+                .allow_headers(&[hyper::header::CONTENT_TYPE]),
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let preflight = |origin: &str, method: &str, headers: &str| {
+        serve
+            .new_request("OPTIONS", "/api/users/1")
+            .header("origin", origin)
+            .header("access-control-request-method", method)
+            .header("access-control-request-headers", headers)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let resp = Client::new()
+        .request(preflight("https://example.com", "PUT", "Content-Type"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://example.com");
+    assert_eq!(resp.headers()["access-control-allow-methods"], "PUT, DELETE");
+    assert_eq!(resp.headers()["access-control-allow-headers"], "content-type");
+    assert_eq!(
+        resp.headers()["vary"],
+        "origin, access-control-request-method, access-control-request-headers"
+    );
+
+    for (origin, method, headers) in [
+        ("https://evil.example", "PUT", "content-type"),
+        ("https://example.com", "PATCH", "content-type"),
+        ("https://example.com", "PUT", "content-type, x-secret"),
+    ] {
+        let resp = Client::new().request(preflight(origin, method, headers)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(resp.headers().get("access-control-allow-origin").is_none());
+        assert!(resp.headers().get("access-control-allow-methods").is_none());
+        assert!(resp.headers().get("access-control-allow-headers").is_none());
+    }
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_rewrite_request_path_before_matching() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .rewrite(|path: &str| path.strip_prefix("/v0").map(|path| path.to_owned()))
+        .get("/x", |req| async move {
+            Ok(Response::new(Body::from(format!(
+                "{} {}",
+                req.uri(),
+                req.original_uri()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/v0/x?page=2").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "/x?page=2 /v0/x?page=2".to_owned());
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/x").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "/x /x".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_invalid_rewritten_path() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .rewrite(|path: &str| path.strip_prefix("/v0").map(|path| format!("{} invalid", path)))
+        .get("/x", |_| async move { Ok(Response::new(Body::from("x"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/v0/x").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_mark_route_as_deprecated() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get(
+            "/v1/users",
+            |_| async move { Ok(Response::new(Body::from("Users v1"))) },
+        )
+        .get(
+            "/v2/users",
+            |_| async move { Ok(Response::new(Body::from("Users v2"))) },
+        )
+        .deprecated_with_sunset("/v1/users", "Use /v2/users instead", "Sat, 31 Dec 2022 23:59:59 GMT")
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/v1/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["deprecation"], "true");
+    assert!(resp.headers().get("warning").is_none());
+    assert_eq!(resp.headers()["sunset"], "Sat, 31 Dec 2022 23:59:59 GMT");
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/v2/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(resp.headers().get("deprecation").is_none());
+    assert!(resp.headers().get("sunset").is_none());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_mark_route_as_deprecated_before_strict_slash() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .deprecated("/v1/users", "Use /v2/users instead")
+        .get(
+            "/v1/users",
+            |_| async move { Ok(Response::new(Body::from("Users v1"))) },
+        )
+        .strict_slash(true)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/v1/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["deprecation"], "true");
+    serve.shutdown();
+}
+
+#[test]
+fn can_match_same_routes_with_regex_set_and_trie_matchers() {
+    fn build(matcher: Matcher) -> Router<Body, io::Error> {
+        let mut builder = Router::builder()
+            .matcher(matcher)
+            .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+            .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+            .get("/users/me", |_| async move { Ok(Response::new(Body::from("Me"))) })
+            .post(
+                "/users/me",
+                |_| async move { Ok(Response::new(Body::from("Update me"))) },
+            )
+            .get("/files/*", |_| async move { Ok(Response::new(Body::from("File"))) })
+            .scope(
+                "/api",
+                Router::builder()
+                    .get("/status", |_| async move { Ok(Response::new(Body::from("Status"))) })
+                    .get("/a.b", |_| async move { Ok(Response::new(Body::from("Dotted"))) })
+                    .build()
+                    .unwrap(),
+            );
+        for idx in 0..500 {
+            builder = builder.get(format!("/items/{}", idx), |_| async move {
+                Ok(Response::new(Body::from("Item")))
+            });
+        }
+        let mut router = builder.build().unwrap();
+        router.warmup().unwrap();
+        router
+    }
+
+    let regex_set_router = build(Matcher::RegexSet);
+    let trie_router = build(Matcher::Trie);
+
+    let paths = [
+        "/",
+        "/users/1",
+        "/users/me",
+        "/users/me/",
+        "/users//me",
+        "/files/a/b.txt",
+        "/api/status",
+        "/api/a.b",
+        "/api/axb",
+        "/items/0",
+        "/items/499",
+        "/items/500",
+        "/items/42/",
+        "/unknown",
+        "",
+    ];
+    for method in [hyper::Method::GET, hyper::Method::POST] {
+        for path in paths {
+            let expected = regex_set_router.match_route(&method, path);
+            let actual = trie_router.match_route(&method, path);
+            let summary = |m: &RouteMatch| {
+                let mut params = m.params.iter().collect::<Vec<_>>();
+                params.sort();
+                (m.index, m.pattern.clone(), format!("{:?}", params))
+            };
+            assert_eq!(
+                expected.as_ref().map(summary),
+                actual.as_ref().map(summary),
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+
+    assert_eq!(
+        trie_router
+            .match_route(&hyper::Method::GET, "/users/me")
+            .unwrap()
+            .pattern,
+        "/users/:id"
+    );
+    assert_eq!(
+        trie_router
+            .match_route(&hyper::Method::POST, "/users/me")
+            .unwrap()
+            .pattern,
+        "/users/me"
+    );
+    assert_eq!(
+        trie_router
+            .match_route(&hyper::Method::GET, "/items/499")
+            .unwrap()
+            .pattern,
+        "/items/499"
+    );
+}
+
+#[tokio::test]
+async fn can_share_data_by_name() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .named_data("primary", String::from("postgres://primary"))
+        .named_data("replica", String::from("postgres://replica"))
+        .get("/", |req| async move {
+            Ok(Response::new(Body::from(format!(
+                "{} {} {:?}",
+                req.named_data::<String>("primary").unwrap(),
+                req.named_data::<String>("replica").unwrap(),
+                req.data::<String>()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        into_text(resp.into_body()).await,
+        "postgres://primary postgres://replica None".to_owned()
+    );
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_scope_router_after_scoping_its_copy() {
+    let api_router: Router<Body, io::Error> = Router::builder()
+        .data(String::from("api"))
+        .get("/name", |req| async move {
+            Ok(Response::new(Body::from(req.data::<String>().unwrap().clone())))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .scope_cloned("/v1", &api_router)
+        .scope("/v2", api_router)
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    for path in ["/v1/name", "/v2/name"] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(into_text(resp.into_body()).await, "api".to_owned());
+    }
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_with_stream_body() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move {
+            let chunks = ["one", "two", "three"].map(|chunk| Ok::<_, io::Error>(hyper::body::Bytes::from(chunk)));
+            Ok(Response::new(routerify::body::stream(futures::stream::iter(chunks))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "onetwothree".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_scope_at_root_with_own_err_handler() {
+    let billing_router: Router<Body, io::Error> = Router::builder()
+        .get("/invoices", |_| async move { Err(io::Error::other("db down")) })
+        .err_handler(|err: RouteError| async move {
+            Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(Body::from(format!("billing: {}", err)))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/users", |_| async move { Err(io::Error::other("db down")) })
+        .scope_at_root(billing_router)
+        .err_handler(|err: RouteError| async move {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(format!("root: {}", err)))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/invoices").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(into_text(resp.into_body()).await, "billing: db down".to_owned());
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(into_text(resp.into_body()).await, "root: db down".to_owned());
+    serve.shutdown();
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn can_parse_urlencoded_form_body() {
+    use routerify::body::BodyError;
+    use routerify::ext::RequestBodyExt;
+
+    #[derive(serde::Deserialize)]
+    struct Login {
+        username: String,
+        remember: bool,
+    }
+
+    let router: Router<Body, BodyError> = Router::builder()
+        .error_status::<BodyError>()
+        .post("/login", |req| async move {
+            let login: Login = req.body_form().await?;
+            Ok(Response::new(Body::from(format!(
+                "{} {}",
+                login.username, login.remember
+            ))))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "/login")
+                .header("content-type", "application/x-www-form-urlencoded; charset=utf-8")
+                .body(Body::from("username=alice+smith&remember=true"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "alice smith true".to_owned());
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("POST", "/login")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"username":"alice","remember":true}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_sniff_content_type_of_string_responses() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .sniff_content_type(true)
+        .get(
+            "/html",
+            |_| async move { Ok(Response::new(Body::from("  <h1>Home</h1>"))) },
+        )
+        .get("/text", |_| async move { Ok(Response::new(Body::from("Hello world"))) })
+        .get("/binary", |_| async move {
+            Ok(Response::new(Body::from(vec![0xffu8, 0xfe, 0x00])))
+        })
+        .get("/json", |_| async move {
+            Ok(Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from("<not sniffed>"))
+                .unwrap())
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    for (path, content_type) in [
+        ("/html", Some("text/html; charset=utf-8")),
+        ("/text", Some("text/plain; charset=utf-8")),
+        ("/binary", None),
+        ("/json", Some("application/json")),
+    ] {
+        let resp = Client::new()
+            .request(serve.new_request("GET", path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.headers().get("content-type").map(|val| val.to_str().unwrap()),
+            content_type,
+            "{}",
+            path
+        );
+    }
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/html").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-length"], "15");
+    assert_eq!(into_text(resp.into_body()).await, "  <h1>Home</h1>".to_owned());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_observe_client_disconnect_in_handler() {
+    use tokio::io::AsyncWriteExt;
+
+    struct CancelGuard(Arc<Mutex<bool>>);
+
+    impl Drop for CancelGuard {
+        fn drop(&mut self) {
+            *self.0.lock().unwrap() = true;
+        }
+    }
+
+    let token = Arc::new(Mutex::new(None));
+    let cancelled = Arc::new(Mutex::new(false));
+    let (token2, cancelled2) = (token.clone(), cancelled.clone());
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/slow", move |req| {
+            let (token, cancelled) = (token2.clone(), cancelled2.clone());
+            async move {
+                *token.lock().unwrap() = Some(req.disconnect_token());
+                let _guard = CancelGuard(cancelled);
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(Response::new(Body::from("Too late")))
+            }
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let mut stream = tokio::net::TcpStream::connect(serve.addr()).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    while token.lock().unwrap().is_none() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let token = token.lock().unwrap().clone().unwrap();
+    assert!(!token.is_disconnected());
+    drop(stream);
+
+    for _ in 0..200 {
+        if token.is_disconnected() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(token.is_disconnected());
+    assert!(*cancelled.lock().unwrap());
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_inspect_builder_mid_chain() {
+    let counts = Arc::new(Mutex::new(Vec::new()));
+    let (counts2, counts3) = (counts.clone(), counts.clone());
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+        .inspect(move |snapshot| counts2.lock().unwrap().push(snapshot.route_count()))
+        .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+        .post("/users", |_| async move { Ok(Response::new(Body::from("Created"))) })
+        .middleware(Middleware::pre(|req| async move { Ok(req) }))
+        .inspect(move |snapshot| {
+            counts3.lock().unwrap().push(snapshot.route_count());
+            assert_eq!(snapshot.pre_middleware_count(), 1);
+            assert_eq!(
+                snapshot.routes().map(|(path, _)| path).collect::<Vec<_>>(),
+                vec!["/", "/users", "/users"]
+            );
+        })
+        .build()
+        .unwrap();
+    assert_eq!(*counts.lock().unwrap(), vec![1, 3]);
+
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Users");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_customize_payload_too_large_response() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .max_size(1024)
+        .post("/upload", |req| async move {
+            let body = hyper::body::to_bytes(req.into_body()).await.map_err(io::Error::other)?;
+            Ok(Response::new(Body::from(format!("Uploaded {} bytes", body.len()))))
+        })
+        .on_payload_too_large(|req_info: RequestInfo| async move {
+            Response::builder()
+                .status(StatusCode::PAYLOAD_TOO_LARGE)
+                .body(Body::from(format!("Too large for {}", req_info.uri().path())))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let upload = |body: Body| Client::new().request(serve.new_request("POST", "/upload").body(body).unwrap());
+
+    let resp = upload(Body::from(vec![b'a'; 1024])).await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Uploaded 1024 bytes");
+
+    let resp = upload(Body::from(vec![b'a'; 1025])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(into_text(resp.into_body()).await, "Too large for /upload");
+
+    // A chunked body doesn't declare its length, so it's rejected while the handler reads it.
+    let chunks = futures::stream::iter(vec![Ok::<_, io::Error>(vec![b'a'; 1025])]);
+    let resp = upload(Body::wrap_stream(chunks)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(into_text(resp.into_body()).await, "Too large for /upload");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_customize_bad_request_response() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/:name", |_| async { Ok(Response::new(Body::from("Found"))) })
+        .on_bad_request(|req_info: RequestInfo| async move {
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!("Malformed path: {}", req_info.uri().path())))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/invalid%FF").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(into_text(resp.into_body()).await, "Malformed path: /invalid%FF");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_enforce_permissions_by_route_tags() {
+    let admin: Router<Body, io::Error> = Router::builder()
+        .get_tagged("/stats", &["admin"], |_| async move {
+            Ok(Response::new(Body::from("Stats")))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(Middleware::pre(|req| async move {
+            let is_admin = req.headers().get("x-role").is_some_and(|role| role == "admin");
+            if req.route_tags().iter().any(|tag| tag == "admin") && !is_admin {
+                return Err(io::Error::other("Forbidden"));
+            }
+            Ok(req)
+        }))
+        .get_tagged("/users", &["admin", "audit"], |req| async move {
+            Ok(Response::new(Body::from(req.route_tags().join(","))))
+        })
+        .get("/", |req| async move {
+            assert!(req.route_tags().is_empty());
+            Ok(Response::new(Body::from("Home")))
+        })
+        .scope("/admin", admin)
+        .err_handler(|err: routerify::RouteError| async move {
+            Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Body::from(err.to_string()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let request = |path: &str, role: &str| {
+        Client::new().request(
+            serve
+                .new_request("GET", path)
+                .header("x-role", role)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let resp = request("/", "guest").await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Home");
+
+    let resp = request("/users", "guest").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = request("/users", "admin").await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "admin,audit");
+
+    let resp = request("/admin/stats", "guest").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = request("/admin/stats", "admin").await.unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "Stats");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_decoded_and_raw_params_consistently() {
+    fn describe(params: &routerify::RouteParams) -> String {
+        format!(
+            "{}|{}",
+            params.get_decoded("name").unwrap(),
+            params.get_raw("name").unwrap()
+        )
+    }
+
+    for decode_before_match in [true, false] {
+        let router: Router<Body, io::Error> = Router::builder()
+            .decode_before_match(decode_before_match)
+            .get("/files/:name", |req| async move {
+                assert_eq!(req.param("name"), req.params().get_decoded("name"));
+                assert_eq!(req.param_raw("name"), req.params().get_raw("name"));
+                Ok(Response::new(Body::from(describe(req.params()))))
+            })
+            .get("/broken/:name", |_| async move { Err(io::Error::other("Broken")) })
+            .middleware(Middleware::post_with_info(|mut resp, req_info| async move {
+                let params = describe(req_info.params().unwrap());
+                resp.headers_mut().insert("x-params", params.parse().unwrap());
+                Ok(resp)
+            }))
+            .err_handler_with_info(|_, req_info| async move {
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from(describe(req_info.params().unwrap())))
+                    .unwrap()
+            })
+            .build()
+            .unwrap();
+        let serve = serve(router).await;
+
+        let resp = Client::new()
+            .request(
+                serve
+                    .new_request("GET", "/files/my%20notes")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.headers()["x-params"], "my notes|my%20notes");
+        assert_eq!(into_text(resp.into_body()).await, "my notes|my%20notes");
+
+        let resp = Client::new()
+            .request(
+                serve
+                    .new_request("GET", "/broken/caf%C3%A9")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(resp.headers()["x-params"], "café|caf%C3%A9");
+        assert_eq!(into_text(resp.into_body()).await, "café|caf%C3%A9");
+        serve.shutdown();
+    }
+
+    let mut params = routerify::RouteParams::new();
+    params.set("name", "notes");
+    assert_eq!(params.get_raw("name"), params.get_decoded("name"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn can_serve_over_unix_domain_socket() {
+    use hyper::server::{accept, Server};
+    use routerify::{RouterService, UNSPECIFIED_REMOTE_ADDR};
+    use tokio::net::{UnixListener, UnixStream};
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/", |req| async move {
+            Ok(Response::new(Body::from(req.remote_addr().to_string())))
+        })
+        .build()
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("routerify-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let incoming = accept::poll_fn(move |cx| listener.poll_accept(cx).map(|res| Some(res.map(|(stream, _)| stream))));
+    let server = Server::builder(incoming).serve(RouterService::new(router).unwrap());
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(server.with_graceful_shutdown(async move {
+        rx.await.ok();
+    }));
+
+    let stream = UnixStream::connect(&path).await.unwrap();
+    let (mut sender, conn) = hyper::client::conn::handshake(stream).await.unwrap();
+    tokio::spawn(conn);
+    let resp = sender
+        .send_request(Request::builder().uri("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, UNSPECIFIED_REMOTE_ADDR.to_string());
+
+    tx.send(()).unwrap();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn can_parse_authorization_credentials() {
+    let req = |auth: Option<&str>| {
+        let mut builder = Request::builder().uri("/");
+        if let Some(auth) = auth {
+            builder = builder.header("authorization", auth);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+
+    assert_eq!(req(Some("Bearer abc.def-ghi")).bearer_token(), Some("abc.def-ghi"));
+    assert_eq!(req(Some("bearer  abc")).bearer_token(), Some("abc"));
+    assert_eq!(req(Some("Bearer abc")).basic_auth(), None);
+
+    let (req_parts, _) = req(Some("Basic YWxhZGRpbjpvcGVuOnNlc2FtZQ==")).into_parts();
+    assert_eq!(
+        req_parts.basic_auth(),
+        Some(("aladdin".to_owned(), "open:sesame".to_owned()))
+    );
+    assert_eq!(req_parts.bearer_token(), None);
+
+    for auth in [
+        None,
+        Some("Bearer"),
+        Some("Bearer "),
+        Some("Bearer a b"),
+        Some("Token abc"),
+    ] {
+        assert_eq!(req(auth).bearer_token(), None, "{:?}", auth);
+    }
+    for auth in [
+        None,
+        Some("Basic"),
+        Some("Basic not*base64"),
+        Some("Basic YWxhZGRpbg=="),
+    ] {
+        assert_eq!(req(auth).basic_auth(), None, "{:?}", auth);
+    }
+}
+
+#[tokio::test]
+async fn can_sign_response_body_in_post_middleware() {
+    use hyper::header::HeaderValue;
+    use routerify::utility::response::with_signature;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    fn sign(path: &str, body: &[u8]) -> HeaderValue {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(b"secret");
+        hasher.write(path.as_bytes());
+        hasher.write(body);
+        HeaderValue::from(hasher.finish())
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/report", |_| async move {
+            let chunks = futures::stream::iter(vec![Ok::<_, io::Error>("Monthly "), Ok("report")]);
+            Ok(Response::new(Body::wrap_stream(chunks)))
+        })
+        .middleware(Middleware::post_with_info(|res, req_info| async move {
+            with_signature(res, "x-signature", |body| sign(req_info.uri().path(), body))
+                .await
+                .map_err(io::Error::other)
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/report").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let signature = resp.headers()["x-signature"].clone();
+    let body = into_text(resp.into_body()).await;
+    assert_eq!(body, "Monthly report");
+    assert_eq!(signature, sign("/report", body.as_bytes()));
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_reject_too_long_request_path() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .max_path_length(20)
+        .max_path_segments(3)
+        .get(
+            "/users/:userId",
+            |_| async move { Ok(Response::new(Body::from("User"))) },
+        )
+        .get("/*", |_| async move { Ok(Response::new(Body::from("Fallback"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/users/1").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "User");
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/users/1234567890abcdef")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/a/b/c/d").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_scope_router_with_additional_middleware() {
+    let admin: Router<Body, io::Error> = Router::builder()
+        .get("/stats", |req| async move {
+            let user = req.headers().get("x-user").map(|v| v.to_str().unwrap().to_owned());
+            Ok(Response::new(Body::from(format!(
+                "Stats for {}",
+                user.unwrap_or_default()
+            ))))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        .get(
+            "/stats",
+            |_| async move { Ok(Response::new(Body::from("Public stats"))) },
+        )
+        .scope_with_middleware(
+            "/admin",
+            vec![
+                Middleware::pre(|mut req| async move {
+                    req.headers_mut().insert("x-user", "admin".parse().unwrap());
+                    Ok(req)
+                }),
+                Middleware::post(|mut res| async move {
+                    res.headers_mut().insert("x-scope", "admin".parse().unwrap());
+                    Ok(res)
+                }),
+            ],
+            admin,
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/admin/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-scope"], "admin");
+    assert_eq!(into_text(resp.into_body()).await, "Stats for admin");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(resp.headers().get("x-scope").is_none());
+    assert_eq!(into_text(resp.into_body()).await, "Public stats");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_wrap_scope_with_prioritized_middleware() {
+    let admin: Router<Body, io::Error> = Router::builder()
+        .middleware_with_priority(
+            -10,
+            Middleware::pre(|mut req| async move {
+                let user = req.headers().get("x-user").unwrap().to_str().unwrap().to_owned();
+                req.headers_mut()
+                    .insert("x-user", format!("{} (checked)", user).parse().unwrap());
+                Ok(req)
+            }),
+        )
+        .middleware_with_priority(
+            10,
+            Middleware::post(|mut res| async move {
+                res.headers_mut().insert("x-scope", "inner".parse().unwrap());
+                Ok(res)
+            }),
+        )
+        .get("/stats", |req| async move {
+            let user = req.headers()["x-user"].to_str().unwrap().to_owned();
+            Ok(Response::new(Body::from(user)))
+        })
+        .build()
+        .unwrap();
+    let router: Router<Body, io::Error> = Router::builder()
+        // It runs before the added middlewares although the scoped router has a lower priority.
+        .middleware(Middleware::pre(|mut req| async move {
+            req.headers_mut().insert("x-user", "root:".parse().unwrap());
+            Ok(req)
+        }))
+        .scope_with_middleware(
+            "/admin",
+            vec![
+                Middleware::pre(|mut req| async move {
+                    let user = format!("{}admin", req.header_str("x-user").unwrap_or_default());
+                    req.headers_mut().insert("x-user", user.parse().unwrap());
+                    Ok(req)
+                }),
+                Middleware::post(|mut res| async move {
+                    res.headers_mut().insert("x-scope", "outer".parse().unwrap());
+                    Ok(res)
+                }),
+            ],
+            admin,
+        )
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/admin/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-scope"], "outer");
+    assert_eq!(into_text(resp.into_body()).await, "root:admin (checked)");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_respond_not_modified_to_conditional_request() {
+    use hyper::header::{self, HeaderValue};
+    use routerify::not_modified;
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/report", |req| async move {
+            let etag = HeaderValue::from_static("\"v1\"");
+            let mut res = if req.headers().get(header::IF_NONE_MATCH) == Some(&etag) {
+                not_modified()
+            } else {
+                Response::new(Body::from("Monthly report"))
+            };
+            res.headers_mut().insert(header::ETAG, etag);
+            res.headers_mut()
+                .insert(header::CACHE_CONTROL, HeaderValue::from_static("max-age=60"));
+            Ok(res)
+        })
+        .get("/manual", |_| async move {
+            Ok(Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::empty())
+                .unwrap())
+        })
+        .middleware(Middleware::post(|mut res| async move {
+            res.headers_mut()
+                .entry(header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static("text/plain"));
+            Ok(res)
+        }))
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(
+            serve
+                .new_request("GET", "/report")
+                .header(header::IF_NONE_MATCH, "\"v1\"")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()[header::ETAG], "\"v1\"");
+    assert_eq!(resp.headers()[header::CACHE_CONTROL], "max-age=60");
+    assert!(resp.headers().get(header::CONTENT_TYPE).is_none());
+    assert_eq!(into_text(resp.into_body()).await, "");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/report").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/plain");
+    assert_eq!(into_text(resp.into_body()).await, "Monthly report");
+
+    // A `304` response built by the handler itself keeps its headers.
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/manual").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+    serve.shutdown();
+}
+
+#[test]
+fn can_show_middleware_names_in_debug_output() {
+    let auth: Middleware<Body, io::Error> = Middleware::pre_named("auth", |req| async move { Ok(req) });
+    match auth {
+        Middleware::Pre(ref middleware) => {
+            assert_eq!(middleware.name(), Some("auth"));
+            assert!(format!("{:?}", middleware).contains("\"auth\""));
+        }
+        Middleware::Post(_) => unreachable!(),
+    }
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .middleware(auth)
+        .middleware(Middleware::post_named("logger", |res| async move { Ok(res) }))
+        .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+        .build()
+        .unwrap();
+    let summary = format!("{:?}", router);
+    assert!(summary.contains("\"auth\""));
+    assert!(summary.contains("\"logger\""));
+}
+
+#[tokio::test]
+async fn can_time_out_whole_request_pipeline() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .request_timeout(Duration::from_millis(50))
+        .middleware(Middleware::pre(|req| async move {
+            if req.uri().path() == "/slow" {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Ok(req)
+        }))
+        .get("/slow", |_| async move { Ok(Response::new(Body::from("Slow page"))) })
+        .get("/fast", |_| async move { Ok(Response::new(Body::from("Fast page"))) })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/slow").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/fast").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(into_text(resp.into_body()).await, "Fast page");
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_root_data_in_err_handler_outside_route() {
+    struct AppName(&'static str);
+
+    let router: Router<Body, io::Error> = Router::builder()
+        .data(AppName("Billing"))
+        .request_timeout(Duration::from_millis(50))
+        .get("/slow", |_| async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(Response::new(Body::from("Slow page")))
+        })
+        .err_handler_with_info(|err: RouteError, req_info: RequestInfo| async move {
+            let app_name = req_info.data::<AppName>().map(|name| name.0).unwrap_or("Unknown");
+            Response::builder()
+                .status(StatusCode::GATEWAY_TIMEOUT)
+                .body(Body::from(format!("{}: {}", app_name, err)))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/slow").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(into_text(resp.into_body()).await.starts_with("Billing: "));
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_build_default_responses_with_stream_body_factory() {
+    use hyper::body::{Bytes, HttpBody};
+    use hyper::header::{self, HeaderMap};
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    // A body of an unknown size which isn't a `hyper::Body`, so the router can't compute its length.
+    struct ChunkedBody(VecDeque<Bytes>);
+
+    impl HttpBody for ChunkedBody {
+        type Data = Bytes;
+        type Error = io::Error;
+
+        fn poll_data(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Result<Bytes, io::Error>>> {
+            Poll::Ready(self.0.pop_front().map(Ok))
+        }
+
+        fn poll_trailers(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, io::Error>> {
+            Poll::Ready(Ok(None))
+        }
+    }
+
+    let router: Router<ChunkedBody, io::Error> = Router::builder()
+        .get("/error", |_| async move { Err(io::Error::other("Broken")) })
+        .default_response_factory(|status, text| {
+            let chunks = vec![Bytes::from("<h1>"), Bytes::from(text), Bytes::from("</h1>")];
+            Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, "text/html")
+                .body(ChunkedBody(chunks.into()))
+                .unwrap()
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/missing").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/html");
+    assert!(resp.headers().get(header::CONTENT_LENGTH).is_none());
+    assert_eq!(into_text(resp.into_body()).await, "<h1>Not Found</h1>");
+
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/error").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/html");
+    assert!(into_text(resp.into_body()).await.ends_with("Broken</h1>"));
+    serve.shutdown();
+}
+
+#[tokio::test]
+async fn can_read_raw_params_split_by_encoded_slash() {
+    let router: Router<Body, io::Error> = Router::builder()
+        .get("/a/:x/*", |req| async move {
+            let params = req.params();
+            let text = format!(
+                "{}|{}|{}|{}",
+                params.get_decoded("x").unwrap(),
+                params.get_raw("x").unwrap(),
+                params.get_decoded("*").unwrap(),
+                params.get_raw("*").unwrap()
+            );
+            Ok(Response::new(Body::from(text)))
+        })
+        .build()
+        .unwrap();
+    let serve = serve(router).await;
+    let resp = Client::new()
+        .request(serve.new_request("GET", "/a/p%2Fq/r").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(into_text(resp.into_body()).await, "p|p|q/r/|q/r/");
+    serve.shutdown();
+}